@@ -0,0 +1,27 @@
+#ifndef LANGBOOK_SDB_H
+#define LANGBOOK_SDB_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct SdbDatabase SdbDatabase;
+
+SdbDatabase *sdb_open_file(const char *path);
+SdbDatabase *sdb_open_buffer(const unsigned char *data, size_t length);
+void sdb_close(SdbDatabase *database);
+
+size_t sdb_alphabet_count(const SdbDatabase *database);
+size_t sdb_acceptation_count(const SdbDatabase *database);
+size_t sdb_acceptation_concept(const SdbDatabase *database, size_t acceptation);
+char *sdb_acceptation_text(const SdbDatabase *database, size_t acceptation, size_t alphabet);
+char *sdb_concept_text(const SdbDatabase *database, size_t concept);
+void sdb_free_string(char *text);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
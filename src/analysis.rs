@@ -0,0 +1,422 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::Hash;
+use crate::sdb::{Alphabet, LanguageCode, SdbReadResult, Section};
+
+/// Number of bits needed to encode the given values if each of them were encoded with an ideal
+/// code built from their frequencies, this is, the Shannon entropy multiplied by the number of
+/// values.
+fn entropy_bits<T: Eq + Hash>(values: impl Iterator<Item = T>) -> f64 {
+    let mut frequencies: HashMap<T, usize> = HashMap::new();
+    let mut count = 0usize;
+    for value in values {
+        *frequencies.entry(value).or_insert(0) += 1;
+        count += 1;
+    }
+
+    let total = count as f64;
+    frequencies.values().map(|frequency| {
+        let frequency = *frequency as f64;
+        frequency * (total / frequency).log2()
+    }).fold(0.0, |sum, bits| sum + bits)
+}
+
+/// Comparison between the space used by a section and the space it would need according to
+/// the distribution of its values.
+pub struct SectionAnalysis {
+    pub section: Section,
+
+    /// Bits used by the section in the file, including its tables.
+    pub actual_bits: usize,
+
+    /// Sum of the entropy of each kind of value stored in the section, taking each value
+    /// independently. This ignores the order of the values, which the format takes advantage of
+    /// in some sections, like the sorted sets of acceptations, so it is only an approximation.
+    pub entropy_bits: f64
+}
+
+impl SectionAnalysis {
+    /// Ratio between the entropy and the actual size. Values close to 1 mean that there is little
+    /// room for improvement.
+    pub fn efficiency(&self) -> f64 {
+        if self.actual_bits == 0 {
+            1.0
+        }
+        else {
+            self.entropy_bits / self.actual_bits as f64
+        }
+    }
+}
+
+fn section_entropy_bits(result: &SdbReadResult, section: Section) -> f64 {
+    match section {
+        Section::SymbolArrays => {
+            entropy_bits(result.symbol_arrays.iter().map(|text| text.chars().count()))
+                + entropy_bits(result.symbol_arrays.iter().flat_map(str::chars))
+        },
+        Section::Languages => {
+            entropy_bits(result.languages.iter().map(|language| language.code.code))
+                + entropy_bits(result.languages.iter().map(|language| language.number_of_alphabets))
+        },
+        Section::Conversions => {
+            let pairs = || result.conversions.iter().flat_map(|conversion| conversion.pairs.iter());
+            entropy_bits(result.conversions.iter().map(|conversion| (conversion.source, conversion.target)))
+                + entropy_bits(result.conversions.iter().map(|conversion| conversion.pairs.len()))
+                + entropy_bits(pairs().map(|(source, _)| source.index))
+                + entropy_bits(pairs().map(|(_, target)| target.index))
+        },
+        Section::Correlations => {
+            let entries = || result.correlations.iter().flat_map(|correlation| correlation.iter());
+            entropy_bits(result.correlations.iter().map(BTreeMap::len))
+                + entropy_bits(entries().map(|(alphabet, _)| *alphabet))
+                + entropy_bits(entries().map(|(_, symbol_array)| symbol_array.index))
+        },
+        Section::CorrelationArrays => {
+            entropy_bits(result.correlation_arrays.iter().map(Vec::len))
+                + entropy_bits(result.correlation_arrays.iter().flatten().map(|correlation| correlation.index))
+        },
+        Section::Acceptations => {
+            entropy_bits(result.acceptations.iter().map(|acc| acc.concept))
+                + entropy_bits(result.acceptations.iter().map(|acc| acc.correlation_array_index.index))
+        },
+        Section::Definitions => {
+            let definitions = || result.definitions.values();
+            entropy_bits(definitions().map(|definition| definition.base_concept))
+                + entropy_bits(result.definitions.keys())
+                + entropy_bits(definitions().map(|definition| definition.complements.len()))
+                + entropy_bits(definitions().flat_map(|definition| definition.complements.iter()))
+        }
+    }
+}
+
+/// Compares the size of each section decoded against the entropy of its values.
+pub fn analyze(result: &SdbReadResult) -> Vec<SectionAnalysis> {
+    result.section_bits.iter().map(|(section, bits)| SectionAnalysis {
+        section: *section,
+        actual_bits: *bits,
+        entropy_bits: section_entropy_bits(result, *section)
+    }).collect()
+}
+
+/// Entries that are not referenced from any other section, by index.
+pub struct Orphans {
+    /// Symbol arrays not used by any correlation or conversion.
+    pub symbol_arrays: Vec<usize>,
+
+    /// Correlations not used by any correlation array.
+    pub correlations: Vec<usize>,
+
+    /// Correlation arrays not used by any acceptation. Sentences are not decoded, so correlation
+    /// arrays only used by them are also reported here.
+    pub correlation_arrays: Vec<usize>,
+
+    /// Concepts up to the maximum one that are not used by any acceptation or definition.
+    /// Concepts naming languages and alphabets are not decoded, so they are also reported here.
+    pub concepts: Vec<usize>
+}
+
+impl Orphans {
+    pub fn is_empty(&self) -> bool {
+        self.symbol_arrays.is_empty() && self.correlations.is_empty() && self.correlation_arrays.is_empty() && self.concepts.is_empty()
+    }
+}
+
+fn unused_indexes(count: usize, used: impl Iterator<Item = usize>) -> Vec<usize> {
+    let mut is_used = vec![false; count];
+    for index in used {
+        if let Some(entry) = is_used.get_mut(index) {
+            *entry = true;
+        }
+    }
+
+    (0..count).filter(|index| !is_used[*index]).collect()
+}
+
+/// Finds all entries that could be removed without affecting any acceptation or conversion.
+/// Only direct references are considered, so an entry only used by other orphans is not reported.
+pub fn find_orphans(result: &SdbReadResult) -> Orphans {
+    let correlation_symbol_arrays = result.correlations.iter().flat_map(|correlation| correlation.values().map(|symbol_array| symbol_array.index));
+    let conversion_symbol_arrays = result.conversions.iter().flat_map(|conversion| conversion.pairs.iter().flat_map(|(source, target)| [source.index, target.index]));
+    let acceptation_concepts = result.acceptations.iter().map(|acc| acc.concept);
+    let definition_concepts = result.definitions.iter().flat_map(|(concept, definition)| {
+        [*concept, definition.base_concept].into_iter().chain(definition.complements.iter().copied())
+    });
+
+    // Concept 0 is never valid, so it is marked as used to be skipped
+    let used_concepts = std::iter::once(0).chain(acceptation_concepts).chain(definition_concepts);
+    Orphans {
+        symbol_arrays: unused_indexes(result.symbol_arrays.len(), correlation_symbol_arrays.chain(conversion_symbol_arrays)),
+        correlations: unused_indexes(result.correlations.len(), result.correlation_arrays.iter().flatten().map(|correlation| correlation.index)),
+        correlation_arrays: unused_indexes(result.correlation_arrays.len(), result.acceptations.iter().map(|acc| acc.correlation_array_index.index)),
+        concepts: unused_indexes(result.max_concept + 1, used_concepts)
+    }
+}
+
+/// How many concepts can be expressed in a language.
+pub struct LanguageCoverage {
+    pub language: LanguageCode,
+
+    /// Concepts with at least one acceptation in this language.
+    pub represented: usize,
+
+    /// Concepts with at least one acceptation in this language that are also defined.
+    pub defined: usize,
+
+    /// Concepts without any acceptation in this language.
+    pub missing: usize
+}
+
+/// Computes the coverage of each language, in the same order as the languages in the database.
+/// Acceptations mixing alphabets of several languages count for all of them.
+pub fn language_coverage(result: &SdbReadResult) -> Vec<LanguageCoverage> {
+    let mut concepts: Vec<HashSet<usize>> = result.languages.iter().map(|_| HashSet::new()).collect();
+    for acc in result.acceptations.iter() {
+        for correlation in result.correlation_arrays[acc.correlation_array_index.index].iter() {
+            for alphabet in result.correlations[correlation.index].keys() {
+                if let Some(language_index) = result.language_index_of(*alphabet) {
+                    concepts[language_index].insert(acc.concept);
+                }
+            }
+        }
+    }
+
+    result.languages.iter().zip(concepts).map(|(language, concepts)| LanguageCoverage {
+        language: language.code,
+        represented: concepts.len(),
+        defined: concepts.iter().filter(|concept| result.definitions.contains_key(concept)).count(),
+        missing: result.max_concept.saturating_sub(concepts.len())
+    }).collect()
+}
+
+/// Number of entries of each kind, keyed by the names used in the documents exported by stats and
+/// served by /stats, in the order they are found in the file.
+pub fn entry_counts(result: &SdbReadResult) -> [(&'static str, usize); 9] {
+    [
+        ("symbol_arrays", result.symbol_arrays.len()),
+        ("languages", result.languages.len()),
+        ("alphabets", result.alphabet_count()),
+        ("conversions", result.conversions.len()),
+        ("concepts", result.max_concept),
+        ("correlations", result.correlations.len()),
+        ("correlation_arrays", result.correlation_arrays.len()),
+        ("acceptations", result.acceptations.len()),
+        ("definitions", result.definitions.len())
+    ]
+}
+
+/// Number of times each length appears, sorted by length.
+pub type Histogram = BTreeMap<usize, usize>;
+
+fn histogram(lengths: impl Iterator<Item = usize>) -> Histogram {
+    let mut histogram = Histogram::new();
+    for length in lengths {
+        *histogram.entry(length).or_insert(0) += 1;
+    }
+
+    histogram
+}
+
+/// Distributions of the lengths the encoder uses tables for.
+pub struct Histograms {
+    /// Number of characters of each symbol array.
+    pub symbol_array_lengths: Histogram,
+
+    /// Number of correlations within each correlation array.
+    pub correlation_array_lengths: Histogram,
+
+    /// Number of alphabets with text in each correlation.
+    pub alphabets_per_correlation: Histogram,
+
+    /// Number of complements of each definition.
+    pub complements_per_definition: Histogram
+}
+
+pub fn histograms(result: &SdbReadResult) -> Histograms {
+    Histograms {
+        symbol_array_lengths: histogram(result.symbol_arrays.iter().map(|text| text.chars().count())),
+        correlation_array_lengths: histogram(result.correlation_arrays.iter().map(Vec::len)),
+        alphabets_per_correlation: histogram(result.correlations.iter().map(BTreeMap::len)),
+        complements_per_definition: histogram(result.definitions.values().map(|definition| definition.complements.len()))
+    }
+}
+
+/// Number of times each character appears in the texts of an alphabet.
+pub struct CharacterFrequencies {
+    pub alphabet: Alphabet,
+
+    /// Characters sorted by decreasing frequency, and by character when sharing the same one.
+    pub characters: Vec<(char, usize)>
+}
+
+/// Counts the characters in the texts of each alphabet, in the same order as the alphabets. Each
+/// symbol array is counted once for each correlation using it.
+pub fn character_frequencies(result: &SdbReadResult) -> Vec<CharacterFrequencies> {
+    let mut counts: Vec<BTreeMap<char, usize>> = vec![BTreeMap::new(); result.alphabet_count()];
+    for correlation in result.correlations.iter() {
+        for (alphabet, symbol_array) in correlation {
+            if let Some(alphabet_counts) = counts.get_mut(alphabet.index()) {
+                for ch in result.symbol_arrays[symbol_array.index()].chars() {
+                    *alphabet_counts.entry(ch).or_insert(0) += 1;
+                }
+            }
+        }
+    }
+
+    counts.into_iter().enumerate().map(|(index, alphabet_counts)| {
+        let mut characters: Vec<(char, usize)> = alphabet_counts.into_iter().collect();
+        characters.sort_by(|(ch_a, count_a), (ch_b, count_b)| count_b.cmp(count_a).then(ch_a.cmp(ch_b)));
+        CharacterFrequencies {
+            alphabet: result.alphabet(index).unwrap(),
+            characters
+        }
+    }).collect()
+}
+
+/// Returns the characters defined in the character table of the file that do not appear in the
+/// text of any alphabet, as they are only used by conversions or by unused symbol arrays.
+pub fn characters_without_alphabet(result: &SdbReadResult, frequencies: &[CharacterFrequencies]) -> Vec<char> {
+    let used: HashSet<char> = frequencies.iter().flat_map(|alphabet| alphabet.characters.iter().map(|(ch, _)| *ch)).collect();
+    let mut unused: Vec<char> = match &result.tables.chars {
+        Some(table) => table.levels().flat_map(|(_, level)| level.iter().copied()).filter(|ch| !used.contains(ch)).collect(),
+        None => Vec::new()
+    };
+
+    unused.sort();
+    unused
+}
+
+/// Structure of the definitions, where each defined concept points to its base concept.
+pub struct DefinitionHierarchy {
+    /// Number of base concepts that must be followed from each defined concept until reaching a
+    /// concept that is not defined. Concepts within a cycle, or leading to one, are not included.
+    pub depths: HashMap<usize, usize>,
+
+    /// Groups of concepts that are transitively defined by themselves, in the order their bases
+    /// are followed.
+    pub cycles: Vec<Vec<usize>>
+}
+
+impl DefinitionHierarchy {
+    pub fn max_depth(&self) -> usize {
+        self.depths.values().copied().max().unwrap_or(0)
+    }
+
+    /// Returns the concepts found following the bases from the given concept, starting with the
+    /// concept itself and finishing with the first concept that is not defined.
+    pub fn chain(&self, result: &SdbReadResult, concept: usize) -> Vec<usize> {
+        let mut chain = vec![concept];
+        let mut current = concept;
+        for _ in 0..self.depths.get(&concept).copied().unwrap_or(0) {
+            current = result.definitions[&current].base_concept;
+            chain.push(current);
+        }
+
+        chain
+    }
+
+    /// Returns the given number of longest chains, starting with the longest one.
+    pub fn deepest_chains(&self, result: &SdbReadResult, count: usize) -> Vec<Vec<usize>> {
+        let mut concepts: Vec<(usize, usize)> = self.depths.iter().map(|(concept, depth)| (*concept, *depth)).collect();
+        concepts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        concepts.into_iter().take(count).map(|(concept, _)| self.chain(result, concept)).collect()
+    }
+}
+
+/// Computes the depth of each defined concept, and finds all cycles among definitions.
+pub fn definition_hierarchy(result: &SdbReadResult) -> DefinitionHierarchy {
+    let mut depths: HashMap<usize, usize> = HashMap::new();
+    let mut without_depth: HashSet<usize> = HashSet::new();
+    let mut cycles = Vec::new();
+
+    for concept in result.definitions.keys() {
+        let mut path: Vec<usize> = Vec::new();
+        let mut current = *concept;
+        let depth = loop {
+            if let Some(depth) = depths.get(&current) {
+                break Some(*depth);
+            }
+
+            if without_depth.contains(&current) {
+                break None;
+            }
+
+            if let Some(position) = path.iter().position(|visited| *visited == current) {
+                cycles.push(path[position..].to_vec());
+                break None;
+            }
+
+            match result.definitions.get(&current) {
+                Some(definition) => {
+                    path.push(current);
+                    current = definition.base_concept;
+                },
+                None => break Some(0)
+            }
+        };
+
+        match depth {
+            Some(mut depth) => {
+                for visited in path.into_iter().rev() {
+                    depth += 1;
+                    depths.insert(visited, depth);
+                }
+            },
+            None => without_depth.extend(path)
+        }
+    }
+
+    DefinitionHierarchy {
+        depths,
+        cycles
+    }
+}
+
+/// Groups the indexes of the given keys that are equal, keeping only groups with more than one
+/// index. Groups are sorted by their first index.
+fn duplicate_groups<K: Eq + Hash>(keys: impl Iterator<Item = K>) -> Vec<Vec<usize>> {
+    let mut groups: HashMap<K, Vec<usize>> = HashMap::new();
+    for (index, key) in keys.enumerate() {
+        groups.entry(key).or_default().push(index);
+    }
+
+    let mut groups: Vec<Vec<usize>> = groups.into_values().filter(|group| group.len() > 1).collect();
+    groups.sort();
+    groups
+}
+
+/// Entries with the same content stored more than once, grouped by their indexes.
+pub struct Duplicates {
+    /// Symbol arrays with the same text.
+    pub symbol_arrays: Vec<Vec<usize>>,
+
+    /// Correlations with the same text for the same alphabets, even if they point to different
+    /// symbol arrays.
+    pub correlations: Vec<Vec<usize>>,
+
+    /// Acceptations for the same concept with the same complete correlation.
+    pub acceptations: Vec<Vec<usize>>
+}
+
+impl Duplicates {
+    pub fn is_empty(&self) -> bool {
+        self.symbol_arrays.is_empty() && self.correlations.is_empty() && self.acceptations.is_empty()
+    }
+
+    /// Number of entries that could be removed by keeping only one entry per group.
+    pub fn removable_count(groups: &[Vec<usize>]) -> usize {
+        groups.iter().map(|group| group.len() - 1).sum()
+    }
+}
+
+pub fn find_duplicates(result: &SdbReadResult) -> Duplicates {
+    let symbol_arrays = &result.symbol_arrays;
+    let correlation_texts = result.correlations.iter().map(|correlation| {
+        correlation.iter().map(|(alphabet, symbol_array)| (*alphabet, &symbol_arrays[symbol_array.index])).collect::<BTreeMap<_, _>>()
+    });
+
+    let acceptation_texts = result.acceptations.iter().map(|acc| (acc.concept, result.get_complete_correlation(acc.correlation_array_index)));
+    Duplicates {
+        symbol_arrays: duplicate_groups(symbol_arrays.iter()),
+        correlations: duplicate_groups(correlation_texts),
+        acceptations: duplicate_groups(acceptation_texts)
+    }
+}
@@ -0,0 +1,91 @@
+use std::io::{self, BufReader, Read};
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::sync::mpsc::{self, Receiver};
+use crate::file_utils::{self, ReadError, ReadErrorKind};
+use crate::huffman::InputBitStream;
+use crate::sdb::{SdbReader, SdbReadResult, SDB_HEADER};
+
+/// Number of bytes read from the async reader at once.
+const CHUNK_SIZE: usize = 1 << 16;
+
+/// Number of chunks that can be waiting for the decoder, so that memory is bounded when the
+/// input is faster than the decoding.
+const MAX_PENDING_CHUNKS: usize = 4;
+
+/// Synchronous reader receiving the chunks read from the async reader. Errors found reading them
+/// are returned once all chunks before them have been consumed.
+pub struct ChunkReader {
+    receiver: Receiver<io::Result<Vec<u8>>>,
+    chunk: Vec<u8>,
+    position: usize
+}
+
+impl Read for ChunkReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.chunk.len() {
+            match self.receiver.blocking_recv() {
+                Some(chunk) => {
+                    self.chunk = chunk?;
+                    self.position = 0;
+                },
+                None => return Ok(0)
+            }
+        }
+
+        let length = buf.len().min(self.chunk.len() - self.position);
+        buf[..length].copy_from_slice(&self.chunk[self.position..self.position + length]);
+        self.position += length;
+        Ok(length)
+    }
+}
+
+/// Decodes the SDB file given by the async reader, like a download from an object storage,
+/// including its header. The reader is configured by the given function, which can set its mode
+/// or any handler, as when decoding synchronously.
+///
+/// Decoding is bound by the CPU rather than by the input, so it runs in the blocking thread pool
+/// of the current Tokio runtime, receiving the bytes as they are read here. Runtime threads are
+/// never blocked, neither while waiting for the input nor while decoding. This must be called
+/// within a Tokio runtime.
+pub async fn read<R, F>(mut input: R, configure: F) -> Result<SdbReadResult, ReadError>
+where
+    R: AsyncRead + Unpin,
+    F: for<'a> FnOnce(SdbReader<'a, BufReader<ChunkReader>>) -> SdbReader<'a, BufReader<ChunkReader>> + Send + 'static
+{
+    let (sender, receiver) = mpsc::channel(MAX_PENDING_CHUNKS);
+    let decoding = tokio::task::spawn_blocking(move || {
+        let mut input = BufReader::new(ChunkReader {
+            receiver,
+            chunk: Vec::new(),
+            position: 0
+        });
+
+        file_utils::read_header(&mut (&mut input).bytes(), SDB_HEADER)?;
+        configure(SdbReader::new(InputBitStream::from(&mut input))).read()
+    });
+
+    loop {
+        let mut chunk = vec![0; CHUNK_SIZE];
+        let chunk = match input.read(&mut chunk).await {
+            Ok(0) => break,
+            Ok(length) => {
+                chunk.truncate(length);
+                Ok(chunk)
+            },
+            Err(err) => Err(err)
+        };
+
+        let failed = chunk.is_err();
+        // Sending only fails if the decoding has already finished, so the rest is not needed
+        if sender.send(chunk).await.is_err() || failed {
+            break;
+        }
+    }
+
+    drop(sender);
+    match decoding.await {
+        Ok(result) => result,
+        Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+        Err(_) => Err(ReadError::new(ReadErrorKind::Cancelled, "Decoding cancelled"))
+    }
+}
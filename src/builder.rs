@@ -0,0 +1,163 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use crate::sdb::{Acceptation, Alphabet, Conversion, CorrelationArrayIndex, CorrelationIndex, DefinedTables, Definition, Language, LanguageCode, SdbReadResult, SymbolArrayIndex, SymbolArrays};
+
+/// Assembles a database from its parts, reusing symbol arrays, correlations and correlation
+/// arrays when the same content is added more than once.
+///
+/// Languages must be added in ascending order of their codes, as that is the order the format
+/// expects. Alphabets are numbered following the order of their languages.
+#[derive(Default)]
+pub struct SdbBuilder {
+    symbol_arrays: SymbolArrays,
+    symbol_array_indexes: HashMap<String, usize>,
+    languages: Vec<Language>,
+    alphabet_count: usize,
+    conversions: Vec<Conversion>,
+    max_concept: usize,
+    correlations: Vec<BTreeMap<Alphabet, SymbolArrayIndex>>,
+    correlation_indexes: HashMap<Vec<(usize, usize)>, usize>,
+    correlation_arrays: Vec<Vec<CorrelationIndex>>,
+    correlation_array_indexes: HashMap<Vec<usize>, usize>,
+    acceptations: Vec<Acceptation>,
+    definitions: BTreeMap<usize, Definition>
+}
+
+impl SdbBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a new language and returns its alphabets.
+    pub fn add_language(&mut self, code: LanguageCode, number_of_alphabets: usize) -> Result<Vec<Alphabet>, String> {
+        if let Some(last) = self.languages.last() {
+            if last.code.code >= code.code {
+                return Err(format!("Language {} must be added before {}", code, last.code));
+            }
+        }
+
+        if number_of_alphabets == 0 {
+            return Err(format!("Language {} must have at least one alphabet", code));
+        }
+
+        let first_alphabet = self.alphabet_count;
+        self.alphabet_count += number_of_alphabets;
+        self.languages.push(Language {
+            code,
+            number_of_alphabets
+        });
+
+        Ok((first_alphabet..self.alphabet_count).map(|index| Alphabet { index }).collect())
+    }
+
+    pub fn symbol_array(&mut self, text: &str) -> SymbolArrayIndex {
+        let index = match self.symbol_array_indexes.get(text) {
+            Some(index) => *index,
+            None => {
+                let index = self.symbol_arrays.len();
+                self.symbol_arrays.push(text);
+                self.symbol_array_indexes.insert(String::from(text), index);
+                index
+            }
+        };
+
+        SymbolArrayIndex {
+            index
+        }
+    }
+
+    /// Returns the correlation assigning the given text to each alphabet.
+    pub fn correlation(&mut self, texts: &BTreeMap<Alphabet, &str>) -> CorrelationIndex {
+        let entries: Vec<(usize, usize)> = texts.iter().map(|(alphabet, text)| (alphabet.index, self.symbol_array(text).index)).collect();
+        let index = match self.correlation_indexes.get(&entries) {
+            Some(index) => *index,
+            None => {
+                let index = self.correlations.len();
+                self.correlations.push(entries.iter().map(|(alphabet, symbol_array)| (Alphabet { index: *alphabet }, SymbolArrayIndex { index: *symbol_array })).collect());
+                self.correlation_indexes.insert(entries, index);
+                index
+            }
+        };
+
+        CorrelationIndex {
+            index
+        }
+    }
+
+    pub fn correlation_array(&mut self, correlations: &[CorrelationIndex]) -> CorrelationArrayIndex {
+        let entries: Vec<usize> = correlations.iter().map(|correlation| correlation.index).collect();
+        let index = match self.correlation_array_indexes.get(&entries) {
+            Some(index) => *index,
+            None => {
+                let index = self.correlation_arrays.len();
+                self.correlation_arrays.push(correlations.to_vec());
+                self.correlation_array_indexes.insert(entries, index);
+                index
+            }
+        };
+
+        CorrelationArrayIndex {
+            index
+        }
+    }
+
+    fn use_concept(&mut self, concept: usize) -> Result<(), String> {
+        if concept == 0 {
+            return Err(String::from("Concepts must be greater than 0"));
+        }
+
+        if concept > self.max_concept {
+            self.max_concept = concept;
+        }
+
+        Ok(())
+    }
+
+    pub fn add_acceptation(&mut self, concept: usize, correlation_array_index: CorrelationArrayIndex) -> Result<(), String> {
+        self.use_concept(concept)?;
+        self.acceptations.push(Acceptation {
+            concept,
+            correlation_array_index
+        });
+
+        Ok(())
+    }
+
+    pub fn add_definition(&mut self, concept: usize, base_concept: usize, complements: BTreeSet<usize>) -> Result<(), String> {
+        self.use_concept(concept)?;
+        self.use_concept(base_concept)?;
+        for complement in complements.iter() {
+            self.use_concept(*complement)?;
+        }
+
+        if self.definitions.insert(concept, Definition { base_concept, complements }).is_some() {
+            return Err(format!("Concept {} is defined more than once", concept));
+        }
+
+        Ok(())
+    }
+
+    pub fn add_conversion(&mut self, source: Alphabet, target: Alphabet, pairs: &[(&str, &str)]) {
+        let pairs = pairs.iter().map(|(source, target)| (self.symbol_array(source), self.symbol_array(target))).collect();
+        self.conversions.push(Conversion {
+            source,
+            target,
+            pairs
+        });
+    }
+
+    pub fn build(self) -> SdbReadResult {
+        SdbReadResult {
+            symbol_arrays: self.symbol_arrays,
+            languages: self.languages,
+            conversions: self.conversions,
+            max_concept: self.max_concept,
+            correlations: self.correlations,
+            correlation_arrays: self.correlation_arrays,
+            acceptations: self.acceptations,
+            definitions: self.definitions,
+            section_bits: Vec::new(),
+            tables: DefinedTables::default(),
+            warnings: Vec::new()
+        }
+    }
+}
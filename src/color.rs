@@ -0,0 +1,73 @@
+use std::env;
+use std::io::IsTerminal;
+use std::str::FromStr;
+use crate::i18n::{tr, Message};
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(tr!(Message::InvalidColorChoice, s))
+        }
+    }
+}
+
+impl ColorChoice {
+    /// Whether colors should be used for the given stream. In auto mode, colors are only used if the
+    /// stream is a terminal and the NO_COLOR environment variable is not set.
+    pub fn is_enabled_for(self, stream: &impl IsTerminal) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => stream.is_terminal() && env::var_os("NO_COLOR").is_none()
+        }
+    }
+}
+
+/// Wraps texts into ANSI escape sequences when colors are enabled.
+pub struct Palette {
+    enabled: bool
+}
+
+impl Palette {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled
+        }
+    }
+
+    fn paint(&self, code: &str, text: &str) -> String {
+        if self.enabled {
+            format!("\x1b[{}m{}\x1b[0m", code, text)
+        }
+        else {
+            text.to_string()
+        }
+    }
+
+    /// Style for the main entry of each line.
+    pub fn headword(&self, text: &str) -> String {
+        self.paint("1", text)
+    }
+
+    /// Style for concepts referenced from the main entry.
+    pub fn reference(&self, text: &str) -> String {
+        self.paint("36", text)
+    }
+
+    /// Style for separators and other punctuation.
+    pub fn punctuation(&self, text: &str) -> String {
+        self.paint("2", text)
+    }
+}
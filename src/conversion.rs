@@ -0,0 +1,190 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use crate::sdb::{Alphabet, Conversion, SdbReadResult, SymbolArrayIndex, SymbolArrays};
+
+/// Maximum number of source texts returned when converting back a text. Conversions are usually
+/// unambiguous, but each ambiguous pair multiplies the number of candidates.
+const MAX_INVERSE_RESULTS: usize = 32;
+
+impl Conversion {
+    /// Returns the texts of each pair, in the order they are encoded.
+    pub fn text_pairs<'a>(&self, symbol_arrays: &'a SymbolArrays) -> Vec<(&'a str, &'a str)> {
+        self.pairs.iter().map(|(source, target)| (&symbol_arrays[source.index], &symbol_arrays[target.index])).collect()
+    }
+
+    /// Converts a text written in the source alphabet into the target alphabet, or returns None
+    /// if the text cannot be converted. At each position the longest source that still allows
+    /// converting the rest of the text is chosen.
+    pub fn convert(&self, symbol_arrays: &SymbolArrays, text: &str) -> Option<String> {
+        let mut pairs = self.text_pairs(symbol_arrays);
+        pairs.retain(|(source, _)| !source.is_empty());
+        pairs.sort_by_key(|(source, _)| std::cmp::Reverse(source.len()));
+
+        // converted[i] is the conversion of text[i..], computed from the end of the text
+        let mut converted: Vec<Option<String>> = vec![None; text.len() + 1];
+        converted[text.len()] = Some(String::new());
+        for (position, _) in text.char_indices().rev() {
+            let rest = &text[position..];
+            converted[position] = pairs.iter().find_map(|(source, target)| {
+                if rest.starts_with(source) {
+                    converted[position + source.len()].as_ref().map(|tail| format!("{}{}", target, tail))
+                }
+                else {
+                    None
+                }
+            });
+        }
+
+        converted.swap_remove(0)
+    }
+
+    /// Returns all texts in the source alphabet that are converted into the given text.
+    pub fn convert_back(&self, symbol_arrays: &SymbolArrays, text: &str) -> Vec<String> {
+        let mut pairs = self.text_pairs(symbol_arrays);
+        pairs.retain(|(_, target)| !target.is_empty());
+
+        // candidates[i] contains the texts whose conversion may be text[i..]
+        let mut candidates: Vec<Vec<String>> = vec![Vec::new(); text.len() + 1];
+        candidates[text.len()].push(String::new());
+        for (position, _) in text.char_indices().rev() {
+            let rest = &text[position..];
+            let mut texts = Vec::new();
+            for (source, target) in pairs.iter() {
+                if rest.starts_with(target) {
+                    for tail in candidates[position + target.len()].iter() {
+                        if texts.len() < MAX_INVERSE_RESULTS {
+                            texts.push(format!("{}{}", source, tail));
+                        }
+                    }
+                }
+            }
+
+            candidates[position] = texts;
+        }
+
+        let mut texts = candidates.swap_remove(0);
+        texts.retain(|source| self.convert(symbol_arrays, source).as_deref() == Some(text));
+        texts.sort();
+        texts.dedup();
+        texts
+    }
+}
+
+impl SdbReadResult {
+    /// Returns the texts, in any source alphabet of the conversions, that are converted into the
+    /// given text. This allows finding texts written in alphabets that are not easy to type.
+    pub fn convert_back_all(&self, text: &str) -> Vec<String> {
+        let mut texts: Vec<String> = self.conversions.iter().flat_map(|conversion| conversion.convert_back(&self.symbol_arrays, text)).collect();
+        texts.sort();
+        texts.dedup();
+        texts
+    }
+
+    /// Sets the conversion between the given alphabets, replacing any previous conversion
+    /// between them. Symbol arrays with the same texts are reused, and the rest are appended.
+    pub fn set_conversion(&mut self, source: Alphabet, target: Alphabet, pairs: &[(&str, &str)]) {
+        let mut indexes: HashMap<&str, usize> = HashMap::new();
+        for (index, text) in self.symbol_arrays.iter().enumerate() {
+            indexes.entry(text).or_insert(index);
+        }
+
+        let mut new_texts: Vec<&str> = Vec::new();
+        let mut symbol_array = |text| {
+            let next_index = self.symbol_arrays.len() + new_texts.len();
+            let index = *indexes.entry(text).or_insert(next_index);
+            if index == next_index {
+                new_texts.push(text);
+            }
+
+            SymbolArrayIndex {
+                index
+            }
+        };
+
+        let pairs = pairs.iter().map(|(source, target)| (symbol_array(source), symbol_array(target))).collect();
+        for text in new_texts {
+            self.symbol_arrays.push(text);
+        }
+
+        let conversion = Conversion {
+            source,
+            target,
+            pairs
+        };
+
+        match self.conversions.iter_mut().find(|conversion| conversion.source == source && conversion.target == target) {
+            Some(existing) => *existing = conversion,
+            None => self.conversions.push(conversion)
+        }
+    }
+}
+
+/// Whether the given character belongs to the Latin script, including its accented letters.
+fn is_latin_letter(ch: char) -> bool {
+    ch.is_ascii_alphabetic() || matches!(ch, '\u{C0}'..='\u{24F}' | '\u{1E00}'..='\u{1EFF}')
+}
+
+/// Computes the romanized form of texts, applying the conversions needed to reach an alphabet
+/// written in Latin script.
+pub struct Romanizer {
+    /// Indexes of the conversions to apply, in order, for each alphabet not written in Latin
+    /// script that can reach one that is.
+    chains: HashMap<Alphabet, Vec<usize>>
+}
+
+impl Romanizer {
+    /// Finds the alphabets written in Latin script, that is, the ones where all letters of their
+    /// texts are Latin, and the shortest chain of conversions reaching any of them from each other
+    /// alphabet.
+    pub fn new(result: &SdbReadResult) -> Self {
+        let mut letters: HashMap<Alphabet, bool> = HashMap::new();
+        for correlation in result.correlations.iter() {
+            for (alphabet, symbol_array) in correlation {
+                for ch in result.symbol_arrays[symbol_array.index()].chars().filter(|ch| ch.is_alphabetic()) {
+                    let all_latin = letters.entry(*alphabet).or_insert(true);
+                    *all_latin = *all_latin && is_latin_letter(ch);
+                }
+            }
+        }
+
+        let latin: HashSet<Alphabet> = letters.into_iter().filter(|(_, all_latin)| *all_latin).map(|(alphabet, _)| alphabet).collect();
+        let mut chains = HashMap::new();
+        for index in 0..result.alphabet_count() {
+            let alphabet = result.alphabet(index).unwrap();
+            if latin.contains(&alphabet) {
+                continue;
+            }
+
+            // Breadth-first search, so that the shortest chain is found
+            let mut visited: HashSet<Alphabet> = HashSet::from([alphabet]);
+            let mut queue: VecDeque<(Alphabet, Vec<usize>)> = VecDeque::from([(alphabet, Vec::new())]);
+            while let Some((current, chain)) = queue.pop_front() {
+                if latin.contains(&current) {
+                    chains.insert(alphabet, chain);
+                    break;
+                }
+
+                for (conversion_index, conversion) in result.conversions.iter().enumerate() {
+                    if conversion.source == current && visited.insert(conversion.target) {
+                        let mut next_chain = chain.clone();
+                        next_chain.push(conversion_index);
+                        queue.push_back((conversion.target, next_chain));
+                    }
+                }
+            }
+        }
+
+        Self {
+            chains
+        }
+    }
+
+    /// Returns the romanized form of the first text that can be converted into an alphabet
+    /// written in Latin script, or None if none of them can, or if they are already written in
+    /// Latin script.
+    pub fn romanize(&self, result: &SdbReadResult, texts: &BTreeMap<Alphabet, String>) -> Option<String> {
+        texts.iter().find_map(|(alphabet, text)| {
+            let chain = self.chains.get(alphabet)?;
+            chain.iter().try_fold(text.clone(), |text, conversion_index| result.conversions[*conversion_index].convert(&result.symbol_arrays, &text))
+        })
+    }
+}
@@ -0,0 +1,175 @@
+use crate::export::{self, ExportOptions};
+use crate::json::{object, JsonValue};
+use crate::sdb::SdbReadResult;
+
+/// Maximum number of cells of the table used to align the differing part of two arrays. Longer
+/// arrays are compared position by position instead, which may result in longer patches, but
+/// avoids running out of memory.
+const MAX_ALIGNMENT_CELLS: usize = 1 << 24;
+
+/// Step of the alignment of two arrays.
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum Edit {
+    Keep,
+    Remove,
+    Insert
+}
+
+/// Escapes a key to be used as a reference token within a JSON pointer.
+fn escape_token(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+fn operation(op: &str, path: &str, value: Option<&JsonValue>) -> JsonValue {
+    match value {
+        Some(value) => object([
+            ("op", op.into()),
+            ("path", path.into()),
+            ("value", value.clone())
+        ]),
+        None => object([
+            ("op", op.into()),
+            ("path", path.into())
+        ])
+    }
+}
+
+/// Aligns both arrays keeping their longest common subsequence.
+fn align(old: &[JsonValue], new: &[JsonValue]) -> Vec<Edit> {
+    let columns = new.len() + 1;
+    // lengths[i * columns + j] is the length of the longest common subsequence of old[i..] and new[j..]
+    let mut lengths = vec![0u32; (old.len() + 1) * columns];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i * columns + j] = if old[i] == new[j] {
+                lengths[(i + 1) * columns + j + 1] + 1
+            }
+            else {
+                lengths[(i + 1) * columns + j].max(lengths[i * columns + j + 1])
+            };
+        }
+    }
+
+    let mut edits = Vec::with_capacity(old.len() + new.len());
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            edits.push(Edit::Keep);
+            i += 1;
+            j += 1;
+        }
+        else if j == new.len() || (i < old.len() && lengths[(i + 1) * columns + j] >= lengths[i * columns + j + 1]) {
+            edits.push(Edit::Remove);
+            i += 1;
+        }
+        else {
+            edits.push(Edit::Insert);
+            j += 1;
+        }
+    }
+
+    edits
+}
+
+/// Aligns both arrays position by position, replacing each element by the one at the same
+/// position and removing or inserting the rest.
+fn align_by_position(old: &[JsonValue], new: &[JsonValue]) -> Vec<Edit> {
+    let mut edits = Vec::with_capacity(old.len() + new.len());
+    for position in 0..old.len().max(new.len()) {
+        if position < old.len() {
+            edits.push(Edit::Remove);
+        }
+
+        if position < new.len() {
+            edits.push(Edit::Insert);
+        }
+    }
+
+    edits
+}
+
+fn diff_arrays(old: &[JsonValue], new: &[JsonValue], path: &str, operations: &mut Vec<JsonValue>) {
+    let prefix = old.iter().zip(new.iter()).take_while(|(old_value, new_value)| old_value == new_value).count();
+    let suffix = old[prefix..].iter().rev().zip(new[prefix..].iter().rev()).take_while(|(old_value, new_value)| old_value == new_value).count();
+    let old = &old[prefix..old.len() - suffix];
+    let new = &new[prefix..new.len() - suffix];
+    let edits = if old.len().saturating_mul(new.len()) <= MAX_ALIGNMENT_CELLS {
+        align(old, new)
+    }
+    else {
+        align_by_position(old, new)
+    };
+
+    // Operations are applied in order, so indexes refer to the array as modified so far
+    let mut index = prefix;
+    let (mut old_index, mut new_index) = (0, 0);
+    let mut edits = edits.into_iter().peekable();
+    while let Some(edit) = edits.next() {
+        match edit {
+            Edit::Keep => {
+                index += 1;
+                old_index += 1;
+                new_index += 1;
+            },
+            Edit::Remove if edits.peek() == Some(&Edit::Insert) => {
+                edits.next();
+                diff_values(&old[old_index], &new[new_index], &format!("{}/{}", path, index), operations);
+                index += 1;
+                old_index += 1;
+                new_index += 1;
+            },
+            Edit::Remove => {
+                operations.push(operation("remove", &format!("{}/{}", path, index), None));
+                old_index += 1;
+            },
+            Edit::Insert => {
+                operations.push(operation("add", &format!("{}/{}", path, index), Some(&new[new_index])));
+                index += 1;
+                new_index += 1;
+            }
+        }
+    }
+}
+
+fn diff_objects(old: &[(String, JsonValue)], new: &[(String, JsonValue)], path: &str, operations: &mut Vec<JsonValue>) {
+    for (key, old_value) in old.iter() {
+        let field_path = format!("{}/{}", path, escape_token(key));
+        match new.iter().find(|(new_key, _)| new_key == key) {
+            Some((_, new_value)) => diff_values(old_value, new_value, &field_path, operations),
+            None => operations.push(operation("remove", &field_path, None))
+        }
+    }
+
+    for (key, new_value) in new.iter() {
+        if !old.iter().any(|(old_key, _)| old_key == key) {
+            operations.push(operation("add", &format!("{}/{}", path, escape_token(key)), Some(new_value)));
+        }
+    }
+}
+
+fn diff_values(old: &JsonValue, new: &JsonValue, path: &str, operations: &mut Vec<JsonValue>) {
+    match (old, new) {
+        (JsonValue::Object(old_fields), JsonValue::Object(new_fields)) => diff_objects(old_fields, new_fields, path, operations),
+        (JsonValue::Array(old_values), JsonValue::Array(new_values)) => diff_arrays(old_values, new_values, path, operations),
+        _ => {
+            if old != new {
+                operations.push(operation("replace", path, Some(new)));
+            }
+        }
+    }
+}
+
+/// Returns the operations, as described by RFC 6902, that turn the old document into the new
+/// one. Elements of arrays are aligned keeping their longest common subsequence, and elements
+/// found at the same position are patched instead of replaced.
+pub fn json_patch(old: &JsonValue, new: &JsonValue) -> JsonValue {
+    let mut operations = Vec::new();
+    diff_values(old, new, "", &mut operations);
+    JsonValue::Array(operations)
+}
+
+/// Returns the JSON patch turning the JSON export of the old database into the export of the
+/// new one.
+pub fn diff_json(old: &SdbReadResult, new: &SdbReadResult, options: &ExportOptions) -> JsonValue {
+    json_patch(&export::export_json(old, options), &export::export_json(new, options))
+}
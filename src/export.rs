@@ -0,0 +1,794 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::io::{self, Write};
+#[cfg(feature = "collation")]
+use icu_collator::{Collator, CollatorOptions};
+#[cfg(feature = "collation")]
+use icu_locid::Locale;
+#[cfg(feature = "normalization")]
+use icu_normalizer::ComposingNormalizer;
+use crate::analysis::{self, Histogram};
+use crate::conversion::Romanizer;
+use crate::json::{object, JsonValue};
+use crate::sdb::{Acceptation, Alphabet, Conversion, CorrelationIndex, Definition, LanguageCode, LanguageInfo, SdbReadResult, Section, SymbolArrayIndex};
+
+/// Version of the structure of the exported documents. It is increased whenever a field is
+/// removed or its meaning changes, so that consumers can reject documents they do not understand.
+/// Adding new fields does not change the version.
+pub const SCHEMA_VERSION: usize = 1;
+
+/// JSON Schema describing the documents returned by [export_json].
+pub const SCHEMA: &str = include_str!("../schema/export.schema.json");
+
+/// Unicode normalization forms that can be applied to the exported texts.
+#[cfg(feature = "normalization")]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Normalization {
+    /// Canonical composition, where combining sequences like "n" followed by a combining tilde
+    /// are replaced by their precomposed characters, like "ñ".
+    Nfc,
+
+    /// Compatibility composition, where compatibility characters like full width letters or
+    /// half width katakana are also replaced by their usual forms.
+    Nfkc
+}
+
+/// Optional content added to the exported documents.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ExportOptions {
+    /// Whether to include the romanized form of each acceptation whose texts can be converted
+    /// into an alphabet written in Latin script.
+    pub romanize: bool,
+
+    /// Normalization form all exported texts are converted into, if any. Texts are stored
+    /// unchanged otherwise.
+    #[cfg(feature = "normalization")]
+    pub normalization: Option<Normalization>
+}
+
+impl ExportOptions {
+    /// Returns the given text converted into the normalization form requested, if any.
+    fn normalize(&self, text: String) -> String {
+        #[cfg(feature = "normalization")]
+        match self.normalization {
+            Some(Normalization::Nfc) => return ComposingNormalizer::new_nfc().normalize(&text),
+            Some(Normalization::Nfkc) => return ComposingNormalizer::new_nfkc().normalize(&text),
+            None => ()
+        }
+
+        text
+    }
+
+    fn romanizer(&self, result: &SdbReadResult) -> Option<Romanizer> {
+        if self.romanize {
+            Some(Romanizer::new(result))
+        }
+        else {
+            None
+        }
+    }
+}
+
+fn language_json(language: &LanguageInfo) -> JsonValue {
+    object([
+        ("code", language.code.to_string().into()),
+        ("alphabets", language.alphabets.clone().collect::<Vec<usize>>().into())
+    ])
+}
+
+fn languages_json(result: &SdbReadResult) -> JsonValue {
+    JsonValue::Array(result.languages().map(|language| language_json(&language)).collect())
+}
+
+fn conversion_json(result: &SdbReadResult, conversion: &Conversion, options: &ExportOptions) -> JsonValue {
+    let pairs: Vec<JsonValue> = conversion.pairs.iter().map(|(source, target)| JsonValue::Array(vec![
+        options.normalize(result.symbol_arrays[source.index].to_string()).into(),
+        options.normalize(result.symbol_arrays[target.index].to_string()).into()
+    ])).collect();
+
+    object([
+        ("source", conversion.source.index.into()),
+        ("target", conversion.target.index.into()),
+        ("pairs", JsonValue::Array(pairs))
+    ])
+}
+
+fn conversions_json(result: &SdbReadResult, options: &ExportOptions) -> JsonValue {
+    JsonValue::Array(result.conversions.iter().map(|conversion| conversion_json(result, conversion, options)).collect())
+}
+
+fn acceptation_json(result: &SdbReadResult, acc: &Acceptation, correlation: BTreeMap<Alphabet, String>, options: &ExportOptions, romanizer: Option<&Romanizer>) -> JsonValue {
+    let romanized = romanizer.and_then(|romanizer| romanizer.romanize(result, &correlation)).map(|text| options.normalize(text));
+    let texts: Vec<JsonValue> = correlation.into_iter().map(|(alphabet, text)| object([
+        ("alphabet", alphabet.index.into()),
+        ("text", options.normalize(text).into())
+    ])).collect();
+
+    let mut value = object([
+        ("concept", acc.concept.into()),
+        ("texts", JsonValue::Array(texts))
+    ]);
+
+    if let (Some(romanized), JsonValue::Object(fields)) = (romanized, &mut value) {
+        fields.push((String::from("romanized"), romanized.into()));
+    }
+
+    value
+}
+
+fn acceptations_json(result: &SdbReadResult, options: &ExportOptions) -> JsonValue {
+    let romanizer = options.romanizer(result);
+    JsonValue::Array(result.acceptations.iter().zip(result.complete_correlations())
+        .map(|(acc, correlation)| acceptation_json(result, acc, correlation, options, romanizer.as_ref())).collect())
+}
+
+fn definition_json(concept: usize, definition: &Definition) -> JsonValue {
+    let complements: Vec<usize> = definition.complements.iter().copied().collect();
+    object([
+        ("concept", concept.into()),
+        ("base_concept", definition.base_concept.into()),
+        ("complements", complements.into())
+    ])
+}
+
+fn definitions_json(result: &SdbReadResult) -> JsonValue {
+    JsonValue::Array(result.definitions.iter().map(|(concept, definition)| definition_json(*concept, definition)).collect())
+}
+
+/// Returns the decoded content as a JSON document, following [SCHEMA]. Symbol arrays, correlations
+/// and correlation arrays are not exported on their own, but resolved into the texts of each
+/// acceptation and conversion.
+pub fn export_json(result: &SdbReadResult, options: &ExportOptions) -> JsonValue {
+    object([
+        ("schema_version", SCHEMA_VERSION.into()),
+        ("languages", languages_json(result)),
+        ("conversions", conversions_json(result, options)),
+        ("max_concept", result.max_concept.into()),
+        ("acceptations", acceptations_json(result, options)),
+        ("definitions", definitions_json(result))
+    ])
+}
+
+/// Receives the content of a database record by record, to write it in a custom format. Records
+/// are visited in the same order they are found in the file, except definitions, which are
+/// visited sorted by concept. Records referring to others are given the whole result too, with
+/// all sections before them already decoded, so that their texts can be resolved. All methods do
+/// nothing by default, so that only the records of interest need to be handled.
+///
+/// Exporters are driven by an [ExportDriver], which can be fed while the database is decoded, or
+/// by [export_with] for databases already decoded.
+pub trait Exporter {
+    fn visit_symbol_array(&mut self, _index: usize, _text: &str) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn visit_language(&mut self, _language: &LanguageInfo) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn visit_conversion(&mut self, _result: &SdbReadResult, _index: usize, _conversion: &Conversion) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn visit_max_concept(&mut self, _max_concept: usize) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn visit_correlation(&mut self, _result: &SdbReadResult, _index: usize, _correlation: &BTreeMap<Alphabet, SymbolArrayIndex>) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn visit_correlation_array(&mut self, _result: &SdbReadResult, _index: usize, _array: &[CorrelationIndex]) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn visit_acceptation(&mut self, _result: &SdbReadResult, _index: usize, _acceptation: &Acceptation) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn visit_definition(&mut self, _result: &SdbReadResult, _concept: usize, _definition: &Definition) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Called once all records have been visited.
+    fn finish(&mut self, _result: &SdbReadResult) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Feeds an exporter with the records of a database. Acceptations can be given while they are
+/// decoded, through the acceptation handler of the reader, so that they do not need to be kept
+/// in memory. The sections before them are visited when the first acceptation is given, and the
+/// definitions when finishing.
+pub struct ExportDriver<E: Exporter> {
+    exporter: E,
+    acceptation_count: usize,
+    head_visited: bool
+}
+
+impl<E: Exporter> ExportDriver<E> {
+    pub fn new(exporter: E) -> Self {
+        Self {
+            exporter,
+            acceptation_count: 0,
+            head_visited: false
+        }
+    }
+
+    /// Visits all records of the sections before the acceptations.
+    fn visit_head(&mut self, result: &SdbReadResult) -> io::Result<()> {
+        self.head_visited = true;
+        for (index, text) in result.symbol_arrays.iter().enumerate() {
+            self.exporter.visit_symbol_array(index, text)?;
+        }
+
+        for language in result.languages() {
+            self.exporter.visit_language(&language)?;
+        }
+
+        for (index, conversion) in result.conversions.iter().enumerate() {
+            self.exporter.visit_conversion(result, index, conversion)?;
+        }
+
+        self.exporter.visit_max_concept(result.max_concept)?;
+        for (index, correlation) in result.correlations.iter().enumerate() {
+            self.exporter.visit_correlation(result, index, correlation)?;
+        }
+
+        for (index, array) in result.correlation_arrays.iter().enumerate() {
+            self.exporter.visit_correlation_array(result, index, array)?;
+        }
+
+        Ok(())
+    }
+
+    /// Visits the given acceptation, and all records before it if this is the first one.
+    pub fn visit_acceptation(&mut self, result: &SdbReadResult, acceptation: &Acceptation) -> io::Result<()> {
+        if !self.head_visited {
+            self.visit_head(result)?;
+        }
+
+        self.exporter.visit_acceptation(result, self.acceptation_count, acceptation)?;
+        self.acceptation_count += 1;
+        Ok(())
+    }
+
+    /// Visits the definitions, and any record not visited yet, and finishes the exporter,
+    /// returning it.
+    pub fn finish(mut self, result: &SdbReadResult) -> io::Result<E> {
+        if !self.head_visited {
+            self.visit_head(result)?;
+        }
+
+        for (concept, definition) in result.definitions.iter() {
+            self.exporter.visit_definition(result, *concept, definition)?;
+        }
+
+        self.exporter.finish(result)?;
+        Ok(self.exporter)
+    }
+}
+
+/// Feeds the given exporter with all records of a database already decoded, and returns it.
+pub fn export_with<E: Exporter>(result: &SdbReadResult, exporter: E) -> io::Result<E> {
+    let mut driver = ExportDriver::new(exporter);
+    for acceptation in result.acceptations.iter() {
+        driver.visit_acceptation(result, acceptation)?;
+    }
+
+    driver.finish(result)
+}
+
+/// Writes the same document returned by [export_json], in its compact form, while the database is
+/// being decoded, so that acceptations do not need to be kept in memory. The records before the
+/// acceptations are kept until the first acceptation is given, and then written together with
+/// it. Acceptations and definitions are written as they are given.
+pub struct JsonStreamWriter<W: Write> {
+    writer: W,
+    options: ExportOptions,
+    romanizer: Option<Romanizer>,
+    languages: Vec<JsonValue>,
+    conversions: Vec<JsonValue>,
+    max_concept: usize,
+    head_written: bool,
+    definitions_started: bool
+}
+
+impl<W: Write> JsonStreamWriter<W> {
+    pub fn new(writer: W, options: ExportOptions) -> Self {
+        Self {
+            writer,
+            options,
+            romanizer: None,
+            languages: Vec::new(),
+            conversions: Vec::new(),
+            max_concept: 0,
+            head_written: false,
+            definitions_started: false
+        }
+    }
+
+    /// Writes all fields before the acceptations, from the records visited so far.
+    fn write_head(&mut self, result: &SdbReadResult) -> io::Result<()> {
+        self.head_written = true;
+        self.romanizer = self.options.romanizer(result);
+        let languages = JsonValue::Array(std::mem::take(&mut self.languages));
+        let conversions = JsonValue::Array(std::mem::take(&mut self.conversions));
+        write!(self.writer, "{{\"schema_version\":{},\"languages\":{},\"conversions\":{},\"max_concept\":{},\"acceptations\":[",
+            SCHEMA_VERSION, languages, conversions, self.max_concept)
+    }
+
+    /// Closes the acceptations and starts the definitions, writing the fields before them if no
+    /// acceptation has been given.
+    fn start_definitions(&mut self, result: &SdbReadResult) -> io::Result<()> {
+        if !self.head_written {
+            self.write_head(result)?;
+        }
+
+        self.definitions_started = true;
+        self.writer.write_all(b"],\"definitions\":[")
+    }
+
+    /// Returns the underlying writer, once the document has been finished.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> Exporter for JsonStreamWriter<W> {
+    fn visit_language(&mut self, language: &LanguageInfo) -> io::Result<()> {
+        self.languages.push(language_json(language));
+        Ok(())
+    }
+
+    fn visit_conversion(&mut self, result: &SdbReadResult, _index: usize, conversion: &Conversion) -> io::Result<()> {
+        self.conversions.push(conversion_json(result, conversion, &self.options));
+        Ok(())
+    }
+
+    fn visit_max_concept(&mut self, max_concept: usize) -> io::Result<()> {
+        self.max_concept = max_concept;
+        Ok(())
+    }
+
+    /// Writes the given acceptation, resolving its texts from the given sections.
+    fn visit_acceptation(&mut self, result: &SdbReadResult, _index: usize, acceptation: &Acceptation) -> io::Result<()> {
+        if !self.head_written {
+            self.write_head(result)?;
+        }
+        else {
+            self.writer.write_all(b",")?;
+        }
+
+        write!(self.writer, "{}", acceptation_json(result, acceptation, result.get_complete_correlation(acceptation.correlation_array_index), &self.options, self.romanizer.as_ref()))
+    }
+
+    fn visit_definition(&mut self, result: &SdbReadResult, concept: usize, definition: &Definition) -> io::Result<()> {
+        if !self.definitions_started {
+            self.start_definitions(result)?;
+        }
+        else {
+            self.writer.write_all(b",")?;
+        }
+
+        write!(self.writer, "{}", definition_json(concept, definition))
+    }
+
+    /// Closes the document.
+    fn finish(&mut self, result: &SdbReadResult) -> io::Result<()> {
+        if !self.definitions_started {
+            self.start_definitions(result)?;
+        }
+
+        self.writer.write_all(b"]}")
+    }
+}
+
+/// Parts of the document returned by [export_json], to be written into separate files.
+#[derive(Copy, Clone, Debug)]
+pub enum Shard {
+    Languages,
+    Conversions,
+    Acceptations,
+    Definitions
+}
+
+impl Shard {
+    pub const ALL: [Shard; 4] = [Shard::Languages, Shard::Conversions, Shard::Acceptations, Shard::Definitions];
+
+    /// Name of this shard, which is also the name of the field it contains.
+    pub fn name(self) -> &'static str {
+        match self {
+            Shard::Languages => "languages",
+            Shard::Conversions => "conversions",
+            Shard::Acceptations => "acceptations",
+            Shard::Definitions => "definitions"
+        }
+    }
+}
+
+/// Returns the given part of the document returned by [export_json], together with its
+/// schema_version. Merging the fields of all shards results in the whole document, as
+/// max_concept is included in the acceptations shard.
+pub fn export_shard(result: &SdbReadResult, shard: Shard, options: &ExportOptions) -> JsonValue {
+    match shard {
+        Shard::Languages => object([
+            ("schema_version", SCHEMA_VERSION.into()),
+            ("languages", languages_json(result))
+        ]),
+        Shard::Conversions => object([
+            ("schema_version", SCHEMA_VERSION.into()),
+            ("conversions", conversions_json(result, options))
+        ]),
+        Shard::Acceptations => object([
+            ("schema_version", SCHEMA_VERSION.into()),
+            ("max_concept", result.max_concept.into()),
+            ("acceptations", acceptations_json(result, options))
+        ]),
+        Shard::Definitions => object([
+            ("schema_version", SCHEMA_VERSION.into()),
+            ("definitions", definitions_json(result))
+        ])
+    }
+}
+
+/// Returns the text used to label each concept, the same concept_text would return, computed in a
+/// single pass over the concepts.
+fn concept_labels(result: &SdbReadResult) -> BTreeMap<usize, String> {
+    result.iter_concepts().filter_map(|entry| {
+        entry.acceptations.into_iter()
+            .map(|(_, texts)| texts.into_values().collect::<Vec<_>>())
+            .find(|texts| !texts.is_empty())
+            .map(|texts| (entry.concept, texts.join("/")))
+    }).collect()
+}
+
+/// Returns the definitions as a graph document with a list of nodes and a list of edges, as
+/// expected by network visualization tools like Cytoscape or Gephi.
+///
+/// Nodes are all concepts with any acceptation or involved in any definition, labeled with the
+/// text of their first acceptation, or null if they have none. Each definition adds an edge of
+/// type "base" from the defined concept to its base concept, and one of type "complement" to
+/// each of its complements.
+pub fn export_concept_graph(result: &SdbReadResult, options: &ExportOptions) -> JsonValue {
+    let labels = concept_labels(result);
+    let mut concepts: BTreeSet<usize> = result.acceptations.iter().map(|acc| acc.concept).collect();
+    let mut edges = Vec::new();
+    for (concept, definition) in result.definitions.iter() {
+        concepts.insert(*concept);
+        concepts.insert(definition.base_concept);
+        concepts.extend(definition.complements.iter().copied());
+
+        edges.push(object([
+            ("source", (*concept).into()),
+            ("target", definition.base_concept.into()),
+            ("type", "base".into())
+        ]));
+
+        for complement in definition.complements.iter() {
+            edges.push(object([
+                ("source", (*concept).into()),
+                ("target", (*complement).into()),
+                ("type", "complement".into())
+            ]));
+        }
+    }
+
+    let nodes: Vec<JsonValue> = concepts.into_iter().map(|concept| object([
+        ("id", concept.into()),
+        ("label", labels.get(&concept).cloned().map(|label| options.normalize(label)).into())
+    ])).collect();
+
+    object([
+        ("schema_version", SCHEMA_VERSION.into()),
+        ("nodes", JsonValue::Array(nodes)),
+        ("edges", JsonValue::Array(edges))
+    ])
+}
+
+/// Formats in which the pairs of a conversion can be exported as substitution rules.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RuleFormat {
+    /// One rule per line, with the text in the source alphabet and the text in the target
+    /// alphabet separated by a tab, in the same order the pairs are encoded.
+    Plain,
+
+    /// Romaji table for Google Japanese Input, where each line contains the text to type, in the
+    /// target alphabet, and the text it is replaced with, in the source alphabet, separated by a
+    /// tab. As each text to type can only be replaced with one text, only the first pair with
+    /// each target text is kept.
+    GoogleIme
+}
+
+/// Returns the pairs of the given conversion as substitution rules in the given format, one per
+/// line.
+pub fn export_conversion_rules(result: &SdbReadResult, conversion: &Conversion, format: RuleFormat, options: &ExportOptions) -> String {
+    let mut typed: HashSet<String> = HashSet::new();
+    let mut rules = String::new();
+    for (source, target) in conversion.pairs.iter() {
+        let source = options.normalize(result.symbol_arrays[source.index].to_string());
+        let target = options.normalize(result.symbol_arrays[target.index].to_string());
+        match format {
+            RuleFormat::Plain => {
+                rules.push_str(&source);
+                rules.push('\t');
+                rules.push_str(&target);
+            },
+            RuleFormat::GoogleIme => {
+                if !typed.insert(target.clone()) {
+                    continue;
+                }
+
+                rules.push_str(&target);
+                rules.push('\t');
+                rules.push_str(&source);
+            }
+        }
+
+        rules.push('\n');
+    }
+
+    rules
+}
+
+/// Joins the texts of an acceptation, placing the texts of all alphabets but the first one within
+/// parentheses, like "犬 (いぬ)".
+fn card_text(texts: &BTreeMap<Alphabet, String>) -> String {
+    let mut values = texts.values();
+    let mut text = values.next().cloned().unwrap_or_default();
+    let rest: Vec<&str> = values.map(String::as_str).collect();
+    if !rest.is_empty() {
+        text.push_str(" (");
+        text.push_str(&rest.join(", "));
+        text.push(')');
+    }
+
+    text
+}
+
+/// Quotes the given field for a CSV file if it contains any comma, quote or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    }
+    else {
+        String::from(field)
+    }
+}
+
+/// Order of the cards exported by [export_flashcards].
+#[derive(Clone, Default)]
+pub enum CardOrder {
+    /// Cards follow the order of their concepts.
+    #[default]
+    Concept,
+
+    /// Cards are sorted by the code points of their front texts.
+    CodePoint,
+
+    /// Cards are sorted by their front texts, following the collation rules of the given locale.
+    #[cfg(feature = "collation")]
+    Collation(Locale)
+}
+
+/// Returns the texts of a card front to be compared when sorting, starting with the one in the
+/// given alphabet, if any, and followed by the rest in alphabet order.
+fn card_sort_key(texts: &BTreeMap<Alphabet, String>, alphabet: Option<Alphabet>) -> Vec<String> {
+    let first = alphabet.and_then(|alphabet| texts.get(&alphabet));
+    let rest = texts.iter().filter(|(key, _)| Some(**key) != alphabet).map(|(_, text)| text);
+    first.into_iter().chain(rest).cloned().collect()
+}
+
+/// Returns a CSV document with a front and a back field per line, to be imported into spaced
+/// repetition tools. A card is added for each acceptation in the front language, backed by the
+/// texts of all acceptations of the same concept in the back language. Concepts without
+/// acceptations in both languages are skipped. When romanizing, a third field is added with the
+/// romanized form of the front, which is empty if it cannot be romanized.
+///
+/// Cards are sorted in the given order, comparing first the front text in the given alphabet, if
+/// any. Cards whose fronts compare equal keep the order of their concepts.
+pub fn export_flashcards(result: &SdbReadResult, front_language: LanguageCode, back_language: LanguageCode, options: &ExportOptions, order: &CardOrder, sort_alphabet: Option<Alphabet>) -> String {
+    let romanizer = options.romanizer(result);
+    let language_of = |texts: &BTreeMap<Alphabet, String>| texts.keys().next().and_then(|alphabet| result.language_code_of(*alphabet));
+    let mut cards: Vec<(Vec<String>, String)> = Vec::new();
+    for entry in result.iter_concepts() {
+        let backs: Vec<String> = entry.acceptations.iter()
+            .filter(|(_, texts)| language_of(texts) == Some(back_language))
+            .map(|(_, texts)| options.normalize(card_text(texts)))
+            .collect();
+
+        if backs.is_empty() {
+            continue;
+        }
+
+        let back = csv_field(&backs.join("; "));
+        for (_, texts) in entry.acceptations.iter().filter(|(_, texts)| language_of(texts) == Some(front_language)) {
+            let mut card = csv_field(&options.normalize(card_text(texts)));
+            card.push(',');
+            card.push_str(&back);
+            if let Some(romanizer) = &romanizer {
+                card.push(',');
+                card.push_str(&csv_field(&options.normalize(romanizer.romanize(result, texts).unwrap_or_default())));
+            }
+
+            card.push('\n');
+            let key = card_sort_key(texts, sort_alphabet).into_iter().map(|text| options.normalize(text)).collect();
+            cards.push((key, card));
+        }
+    }
+
+    match order {
+        CardOrder::Concept => (),
+        CardOrder::CodePoint => cards.sort_by(|(a, _), (b, _)| a.cmp(b)),
+        #[cfg(feature = "collation")]
+        CardOrder::Collation(locale) => {
+            let collator = Collator::try_new(&locale.into(), CollatorOptions::new())
+                .expect("Collation data is compiled in for all locales");
+            cards.sort_by(|(a, _), (b, _)| a.iter().zip(b)
+                .map(|(a, b)| collator.compare(a, b))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or_else(|| a.len().cmp(&b.len())));
+        }
+    }
+
+    cards.into_iter().map(|(_, card)| card).collect()
+}
+
+/// Quotes a text for the record dump. Backslashes, quotes, line breaks and tabs are escaped as
+/// in C, and any other control character as \u{XX}, so that each record takes a single line.
+fn record_text(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for ch in text.chars() {
+        match ch {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            ch if ch.is_control() => quoted.push_str(&format!("\\u{{{:X}}}", u32::from(ch))),
+            ch => quoted.push(ch)
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+/// Writes all records as text, one per line, in the order they are found in the file. Each line
+/// starts with the kind of record, followed by its fields separated by spaces, in a fixed order.
+/// Records refer to each other by index, as the file does, so that the content is represented as
+/// is, and definitions and complements are sorted by concept, so that the same content always
+/// results in the same text, ready to be compared in version control.
+pub struct RecordWriter<W: Write> {
+    writer: W
+}
+
+impl<W: Write> RecordWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer
+        }
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> Exporter for RecordWriter<W> {
+    fn visit_symbol_array(&mut self, index: usize, text: &str) -> io::Result<()> {
+        writeln!(self.writer, "symbol_array {} {}", index, record_text(text))
+    }
+
+    fn visit_language(&mut self, language: &LanguageInfo) -> io::Result<()> {
+        writeln!(self.writer, "language {} {}", language.code, language.alphabet_count)
+    }
+
+    fn visit_conversion(&mut self, _result: &SdbReadResult, index: usize, conversion: &Conversion) -> io::Result<()> {
+        writeln!(self.writer, "conversion {} {} {} {}", index, conversion.source.index, conversion.target.index, conversion.pairs.len())?;
+        for (source, target) in conversion.pairs.iter() {
+            writeln!(self.writer, "conversion_pair {} {} {}", index, source.index, target.index)?;
+        }
+
+        Ok(())
+    }
+
+    fn visit_max_concept(&mut self, max_concept: usize) -> io::Result<()> {
+        writeln!(self.writer, "max_concept {}", max_concept)
+    }
+
+    fn visit_correlation(&mut self, _result: &SdbReadResult, index: usize, correlation: &BTreeMap<Alphabet, SymbolArrayIndex>) -> io::Result<()> {
+        write!(self.writer, "correlation {}", index)?;
+        for (alphabet, symbol_array) in correlation.iter() {
+            write!(self.writer, " {}:{}", alphabet.index, symbol_array.index)?;
+        }
+
+        writeln!(self.writer)
+    }
+
+    fn visit_correlation_array(&mut self, _result: &SdbReadResult, index: usize, array: &[CorrelationIndex]) -> io::Result<()> {
+        write!(self.writer, "correlation_array {}", index)?;
+        for correlation in array.iter() {
+            write!(self.writer, " {}", correlation.index)?;
+        }
+
+        writeln!(self.writer)
+    }
+
+    fn visit_acceptation(&mut self, _result: &SdbReadResult, index: usize, acceptation: &Acceptation) -> io::Result<()> {
+        writeln!(self.writer, "acceptation {} {} {}", index, acceptation.concept, acceptation.correlation_array_index.index)
+    }
+
+    fn visit_definition(&mut self, _result: &SdbReadResult, concept: usize, definition: &Definition) -> io::Result<()> {
+        write!(self.writer, "definition {} {}", concept, definition.base_concept)?;
+        for complement in definition.complements.iter() {
+            write!(self.writer, " {}", complement)?;
+        }
+
+        writeln!(self.writer)
+    }
+}
+
+/// Returns all records of the given database as text, as written by [RecordWriter].
+pub fn export_records(result: &SdbReadResult) -> String {
+    let writer = export_with(result, RecordWriter::new(Vec::new())).expect("Writing into memory should not fail");
+    String::from_utf8(writer.into_inner()).expect("Records should be valid UTF-8")
+}
+
+/// Name used for the given section within the JSON documents, like "symbol_arrays".
+fn section_key(section: Section) -> &'static str {
+    match section {
+        Section::SymbolArrays => "symbol_arrays",
+        Section::Languages => "languages",
+        Section::Conversions => "conversions",
+        Section::Correlations => "correlations",
+        Section::CorrelationArrays => "correlation_arrays",
+        Section::Acceptations => "acceptations",
+        Section::Definitions => "definitions"
+    }
+}
+
+/// Returns each length found in the histogram with the number of entries having it, as pairs.
+fn histogram_json(histogram: &Histogram) -> JsonValue {
+    JsonValue::Array(histogram.iter().map(|(length, count)| JsonValue::Array(vec![(*length).into(), (*count).into()])).collect())
+}
+
+/// Returns the number of entries in each section, and optionally the distribution of their
+/// lengths, as a JSON document to track the growth of a database over time.
+pub fn export_stats(result: &SdbReadResult, include_histograms: bool) -> JsonValue {
+    let mut fields: Vec<(String, JsonValue)> = analysis::entry_counts(result).into_iter()
+        .map(|(key, count)| (String::from(key), count.into()))
+        .collect();
+
+    if include_histograms {
+        let histograms = analysis::histograms(result);
+        fields.push((String::from("histograms"), object([
+            ("symbol_array_lengths", histogram_json(&histograms.symbol_array_lengths)),
+            ("correlation_array_lengths", histogram_json(&histograms.correlation_array_lengths)),
+            ("alphabets_per_correlation", histogram_json(&histograms.alphabets_per_correlation)),
+            ("complements_per_definition", histogram_json(&histograms.complements_per_definition))
+        ])));
+    }
+
+    JsonValue::Object(fields)
+}
+
+/// Returns the bits used by each section compared to the entropy of its values, as a JSON
+/// document to track the compression of a database over time. Entropy is rounded to whole bits.
+pub fn export_analysis(result: &SdbReadResult) -> JsonValue {
+    let analysis = analysis::analyze(result);
+    let sections: Vec<JsonValue> = analysis.iter().map(|section| object([
+        ("section", section_key(section.section).into()),
+        ("actual_bits", section.actual_bits.into()),
+        ("entropy_bits", (section.entropy_bits.round() as usize).into())
+    ])).collect();
+
+    let actual_bits: usize = analysis.iter().map(|section| section.actual_bits).sum();
+    let entropy_bits: f64 = analysis.iter().map(|section| section.entropy_bits).sum();
+    object([
+        ("sections", JsonValue::Array(sections)),
+        ("actual_bits", actual_bits.into()),
+        ("entropy_bits", (entropy_bits.round() as usize).into())
+    ])
+}
@@ -0,0 +1,178 @@
+use std::ffi::{c_char, CStr, CString};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Cursor, Read};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+use tracing::error;
+use crate::file_utils::{self, ReadError};
+use crate::huffman::InputBitStream;
+use crate::sdb::{Alphabet, CorrelationArrayIndex, SdbReader, SdbReadResult, SDB_HEADER};
+
+/// Opaque handle given to C callers. It must be released with sdb_close.
+pub struct SdbDatabase {
+    result: SdbReadResult
+}
+
+fn read<R: BufRead>(mut input: R) -> Result<SdbReadResult, ReadError> {
+    file_utils::read_header(&mut (&mut input).bytes(), SDB_HEADER)?;
+    SdbReader::new(InputBitStream::from(&mut input)).read()
+}
+
+/// Runs the given decoding, returning NULL if it fails. Panics are caught too, as unwinding into
+/// the C caller would abort the whole application.
+fn into_handle(read: impl FnOnce() -> Result<SdbReadResult, ReadError>) -> *mut SdbDatabase {
+    match panic::catch_unwind(AssertUnwindSafe(read)) {
+        Ok(Ok(result)) => Box::into_raw(Box::new(SdbDatabase {
+            result
+        })),
+        Ok(Err(err)) => {
+            error!("Unable to read database: {}", err.message);
+            ptr::null_mut()
+        },
+        Err(_) => {
+            error!("Unable to read database: the decoder panicked");
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Returns the decoded database behind the given handle, or None if it is NULL.
+///
+/// # Safety
+/// The handle must be NULL or a valid handle not released yet.
+unsafe fn result_of<'a>(database: *const SdbDatabase) -> Option<&'a SdbReadResult> {
+    database.as_ref().map(|database| &database.result)
+}
+
+fn into_c_string(text: String) -> *mut c_char {
+    match CString::new(text) {
+        Ok(text) => text.into_raw(),
+        Err(_) => ptr::null_mut()
+    }
+}
+
+/// Decodes the SDB file at the given path. Returns NULL if the file cannot be opened or decoded.
+///
+/// # Safety
+/// The path must be a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn sdb_open_file(path: *const c_char) -> *mut SdbDatabase {
+    if path.is_null() {
+        return ptr::null_mut();
+    }
+
+    let path = match CStr::from_ptr(path).to_str() {
+        Ok(path) => path,
+        Err(_) => return ptr::null_mut()
+    };
+
+    match File::open(path) {
+        Ok(file) => into_handle(|| read(BufReader::new(file))),
+        Err(err) => {
+            error!("Unable to open file {}: {}", path, err);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Decodes the SDB file content within the given buffer. Returns NULL if it cannot be decoded.
+///
+/// # Safety
+/// The buffer must be valid for reads of the given length. It is not retained after this call.
+#[no_mangle]
+pub unsafe extern "C" fn sdb_open_buffer(data: *const u8, length: usize) -> *mut SdbDatabase {
+    if data.is_null() {
+        return ptr::null_mut();
+    }
+
+    into_handle(|| read(Cursor::new(slice::from_raw_parts(data, length))))
+}
+
+/// Releases a database returned by sdb_open_file or sdb_open_buffer.
+///
+/// # Safety
+/// The handle must be NULL or a handle not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn sdb_close(database: *mut SdbDatabase) {
+    if !database.is_null() {
+        drop(Box::from_raw(database));
+    }
+}
+
+/// Returns the number of alphabets defined in the database, or 0 if the handle is NULL.
+///
+/// # Safety
+/// The handle must be NULL or a valid handle not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn sdb_alphabet_count(database: *const SdbDatabase) -> usize {
+    result_of(database).map_or(0, SdbReadResult::alphabet_count)
+}
+
+/// Returns the number of acceptations in the database, or 0 if the handle is NULL.
+///
+/// # Safety
+/// The handle must be NULL or a valid handle not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn sdb_acceptation_count(database: *const SdbDatabase) -> usize {
+    result_of(database).map_or(0, |result| result.acceptations.len())
+}
+
+/// Returns the concept of the acceptation at the given index, or 0 if the index is out of bounds
+/// or the handle is NULL.
+///
+/// # Safety
+/// The handle must be NULL or a valid handle not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn sdb_acceptation_concept(database: *const SdbDatabase, acceptation: usize) -> usize {
+    result_of(database).and_then(|result| result.acceptations.get(acceptation)).map_or(0, |acc| acc.concept)
+}
+
+/// Returns the text of the acceptation at the given index in the given alphabet, or NULL if the
+/// acceptation has no text for that alphabet or the handle is NULL. The returned string must be
+/// released with sdb_free_string.
+///
+/// # Safety
+/// The handle must be NULL or a valid handle not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn sdb_acceptation_text(database: *const SdbDatabase, acceptation: usize, alphabet: usize) -> *mut c_char {
+    let result = match result_of(database) {
+        Some(result) => result,
+        None => return ptr::null_mut()
+    };
+
+    let correlation_array_index: CorrelationArrayIndex = match result.acceptations.get(acceptation) {
+        Some(acc) => acc.correlation_array_index,
+        None => return ptr::null_mut()
+    };
+
+    let mut texts = result.get_complete_correlation(correlation_array_index);
+    match texts.remove(&Alphabet { index: alphabet }) {
+        Some(text) => into_c_string(text),
+        None => ptr::null_mut()
+    }
+}
+
+/// Returns a text for the given concept, as it is displayed in the dump, or NULL if the handle is
+/// NULL. The returned string must be released with sdb_free_string.
+///
+/// # Safety
+/// The handle must be NULL or a valid handle not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn sdb_concept_text(database: *const SdbDatabase, concept: usize) -> *mut c_char {
+    match result_of(database) {
+        Some(result) => into_c_string(result.concept_to_string(concept)),
+        None => ptr::null_mut()
+    }
+}
+
+/// Releases a string returned by any other function in this API.
+///
+/// # Safety
+/// The string must be NULL or a string returned by this API and not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn sdb_free_string(text: *mut c_char) {
+    if !text.is_null() {
+        drop(CString::from_raw(text));
+    }
+}
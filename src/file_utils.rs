@@ -1,53 +1,132 @@
-use std::fs::File;
-use std::io::Bytes;
+use alloc::collections::VecDeque;
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::error::Error;
+use core::fmt::{Display, Formatter};
+use crate::io::{self, Bytes, Read};
+use crate::sdb::Section;
+
+/// Kind of failure found while reading a file, for callers to react differently to each of them.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ReadErrorKind {
+    /// The underlying reader failed, or the file ended before expected.
+    Io,
+
+    /// The file does not start with the expected header, so it is not an SDB file.
+    BadMagic,
+
+    /// The file is an SDB file, but in a version of the format that is not supported. The
+    /// version declared by the file is included.
+    UnsupportedVersion(u8),
+
+    /// The bits read do not match any symbol of the table in use. Section is the one being
+    /// decoded, if known.
+    InvalidSymbol {
+        section: Option<Section>
+    },
+
+    /// A decoded value is out of the range of valid values, or refers to entries that do not
+    /// exist. Section is the one being decoded and index the record within it, if known.
+    RangeViolation {
+        section: Option<Section>,
+        index: Option<usize>
+    },
+
+    /// The decoding was cancelled through a cancellation token before finishing.
+    Cancelled
+}
 
 #[derive(Debug)]
 pub struct ReadError {
+    pub kind: ReadErrorKind,
     pub message: String
 }
 
 impl ReadError {
-    fn new(message: &str) -> ReadError {
+    pub fn new(kind: ReadErrorKind, message: impl Into<String>) -> ReadError {
         ReadError {
-            message: message.to_string()
+            kind,
+            message: message.into()
         }
     }
+
+    pub fn invalid_symbol(message: impl Into<String>) -> ReadError {
+        Self::new(ReadErrorKind::InvalidSymbol { section: None }, message)
+    }
+
+    pub fn range_violation(message: impl Into<String>) -> ReadError {
+        Self::new(ReadErrorKind::RangeViolation { section: None, index: None }, message)
+    }
 }
 
-impl From<&str> for ReadError {
-    fn from(message: &str) -> Self {
-        Self::new(message)
+impl From<io::Error> for ReadError {
+    fn from(err: io::Error) -> Self {
+        Self::new(ReadErrorKind::Io, err.to_string())
     }
 }
 
-pub fn read_u8(bytes: &mut Bytes<File>) -> Result<u8, ReadError> {
+impl Display for ReadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for ReadError {}
+
+pub fn read_u8<R: Read>(bytes: &mut Bytes<R>) -> Result<u8, ReadError> {
     match bytes.next() {
-        None => Err(ReadError::new("Unexpected end of file")),
+        None => Err(ReadError::new(ReadErrorKind::Io, "Unexpected end of file")),
         Some(result) => match result {
-            Err(err) => Err(ReadError::new(&err.to_string())),
+            Err(err) => Err(ReadError::from(err)),
             Ok(x) => Ok(x)
         }
     }
 }
 
-fn assert_next_is_same_u8(bytes: &mut Bytes<File>, value: u8) -> Result<bool, ReadError> {
-    match read_u8(bytes) {
-        Err(x) => Err(x),
-        Ok(x) => {
-            if x == value {
-                Ok(true)
-            }
-            else {
-                Err(ReadError::new(&format!("Unexpected character 0x{:X}, expectation was 0x{:X}", x, value)))
-            }
+/// Checks that the next bytes match the given header, whose last byte is the latest version of
+/// the format supported. Returns the version declared by the file.
+pub fn read_header<R: Read>(bytes: &mut Bytes<R>, header: &str) -> Result<u8, ReadError> {
+    let (version, magic) = header.as_bytes().split_last().expect("Header should include the version");
+    for expected_value in magic {
+        let value = read_u8(bytes)?;
+        if value != *expected_value {
+            return Err(ReadError::new(ReadErrorKind::BadMagic, format!("Unexpected character 0x{:X}, expectation was 0x{:X}", value, expected_value)));
         }
     }
+
+    read_version(bytes, *version)
 }
 
-pub fn assert_next_is_same_text(bytes: &mut Bytes<File>, text: &str) -> Result<bool, ReadError> {
-    for expected_value in text.bytes() {
-        assert_next_is_same_u8(bytes, expected_value)?;
+/// Reads the version declared by the file, failing if it is not the given one.
+fn read_version<R: Read>(bytes: &mut Bytes<R>, latest_version: u8) -> Result<u8, ReadError> {
+    let value = read_u8(bytes)?;
+    if value != latest_version {
+        return Err(ReadError::new(ReadErrorKind::UnsupportedVersion(value), format!("File declares format version {}, this tool supports 1..={}", value, latest_version)));
+    }
+
+    Ok(value)
+}
+
+/// Skips bytes until the given header is found, and checks its version as read_header does.
+/// Returns the number of bytes skipped before the header.
+pub fn find_header<R: Read>(bytes: &mut Bytes<R>, header: &str) -> Result<usize, ReadError> {
+    let (version, magic) = header.as_bytes().split_last().expect("Header should include the version");
+    let mut window: VecDeque<u8> = VecDeque::with_capacity(magic.len());
+    let mut skipped = 0;
+    while !window.iter().eq(magic.iter()) {
+        let value = match bytes.next() {
+            None => return Err(ReadError::new(ReadErrorKind::BadMagic, "Header not found before the end of file")),
+            Some(result) => result?
+        };
+
+        if window.len() == magic.len() {
+            window.pop_front();
+            skipped += 1;
+        }
+
+        window.push_back(value);
     }
 
-    return Ok(true)
+    read_version(bytes, *version)?;
+    Ok(skipped)
 }
@@ -0,0 +1,236 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use crate::sdb::SdbReadResult;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// FNV-1a hash, used instead of the std hashers because its value must not change between
+/// platforms or compiler versions.
+struct FingerprintHasher {
+    state: u64
+}
+
+impl FingerprintHasher {
+    fn new() -> Self {
+        Self {
+            state: FNV_OFFSET_BASIS
+        }
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.state ^= u64::from(*byte);
+            self.state = self.state.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn write_usize(&mut self, value: usize) {
+        self.write_bytes(&u64::try_from(value).unwrap().to_le_bytes());
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    fn write_str(&mut self, text: &str) {
+        self.write_usize(text.len());
+        self.write_bytes(text.as_bytes());
+    }
+}
+
+/// Hash of the content of a database.
+///
+/// It only depends on the decoded values, and not on the Huffman tables chosen to encode them,
+/// so two files encoding the same content in different ways share the same fingerprint.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Fingerprint {
+    value: u64
+}
+
+impl Display for Fingerprint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:016x}", self.value)
+    }
+}
+
+impl TryFrom<&str> for Fingerprint {
+    type Error = String;
+
+    fn try_from(text: &str) -> Result<Self, Self::Error> {
+        match u64::from_str_radix(text, 16) {
+            Ok(value) if text.len() == 16 => Ok(Self {
+                value
+            }),
+            _ => Err(format!("Invalid fingerprint {}, expected 16 hexadecimal digits", text))
+        }
+    }
+}
+
+pub fn fingerprint(result: &SdbReadResult) -> Fingerprint {
+    let mut hasher = FingerprintHasher::new();
+
+    hasher.write_usize(result.symbol_arrays.len());
+    for text in result.symbol_arrays.iter() {
+        hasher.write_str(text);
+    }
+
+    hasher.write_usize(result.languages.len());
+    for language in result.languages.iter() {
+        hasher.write_str(&language.code.to_string());
+        hasher.write_usize(language.number_of_alphabets);
+    }
+
+    hasher.write_usize(result.conversions.len());
+    for conversion in result.conversions.iter() {
+        hasher.write_usize(conversion.source.index);
+        hasher.write_usize(conversion.target.index);
+        hasher.write_usize(conversion.pairs.len());
+        for (source, target) in conversion.pairs.iter() {
+            hasher.write_usize(source.index);
+            hasher.write_usize(target.index);
+        }
+    }
+
+    hasher.write_usize(result.max_concept);
+
+    hasher.write_usize(result.correlations.len());
+    for correlation in result.correlations.iter() {
+        hasher.write_usize(correlation.len());
+        for (alphabet, symbol_array) in correlation.iter() {
+            hasher.write_usize(alphabet.index);
+            hasher.write_usize(symbol_array.index);
+        }
+    }
+
+    hasher.write_usize(result.correlation_arrays.len());
+    for array in result.correlation_arrays.iter() {
+        hasher.write_usize(array.len());
+        for correlation in array.iter() {
+            hasher.write_usize(correlation.index);
+        }
+    }
+
+    hasher.write_usize(result.acceptations.len());
+    for acceptation in result.acceptations.iter() {
+        hasher.write_usize(acceptation.concept);
+        hasher.write_usize(acceptation.correlation_array_index.index);
+    }
+
+    hasher.write_usize(result.definitions.len());
+    for (concept, definition) in result.definitions.iter() {
+        hasher.write_usize(*concept);
+        hasher.write_usize(definition.base_concept);
+        hasher.write_usize(definition.complements.len());
+        for complement in definition.complements.iter() {
+            hasher.write_usize(*complement);
+        }
+    }
+
+    Fingerprint {
+        value: hasher.state
+    }
+}
+
+/// Hashes the given values as a set, so that neither their order nor repeated values matter.
+fn hash_sorted(mut values: Vec<u64>) -> u64 {
+    values.sort();
+    values.dedup();
+    let mut hasher = FingerprintHasher::new();
+    hasher.write_usize(values.len());
+    for value in values {
+        hasher.write_u64(value);
+    }
+
+    hasher.state
+}
+
+/// Hash of the content of a database that does not depend on the indexes assigned to its
+/// entries either, so two files produced by different encoders share it as long as they
+/// describe the same content, even if their entries are sorted or deduplicated differently.
+///
+/// Alphabets are identified by their language and their position within it, acceptations by
+/// the texts of their correlations, and concepts by the acceptations they have. Symbol arrays
+/// and correlations are only considered through the entries referring to them, so unused ones
+/// are ignored. Concepts without acceptations cannot be told apart, and so they are all
+/// considered the same concept.
+pub fn semantic_fingerprint(result: &SdbReadResult) -> Fingerprint {
+    let mut hasher = FingerprintHasher::new();
+
+    let mut languages: Vec<(String, usize)> = result.languages.iter().map(|language| (language.code.to_string(), language.number_of_alphabets)).collect();
+    languages.sort();
+    hasher.write_usize(languages.len());
+    for (code, number_of_alphabets) in languages {
+        hasher.write_str(&code);
+        hasher.write_usize(number_of_alphabets);
+    }
+
+    let conversions: Vec<u64> = result.conversions.iter().map(|conversion| {
+        let pairs: Vec<u64> = conversion.pairs.iter().map(|(source, target)| {
+            let mut pair_hasher = FingerprintHasher::new();
+            pair_hasher.write_str(result.symbol_arrays.get(source.index).unwrap_or_default());
+            pair_hasher.write_str(result.symbol_arrays.get(target.index).unwrap_or_default());
+            pair_hasher.state
+        }).collect();
+
+        let mut conversion_hasher = FingerprintHasher::new();
+        conversion_hasher.write_str(&result.alphabet_label(conversion.source));
+        conversion_hasher.write_str(&result.alphabet_label(conversion.target));
+        conversion_hasher.write_u64(hash_sorted(pairs));
+        conversion_hasher.state
+    }).collect();
+    hasher.write_u64(hash_sorted(conversions));
+
+    let correlations: Vec<u64> = result.correlations.iter().map(|correlation| {
+        let mut entries: Vec<(String, &str)> = correlation.iter().map(|(alphabet, symbol_array)| (result.alphabet_label(*alphabet), result.symbol_arrays.get(symbol_array.index).unwrap_or_default())).collect();
+        entries.sort();
+        let mut correlation_hasher = FingerprintHasher::new();
+        correlation_hasher.write_usize(entries.len());
+        for (alphabet, text) in entries {
+            correlation_hasher.write_str(&alphabet);
+            correlation_hasher.write_str(text);
+        }
+
+        correlation_hasher.state
+    }).collect();
+
+    let correlation_arrays: Vec<u64> = result.correlation_arrays.iter().map(|array| {
+        let mut array_hasher = FingerprintHasher::new();
+        array_hasher.write_usize(array.len());
+        for correlation in array.iter() {
+            array_hasher.write_u64(correlations.get(correlation.index).copied().unwrap_or_default());
+        }
+
+        array_hasher.state
+    }).collect();
+
+    let mut concept_acceptations: HashMap<usize, Vec<u64>> = HashMap::new();
+    for acceptation in result.acceptations.iter() {
+        let array = correlation_arrays.get(acceptation.correlation_array_index.index).copied().unwrap_or_default();
+        concept_acceptations.entry(acceptation.concept).or_default().push(array);
+    }
+
+    let concept_keys: HashMap<usize, u64> = concept_acceptations.into_iter().map(|(concept, acceptations)| (concept, hash_sorted(acceptations))).collect();
+    let concept_key = |concept: usize| concept_keys.get(&concept).copied().unwrap_or_default();
+
+    let acceptations: Vec<u64> = result.acceptations.iter().map(|acceptation| {
+        let mut acceptation_hasher = FingerprintHasher::new();
+        acceptation_hasher.write_u64(concept_key(acceptation.concept));
+        acceptation_hasher.write_u64(correlation_arrays.get(acceptation.correlation_array_index.index).copied().unwrap_or_default());
+        acceptation_hasher.state
+    }).collect();
+    hasher.write_u64(hash_sorted(acceptations));
+
+    let definitions: Vec<u64> = result.definitions.iter().map(|(concept, definition)| {
+        let mut definition_hasher = FingerprintHasher::new();
+        definition_hasher.write_u64(concept_key(*concept));
+        definition_hasher.write_u64(concept_key(definition.base_concept));
+        definition_hasher.write_u64(hash_sorted(definition.complements.iter().map(|complement| concept_key(*complement)).collect()));
+        definition_hasher.state
+    }).collect();
+    hasher.write_u64(hash_sorted(definitions));
+
+    Fingerprint {
+        value: hasher.state
+    }
+}
@@ -1,79 +1,191 @@
-use std::fmt::Display;
-use std::fs::File;
-use std::io::Bytes;
-use std::ops::Range;
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BinaryHeap};
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+use core::fmt::Display;
+use core::ops::Range;
+#[cfg(feature = "std")]
+use std::io::{self, Write};
+use crate::io::Read;
 use crate::file_utils;
-use file_utils::ReadError;
+use file_utils::{ReadError, ReadErrorKind};
+use tracing::trace;
 
-pub struct InputBitStream<'a> {
-    bytes: &'a mut Bytes<File>,
-    buffer: u8,
-    remaining: u32
+/// Maximum number of bits of a code. Symbol indexes are 32-bit values, so longer codes can only be
+/// found in corrupted files.
+const MAX_CODE_BITS: u32 = 32;
+
+pub struct InputBitStream<'a, R: Read> {
+    reader: &'a mut R,
+
+    /// Bits already fetched from the reader but not consumed yet. The next bit to be read is
+    /// always the least significant one.
+    buffer: u64,
+
+    /// Number of valid bits within the buffer.
+    remaining: u32,
+
+    bits_read: usize,
+
+    /// Error found while refilling the buffer, only reported once all bits before it are consumed.
+    pending_error: Option<ReadError>
 }
 
-impl<'a> InputBitStream<'a> {
+impl<'a, R: Read> InputBitStream<'a, R> {
+    /// Number of bytes consumed from the underlying reader since this stream was created. Bytes
+    /// fetched in advance into the buffer are not counted until any of their bits is read.
+    pub fn bytes_read(&self) -> usize {
+        self.bits_read.div_ceil(8)
+    }
+
+    /// Number of bits consumed since this stream was created.
+    pub fn bits_read(&self) -> usize {
+        self.bits_read
+    }
+
+    /// Fetches, in a single read, as many whole bytes as fit in the buffer. This must only be
+    /// called with at least one free byte in the buffer. Reaching the end of the input is only an
+    /// error if the buffer is still empty.
+    fn refill(&mut self) -> Result<(), ReadError> {
+        if self.pending_error.is_none() {
+            let mut chunk = [0u8; 8];
+            let free = ((u64::BITS - self.remaining) / 8) as usize;
+            match self.reader.read(&mut chunk[..free]) {
+                Ok(0) => self.pending_error = Some(ReadError::new(ReadErrorKind::Io, "Unexpected end of file")),
+                Ok(count) => {
+                    self.buffer |= u64::from_le_bytes(chunk) << self.remaining;
+                    self.remaining += count as u32 * 8;
+                },
+                Err(err) => self.pending_error = Some(ReadError::from(err))
+            }
+        }
+
+        if self.remaining == 0 {
+            Err(self.pending_error.take().expect("Refill should either fetch bytes or fail"))
+        }
+        else {
+            Ok(())
+        }
+    }
+
+    /// Marks the given number of buffered bits as read.
+    fn consume(&mut self, count: u32) {
+        self.buffer = self.buffer.checked_shr(count).unwrap_or(0);
+        self.remaining -= count;
+        self.bits_read += count as usize;
+    }
+
     pub fn read_boolean(&mut self) -> Result<bool, ReadError> {
         if self.remaining == 0 {
-            self.buffer = file_utils::read_u8(self.bytes)?;
-            self.remaining = 8;
+            self.refill()?;
         }
 
         let result = (self.buffer & 1) != 0;
-        self.buffer >>= 1;
-        self.remaining -= 1;
+        self.consume(1);
         Ok(result)
     }
 
+    /// Skips the given number of bits without decoding them.
+    pub fn skip_bits(&mut self, count: usize) -> Result<(), ReadError> {
+        let mut count = count;
+        while count > 0 {
+            if self.remaining == 0 {
+                self.refill()?;
+            }
+
+            let skipped = self.remaining.min(u32::try_from(count).unwrap_or(u32::MAX));
+            self.consume(skipped);
+            count -= skipped as usize;
+        }
+
+        Ok(())
+    }
+
+    /// Skips the given number of symbols encoded with the given table, without resolving them.
+    pub fn skip_symbols<S, T : HuffmanTable<S>>(&mut self, table: &T, count: usize) -> Result<(), ReadError> {
+        for _ in 0..count {
+            self.read_code(table)?;
+        }
+
+        Ok(())
+    }
+
     pub fn read_symbol<S, T : HuffmanTable<S>>(&mut self, table: &T) -> Result<S, ReadError> {
+        let (bits, level_index) = self.read_code(table)?;
+        table.get_symbol(bits, level_index).map_err(ReadError::invalid_symbol)
+    }
+
+    /// Reads the next code for the given table, returning its number of bits and its index
+    /// within all the symbols with that number of bits.
+    ///
+    /// Instead of reading one bit at a time, the buffered bits are reversed once, so that the
+    /// code for each number of bits can be peeked by shifting them, and all bits of the code are
+    /// consumed together once it is found.
+    fn read_code<S, T : HuffmanTable<S>>(&mut self, table: &T) -> Result<(u32, u32), ReadError> {
         if table.symbols_with_bits(0) > 0 {
-            Ok(table.get_symbol(0, 0)?)
+            return Ok((0, 0));
         }
-        else {
-            let mut value = 0u32;
-            let mut base = 0u32;
-            let mut bits = 1u32;
 
-            loop {
-                value <<= 1;
-                if self.read_boolean()? {
-                    value += 1;
+        // Codes longer than the buffered bits are resolved in several windows
+        let mut prefix = 0u64;
+        let mut prefix_bits = 0u32;
+        let mut base = 0u64;
+        loop {
+            if self.remaining < MAX_CODE_BITS {
+                self.refill()?;
+            }
+
+            let window = self.buffer.reverse_bits();
+            for taken in 1..=self.remaining {
+                let bits = prefix_bits + taken;
+                if bits > MAX_CODE_BITS {
+                    return Err(ReadError::invalid_symbol(format!("No symbol found within the first {} bits", MAX_CODE_BITS)));
                 }
 
                 base <<= 1;
-                let level_length = table.symbols_with_bits(bits);
-                let level_index = value - base;
-                if level_index < level_length {
-                    return Ok(table.get_symbol(bits, level_index)?);
+                let value = (prefix << taken) | (window >> (u64::BITS - taken));
+                let level_length = u64::from(table.symbols_with_bits(bits));
+                if value - base < level_length {
+                    self.consume(taken);
+                    return Ok((bits, (value - base) as u32));
                 }
 
                 base += level_length;
-                bits += 1;
             }
+
+            let taken = self.remaining;
+            prefix = (prefix << taken) | (window >> (u64::BITS - taken));
+            prefix_bits += taken;
+            self.consume(taken);
         }
     }
 
     pub fn read_diff_u32<T: HuffmanTable<u32>>(&mut self, table: &T, previous: u32) -> Result<u32, ReadError> {
-        Ok(previous + self.read_symbol(table)? + 1u32)
+        previous.checked_add(self.read_symbol(table)?).and_then(|value| value.checked_add(1))
+            .ok_or_else(|| ReadError::invalid_symbol("Out of range"))
     }
 
     pub fn read_diff_i32<T: HuffmanTable<u32>>(&mut self, table: &T, previous: i32) -> Result<i32, ReadError> {
-        match i32::try_from(self.read_symbol(table)?) {
-            Ok(x) => Ok(previous + x + 1),
-            Err(_) => panic!("Out of range")
-        }
+        i32::try_from(self.read_symbol(table)?).ok()
+            .and_then(|x| previous.checked_add(x))
+            .and_then(|value| value.checked_add(1))
+            .ok_or_else(|| ReadError::invalid_symbol("Out of range"))
     }
 
     pub fn read_character<T: HuffmanTable<u32>>(&mut self, table: &T) -> Result<char, ReadError> {
         match char::from_u32(self.read_symbol(table)?) {
             Some(ch) => Ok(ch),
-            None => Err(ReadError::from("Unable to convert char"))
+            None => Err(ReadError::invalid_symbol("Unable to convert char"))
         }
     }
 
     pub fn read_diff_character<T: HuffmanTable<u32>>(&mut self, table: &T, previous: char) -> Result<char, ReadError> {
-        match char::from_u32(self.read_symbol(table)? + (previous as u32) + 1) {
+        let value = self.read_symbol(table)?.checked_add(previous as u32 + 1);
+        match value.and_then(char::from_u32) {
             Some(ch) => Ok(ch),
-            None => Err(ReadError::from("Unable to convert char"))
+            None => Err(ReadError::invalid_symbol("Unable to convert char"))
         }
     }
 
@@ -88,39 +200,38 @@ impl<'a> InputBitStream<'a> {
             max <<= 1;
         }
 
-        let mut level_indexes: Vec<usize> = Vec::new();
-        let mut symbols: Vec<S> = Vec::new();
+        trace!(?level_lengths, "Huffman table level lengths read");
+        let mut level_indexes: Vec<u32> = Vec::with_capacity(level_lengths.len() - 1);
+        let mut symbols: Vec<S> = Vec::with_capacity(level_lengths.iter().map(|&level_length| level_length as usize).sum());
 
-        for index in 0..level_lengths.len() {
+        for (index, &level_length) in level_lengths.iter().enumerate() {
             if index > 0 {
-                level_indexes.push(symbols.len());
+                level_indexes.push(symbols.len() as u32);
             }
 
-            let level_length = level_lengths[index];
             if level_length > 0 {
-                let mut element = supplier(self, &table1)?;
+                let mut element = supplier(self, table1)?;
                 symbols.push(element);
 
                 for _ in 1..level_length {
-                    element = diff_supplier(self, &table2, element)?;
+                    element = diff_supplier(self, table2, element)?;
                     symbols.push(element);
                 }
             }
         }
 
-        Ok(DefinedHuffmanTable {
-            level_indexes,
-            symbols
-        })
+        Ok(DefinedHuffmanTable::new(level_indexes, symbols))
     }
 }
 
-impl<'a> From<&'a mut Bytes<File>> for InputBitStream<'a> {
-    fn from(bytes: &'a mut Bytes<File>) -> InputBitStream<'a> {
+impl<'a, R: Read> From<&'a mut R> for InputBitStream<'a, R> {
+    fn from(reader: &'a mut R) -> InputBitStream<'a, R> {
         InputBitStream {
-            bytes,
+            reader,
             buffer: 0,
-            remaining: 0
+            remaining: 0,
+            bits_read: 0,
+            pending_error: None
         }
     }
 }
@@ -130,6 +241,13 @@ pub trait HuffmanTable<T> {
     fn get_symbol(&self, bits: u32, index: u32) -> Result<T, &str>;
 }
 
+/// Tables that can also be used for encoding, by locating the code for a given symbol.
+pub trait HuffmanEncoder<T>: HuffmanTable<T> {
+    /// Returns the number of bits of the code for the given symbol, and its index among the
+    /// symbols with that number of bits. None is returned if the symbol is not in the table.
+    fn find_symbol(&self, symbol: T) -> Option<(u32, u32)>;
+}
+
 pub struct NaturalNumberHuffmanTable {
     alignment: u32
 }
@@ -144,8 +262,8 @@ impl NaturalNumberHuffmanTable {
 
 impl HuffmanTable<u32> for NaturalNumberHuffmanTable {
     fn symbols_with_bits(&self, bits: u32) -> u32 {
-        if bits > 0 && bits % self.alignment == 0 {
-            1 << ((bits / self.alignment) * (self.alignment - 1))
+        if bits > 0 && bits.is_multiple_of(self.alignment) {
+            1u32.checked_shl((bits / self.alignment) * (self.alignment - 1)).unwrap_or(0)
         }
         else {
             0
@@ -153,7 +271,7 @@ impl HuffmanTable<u32> for NaturalNumberHuffmanTable {
     }
 
     fn get_symbol(&self, bits: u32, index: u32) -> Result<u32, &str> {
-        if bits == 0 || bits % self.alignment != 0 {
+        if bits == 0 || !bits.is_multiple_of(self.alignment) {
             Err("Invalid symbol")
         }
         else {
@@ -169,6 +287,24 @@ impl HuffmanTable<u32> for NaturalNumberHuffmanTable {
     }
 }
 
+impl HuffmanEncoder<u32> for NaturalNumberHuffmanTable {
+    fn find_symbol(&self, symbol: u32) -> Option<(u32, u32)> {
+        let mut base = 0u32;
+        let mut bits = self.alignment;
+        while bits <= MAX_CODE_BITS {
+            let level_length = self.symbols_with_bits(bits);
+            if symbol - base < level_length {
+                return Some((bits, symbol - base));
+            }
+
+            base += level_length;
+            bits += self.alignment;
+        }
+
+        None
+    }
+}
+
 pub struct NaturalUsizeHuffmanTable {
     alignment: u32
 }
@@ -183,8 +319,8 @@ impl NaturalUsizeHuffmanTable {
 
 impl HuffmanTable<usize> for NaturalUsizeHuffmanTable {
     fn symbols_with_bits(&self, bits: u32) -> u32 {
-        if bits > 0 && bits % self.alignment == 0 {
-            1 << ((bits / self.alignment) * (self.alignment - 1))
+        if bits > 0 && bits.is_multiple_of(self.alignment) {
+            1u32.checked_shl((bits / self.alignment) * (self.alignment - 1)).unwrap_or(0)
         }
         else {
             0
@@ -192,7 +328,7 @@ impl HuffmanTable<usize> for NaturalUsizeHuffmanTable {
     }
 
     fn get_symbol(&self, bits: u32, index: u32) -> Result<usize, &str> {
-        if bits == 0 || bits % self.alignment != 0 {
+        if bits == 0 || !bits.is_multiple_of(self.alignment) {
             Err("Invalid symbol")
         }
         else {
@@ -208,6 +344,24 @@ impl HuffmanTable<usize> for NaturalUsizeHuffmanTable {
     }
 }
 
+impl HuffmanEncoder<usize> for NaturalUsizeHuffmanTable {
+    fn find_symbol(&self, symbol: usize) -> Option<(u32, u32)> {
+        let mut base = 0usize;
+        let mut bits = self.alignment;
+        while bits <= MAX_CODE_BITS {
+            let level_length = usize::try_from(self.symbols_with_bits(bits)).unwrap();
+            if symbol - base < level_length {
+                return Some((bits, u32::try_from(symbol - base).unwrap()));
+            }
+
+            base += level_length;
+            bits += self.alignment;
+        }
+
+        None
+    }
+}
+
 pub struct IntegerNumberHuffmanTable {
     alignment: u32
 }
@@ -222,8 +376,8 @@ impl IntegerNumberHuffmanTable {
 
 impl HuffmanTable<i32> for IntegerNumberHuffmanTable {
     fn symbols_with_bits(&self, bits: u32) -> u32 {
-        if bits > 0 && bits % self.alignment == 0 {
-            1 << ((bits / self.alignment) * (self.alignment - 1))
+        if bits > 0 && bits.is_multiple_of(self.alignment) {
+            1u32.checked_shl((bits / self.alignment) * (self.alignment - 1)).unwrap_or(0)
         }
         else {
             0
@@ -231,32 +385,27 @@ impl HuffmanTable<i32> for IntegerNumberHuffmanTable {
     }
 
     fn get_symbol(&self, bits: u32, index: u32) -> Result<i32, &str> {
-        if bits == 0 || bits % self.alignment != 0 {
+        if bits == 0 || !bits.is_multiple_of(self.alignment) {
             Err("Invalid symbol")
         }
         else {
+            // The first half of each level holds the positive values following the ones in shorter
+            // levels, and the second half the same number of negative values preceding them
             let symbols_per_segment = self.symbols_with_bits(bits) / 2;
             let segment_alignment = self.alignment - 1;
             let mut base = 0i32;
 
             Ok(if index < symbols_per_segment {
                 let multiplier = (bits - 1) / self.alignment;
-                if multiplier > 0 {
-                    let mut exp = multiplier * segment_alignment - 1;
-                    while exp > 0 {
-                        base += 1 << exp;
-                        exp -= segment_alignment;
-                    }
+                for segment in 1..=multiplier {
+                    base += 1 << (segment * segment_alignment - 1);
                 }
 
                 base + i32::try_from(index).unwrap()
             }
             else {
-                let mut base = 0i32;
-                let mut exp = (bits / self.alignment) * segment_alignment - 1;
-                while exp > 0 {
-                    base -= 1 << exp;
-                    exp -= segment_alignment;
+                for segment in 1..=(bits / self.alignment) {
+                    base -= 1 << (segment * segment_alignment - 1);
                 }
 
                 base + i32::try_from(index - symbols_per_segment).unwrap()
@@ -265,6 +414,28 @@ impl HuffmanTable<i32> for IntegerNumberHuffmanTable {
     }
 }
 
+impl HuffmanEncoder<i32> for IntegerNumberHuffmanTable {
+    fn find_symbol(&self, symbol: i32) -> Option<(u32, u32)> {
+        let mut bits = self.alignment;
+        while bits < 32 {
+            let symbols_per_segment = self.symbols_with_bits(bits) / 2;
+            let positive_base = self.get_symbol(bits, 0).ok()?;
+            let negative_base = self.get_symbol(bits, symbols_per_segment).ok()?;
+            if symbol >= positive_base && symbol - positive_base < i32::try_from(symbols_per_segment).unwrap() {
+                return Some((bits, u32::try_from(symbol - positive_base).unwrap()));
+            }
+
+            if symbol >= negative_base && symbol - negative_base < i32::try_from(symbols_per_segment).unwrap() {
+                return Some((bits, symbols_per_segment + u32::try_from(symbol - negative_base).unwrap()));
+            }
+
+            bits += self.alignment;
+        }
+
+        None
+    }
+}
+
 pub struct RangedIntegerHuffmanTable {
     min: u32,
     max: u32,
@@ -327,6 +498,20 @@ impl HuffmanTable<u32> for RangedIntegerHuffmanTable {
     }
 }
 
+impl HuffmanEncoder<u32> for RangedIntegerHuffmanTable {
+    fn find_symbol(&self, symbol: u32) -> Option<(u32, u32)> {
+        if symbol < self.min || symbol > self.max {
+            None
+        }
+        else if symbol - self.min < self.limit {
+            Some((self.max_bits - 1, symbol - self.min))
+        }
+        else {
+            Some((self.max_bits, symbol - self.min - self.limit))
+        }
+    }
+}
+
 pub struct RangedNaturalUsizeHuffmanTable {
     min: usize,
     max: usize,
@@ -389,9 +574,30 @@ impl HuffmanTable<usize> for RangedNaturalUsizeHuffmanTable {
     }
 }
 
+impl HuffmanEncoder<usize> for RangedNaturalUsizeHuffmanTable {
+    fn find_symbol(&self, symbol: usize) -> Option<(u32, u32)> {
+        if symbol < self.min || symbol > self.max {
+            return None;
+        }
+
+        let offset = u32::try_from(symbol - self.min).unwrap();
+        if offset < self.limit {
+            Some((self.max_bits - 1, offset))
+        }
+        else {
+            Some((self.max_bits, offset - self.limit))
+        }
+    }
+}
+
+/// Huffman table whose symbols are explicitly listed, sorted by their number of bits.
+///
+/// As big databases may hold thousands of them, the symbols of all levels are kept in a single
+/// boxed slice, and each level is only identified by the position where it starts.
+#[derive(Clone)]
 pub struct DefinedHuffmanTable<S> {
-    level_indexes: Vec<usize>,
-    symbols: Vec<S>
+    level_indexes: Box<[u32]>,
+    symbols: Box<[S]>
 }
 
 impl<S: Copy> HuffmanTable<S> for DefinedHuffmanTable<S> {
@@ -404,13 +610,13 @@ impl<S: Copy> HuffmanTable<S> for DefinedHuffmanTable<S> {
         };
 
         let next_level_index = if self.level_indexes.len() == (bits as usize) {
-            self.symbols.len()
+            self.symbols.len() as u32
         }
         else {
             self.level_indexes[bits as usize]
         };
 
-        (next_level_index - level_index) as u32
+        next_level_index - level_index
     }
 
     fn get_symbol(&self, bits: u32, index: u32) -> Result<S, &str> {
@@ -421,6 +627,395 @@ impl<S: Copy> HuffmanTable<S> for DefinedHuffmanTable<S> {
             self.level_indexes[(bits - 1) as usize]
         };
 
-        Ok(self.symbols[offset + (index as usize)])
+        Ok(self.symbols[(offset + index) as usize])
+    }
+}
+
+impl<S: Copy + Ord> DefinedHuffmanTable<S> {
+    /// Builds the optimal table for the given symbol frequencies. Symbols sharing the same number
+    /// of bits are sorted, as expected by the stream format.
+    ///
+    /// At least one symbol must be given.
+    pub fn from_frequencies(frequencies: &BTreeMap<S, usize>) -> Self {
+        // Nodes are identified by their position in the parents vector, leaves first
+        let mut parents: Vec<usize> = vec![usize::MAX; frequencies.len()];
+        let mut heap: BinaryHeap<Reverse<(usize, usize)>> = frequencies.values().enumerate().map(|(node, frequency)| Reverse((*frequency, node))).collect();
+        while heap.len() > 1 {
+            let Reverse((frequency_a, node_a)) = heap.pop().unwrap();
+            let Reverse((frequency_b, node_b)) = heap.pop().unwrap();
+            let parent = parents.len();
+            parents.push(usize::MAX);
+            parents[node_a] = parent;
+            parents[node_b] = parent;
+            heap.push(Reverse((frequency_a + frequency_b, parent)));
+        }
+
+        let mut levels: Vec<Vec<S>> = Vec::new();
+        for (node, symbol) in frequencies.keys().enumerate() {
+            let mut bits = 0;
+            let mut current = node;
+            while parents[current] != usize::MAX {
+                current = parents[current];
+                bits += 1;
+            }
+
+            while levels.len() <= bits {
+                levels.push(Vec::new());
+            }
+            levels[bits].push(*symbol);
+        }
+
+        let mut level_indexes: Vec<u32> = Vec::with_capacity(levels.len() - 1);
+        let mut symbols: Vec<S> = Vec::with_capacity(frequencies.len());
+        for (bits, level) in levels.into_iter().enumerate() {
+            if bits > 0 {
+                level_indexes.push(symbols.len() as u32);
+            }
+
+            // Frequencies are iterated in order, so symbols within each level are already sorted
+            symbols.extend(level);
+        }
+
+        Self::new(level_indexes, symbols)
+    }
+}
+
+impl<S> DefinedHuffmanTable<S> {
+    /// Builds a table from the position where each level but the first one starts within the
+    /// given symbols. Any spare capacity of the vectors is released when boxing them, as tables
+    /// are kept for the whole decoding.
+    fn new(level_indexes: Vec<u32>, symbols: Vec<S>) -> Self {
+        Self {
+            level_indexes: level_indexes.into_boxed_slice(),
+            symbols: symbols.into_boxed_slice()
+        }
+    }
+
+    /// Number of levels in the table, that is, one more than the maximum number of bits used by a
+    /// symbol. Levels are counted even when no symbol uses their number of bits.
+    pub fn level_count(&self) -> usize {
+        self.level_indexes.len() + 1
+    }
+
+    /// Total number of symbols within the table.
+    pub fn symbol_count(&self) -> usize {
+        self.symbols.len()
+    }
+
+    /// Returns the symbols encoded with the given number of bits, in the order their codes are
+    /// assigned. This is empty for levels without symbols, and panics if the level does not exist.
+    pub fn level(&self, bits: usize) -> &[S] {
+        let start = if bits == 0 {
+            0
+        }
+        else {
+            self.level_indexes[bits - 1] as usize
+        };
+
+        let end = if bits == self.level_indexes.len() {
+            self.symbols.len()
+        }
+        else {
+            self.level_indexes[bits] as usize
+        };
+
+        &self.symbols[start..end]
+    }
+
+    /// Iterates over all levels of the table, giving the number of bits of each one together
+    /// with its symbols.
+    pub fn levels(&self) -> impl Iterator<Item = (usize, &[S])> {
+        (0..self.level_count()).map(|bits| (bits, self.level(bits)))
+    }
+
+    #[cfg(feature = "std")]
+    /// Estimates the average number of bits per symbol encoded with this table. Symbol
+    /// frequencies are not stored, so each symbol is assumed to appear with the probability its
+    /// code length is optimal for, that is 2^-bits.
+    pub fn average_code_length(&self) -> f64 {
+        self.levels().map(|(bits, level)| level.len() as f64 * bits as f64 / 2f64.powi(bits as i32)).sum()
+    }
+}
+
+impl<S: Copy + Ord> HuffmanEncoder<S> for DefinedHuffmanTable<S> {
+    fn find_symbol(&self, symbol: S) -> Option<(u32, u32)> {
+        for bits in 0..self.level_count() {
+            if let Ok(index) = self.level(bits).binary_search(&symbol) {
+                return Some((u32::try_from(bits).unwrap(), u32::try_from(index).unwrap()));
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(feature = "std")]
+fn invalid_symbol() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "Symbol cannot be encoded with the given table")
+}
+
+#[cfg(feature = "std")]
+/// Counterpart of InputBitStream, writing the bits in the same order they are expected to be read.
+pub struct OutputBitStream<W: Write> {
+    writer: W,
+    buffer: u8,
+    used: u32,
+    bytes_written: usize
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> OutputBitStream<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            buffer: 0,
+            used: 0,
+            bytes_written: 0
+        }
     }
-}
\ No newline at end of file
+
+    /// Number of bytes already handed to the underlying writer.
+    pub fn bytes_written(&self) -> usize {
+        self.bytes_written
+    }
+
+    /// Number of bits written so far, including the ones not yet handed to the underlying writer.
+    pub fn bits_written(&self) -> usize {
+        self.bytes_written * 8 + self.used as usize
+    }
+
+    pub fn write_boolean(&mut self, value: bool) -> io::Result<()> {
+        if value {
+            self.buffer |= 1 << self.used;
+        }
+
+        self.used += 1;
+        if self.used == 8 {
+            self.writer.write_all(&[self.buffer])?;
+            self.buffer = 0;
+            self.used = 0;
+            self.bytes_written += 1;
+        }
+
+        Ok(())
+    }
+
+    pub fn write_symbol<S, T: HuffmanEncoder<S>>(&mut self, table: &T, symbol: S) -> io::Result<()> {
+        let (bits, index) = table.find_symbol(symbol).ok_or_else(invalid_symbol)?;
+        let mut base = 0u32;
+        for level in 1..bits {
+            base = (base + table.symbols_with_bits(level)) << 1;
+        }
+
+        let code = base + index;
+        for bit in (0..bits).rev() {
+            self.write_boolean((code >> bit) & 1 != 0)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn write_diff_u32<T: HuffmanEncoder<u32>>(&mut self, table: &T, previous: u32, value: u32) -> io::Result<()> {
+        match value.checked_sub(previous + 1) {
+            Some(diff) => self.write_symbol(table, diff),
+            None => Err(invalid_symbol())
+        }
+    }
+
+    pub fn write_diff_i32<T: HuffmanEncoder<u32>>(&mut self, table: &T, previous: i32, value: i32) -> io::Result<()> {
+        match u32::try_from(i64::from(value) - i64::from(previous) - 1) {
+            Ok(diff) => self.write_symbol(table, diff),
+            Err(_) => Err(invalid_symbol())
+        }
+    }
+
+    pub fn write_character<T: HuffmanEncoder<u32>>(&mut self, table: &T, value: char) -> io::Result<()> {
+        self.write_symbol(table, value as u32)
+    }
+
+    pub fn write_diff_character<T: HuffmanEncoder<u32>>(&mut self, table: &T, previous: char, value: char) -> io::Result<()> {
+        self.write_diff_u32(table, previous as u32, value as u32)
+    }
+
+    /// Counterpart of InputBitStream::read_table.
+    pub fn write_table<S: Copy + Ord, T1, T2>(&mut self, table: &DefinedHuffmanTable<S>, table1: &T1, table2: &T2, supplier: impl Fn(&mut Self, &T1, S) -> io::Result<()>, diff_supplier: impl Fn(&mut Self, &T2, S, S) -> io::Result<()>) -> io::Result<()> {
+        let mut max = 1;
+        for bits in 0..table.level_count() {
+            let level_length = u32::try_from(table.level(bits).len()).unwrap();
+            self.write_symbol(&RangedIntegerHuffmanTable::new(0, max), level_length)?;
+            max -= level_length;
+            max <<= 1;
+        }
+
+        for bits in 0..table.level_count() {
+            let level = table.level(bits);
+            if let Some(first) = level.first() {
+                supplier(self, table1, *first)?;
+                for pair in level.windows(2) {
+                    diff_supplier(self, table2, pair[0], pair[1])?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes any pending bit, padding the last byte with zeros, and returns the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.used > 0 {
+            self.writer.write_all(&[self.buffer])?;
+            self.bytes_written += 1;
+        }
+
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reader returning at most the given number of bytes on each read, and then the given error
+    /// instead of the end of the file, if any.
+    #[cfg(feature = "std")]
+    struct ChunkedReader<'a> {
+        data: &'a [u8],
+        chunk_length: usize,
+        error: Option<io::ErrorKind>
+    }
+
+    #[cfg(feature = "std")]
+    impl Read for ChunkedReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.data.is_empty() {
+                if let Some(kind) = self.error {
+                    return Err(io::Error::from(kind));
+                }
+            }
+
+            let length = buf.len().min(self.chunk_length).min(self.data.len());
+            buf[..length].copy_from_slice(&self.data[..length]);
+            self.data = &self.data[length..];
+            Ok(length)
+        }
+    }
+
+    /// Writes the given number of padding bits, followed by the given values encoded with a table
+    /// of 10-bit codes, so that codes cross the boundaries of bytes and of the 64-bit buffer.
+    #[cfg(feature = "std")]
+    fn encode(padding: usize, values: &[u32]) -> Vec<u8> {
+        let table = RangedIntegerHuffmanTable::new(0, 1023);
+        let mut stream = OutputBitStream::new(Vec::new());
+        for _ in 0..padding {
+            stream.write_boolean(true).unwrap();
+        }
+
+        for value in values {
+            stream.write_symbol(&table, *value).unwrap();
+        }
+
+        stream.finish().unwrap()
+    }
+
+    #[cfg(feature = "std")]
+    fn decode<R: Read>(reader: &mut R, padding: usize, count: usize) -> Vec<u32> {
+        let table = RangedIntegerHuffmanTable::new(0, 1023);
+        let mut stream = InputBitStream::from(reader);
+        stream.skip_bits(padding).unwrap();
+        let values = (0..count).map(|_| stream.read_symbol(&table).unwrap()).collect();
+        assert_eq!(stream.bits_read(), padding + count * 10);
+        values
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn codes_spanning_a_refill_are_read() {
+        let values: Vec<u32> = (0..40).map(|index| index * 37 % 1024).collect();
+        for padding in 0..10 {
+            let data = encode(padding, &values);
+            assert_eq!(decode(&mut data.as_slice(), padding, values.len()), values);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn short_reads_are_refilled() {
+        let values: Vec<u32> = (0..40).map(|index| index * 37 % 1024).collect();
+        let data = encode(5, &values);
+        for chunk_length in 1..4 {
+            let mut reader = ChunkedReader {
+                data: &data,
+                chunk_length,
+                error: None
+            };
+
+            assert_eq!(decode(&mut reader, 5, values.len()), values);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn skipping_more_bits_than_buffered_keeps_the_position() {
+        let values: Vec<u32> = (0..40).map(|index| index * 37 % 1024).collect();
+        let data = encode(3, &values);
+        let table = RangedIntegerHuffmanTable::new(0, 1023);
+        let mut input = data.as_slice();
+        let mut stream = InputBitStream::from(&mut input);
+        stream.read_boolean().unwrap();
+        stream.skip_bits(2 + 15 * 10).unwrap();
+        assert_eq!(stream.bits_read(), 3 + 15 * 10);
+        assert_eq!(stream.read_symbol(&table).unwrap(), values[15]);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn errors_are_reported_once_the_buffered_bits_are_read() {
+        let data = [0xA5u8, 0x0F, 0xFF];
+        for error in [None, Some(io::ErrorKind::ConnectionReset)] {
+            let mut reader = ChunkedReader {
+                data: &data,
+                chunk_length: 2,
+                error
+            };
+
+            let mut stream = InputBitStream::from(&mut reader);
+            for index in 0..24 {
+                assert_eq!(stream.read_boolean().unwrap(), data[index / 8] & (1 << (index % 8)) != 0);
+            }
+
+            let err = stream.read_boolean().unwrap_err();
+            assert!(matches!(err.kind, ReadErrorKind::Io));
+            assert_eq!(err.message == "Unexpected end of file", error.is_none());
+            assert_eq!(stream.bits_read(), 24);
+        }
+    }
+
+    #[test]
+    fn codes_longer_than_32_bits_are_rejected() {
+        let data = [0xFFu8; 16];
+        let mut input = &data[..];
+        let mut stream = InputBitStream::from(&mut input);
+        let err = stream.read_symbol(&NaturalNumberHuffmanTable::create_with_alignment(8)).unwrap_err();
+        assert!(matches!(err.kind, ReadErrorKind::InvalidSymbol { .. }));
+    }
+
+    #[test]
+    fn integer_table_levels_cover_consecutive_values() {
+        for alignment in [4, 8] {
+            let table = IntegerNumberHuffmanTable::create_with_alignment(alignment);
+            let mut values: Vec<i32> = Vec::new();
+            for bits in (alignment..=16).step_by(alignment as usize) {
+                for index in 0..table.symbols_with_bits(bits) {
+                    let value = table.get_symbol(bits, index).unwrap();
+                    assert_eq!(table.find_symbol(value), Some((bits, index)));
+                    values.push(value);
+                }
+            }
+
+            values.sort();
+            let first = -(values.len() as i32) / 2;
+            assert!(values.iter().zip(first..).all(|(value, expected)| *value == expected));
+        }
+    }
+}
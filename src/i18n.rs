@@ -0,0 +1,497 @@
+use std::env;
+use std::fmt::{Display, Write};
+use std::str::FromStr;
+use std::sync::OnceLock;
+use langbook_sdb_dump::sdb::Section;
+use langbook_sdb_dump::writer::Table;
+
+/// Returns the text of a message in the current locale. When arguments are given, a String is
+/// returned with them replacing the placeholders.
+macro_rules! tr {
+    ($message:expr) => {
+        $message.text()
+    };
+    ($message:expr, $($arg:expr),+) => {
+        $message.format(&[$(&$arg),+])
+    };
+}
+
+pub(crate) use tr;
+
+/// Languages in which messages can be displayed.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Locale {
+    English,
+    Spanish
+}
+
+impl FromStr for Locale {
+    type Err = String;
+
+    /// Accepts language codes, optionally followed by a territory and encoding, as in es_ES.UTF-8.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split(['_', '-', '.']).next() {
+            Some("en") | Some("C") | Some("POSIX") => Ok(Locale::English),
+            Some("es") => Ok(Locale::Spanish),
+            _ => Err(tr!(Message::InvalidLocale, s))
+        }
+    }
+}
+
+impl Locale {
+    /// Takes the locale from the environment variables, in the same order of precedence the C
+    /// library uses. English is used when none of them is set to a supported language.
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"].into_iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(Locale::English)
+    }
+}
+
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+/// Sets the locale for all messages. Only the first call has any effect.
+pub fn set_locale(locale: Locale) {
+    let _ = LOCALE.set(locale);
+}
+
+fn locale() -> Locale {
+    LOCALE.get().copied().unwrap_or(Locale::English)
+}
+
+/// All messages displayed to the user. Messages only displayed by the features this binary was
+/// built without are kept, so that catalogs stay complete.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(not(all(feature = "archives", feature = "download", feature = "export", feature = "search", feature = "server")), allow(dead_code))]
+pub enum Message {
+    UnknownImportFormat,
+    MissingValue,
+    InputFileAlreadySet,
+    OutputFileAlreadySet,
+    InvalidEdits,
+    InvalidAlphabet,
+    InvalidPort,
+    InvalidLocale,
+    InvalidColorChoice,
+    InvalidArgument,
+    MissingOutputFile,
+    PrefixAndFuzzy,
+    MissingInputFile,
+    ReadingFile,
+    UnableToOpenFile,
+    UnableToReadArchive,
+    ArchiveEntryNotFound,
+    NoSdbEntry,
+    SeveralSdbEntries,
+    ReadingArchiveEntry,
+    UnableToDownload,
+    Downloading,
+    UsingCachedDownload,
+    WhileReadingHeader,
+    InvalidOffset,
+    InvalidMaxTextLength,
+    HeaderFound,
+    DecodingWarnings,
+    ErrorFound,
+    BadMagicHint,
+    UnsupportedVersionHint,
+    CorruptedFileHint,
+    PartialResult,
+    #[cfg(not(feature = "sqlite"))]
+    SqliteNotAvailable,
+    FeatureNotAvailable,
+    #[cfg(not(all(feature = "archives", feature = "download")))]
+    InputNeedsFeature,
+    UnableToReadFile,
+    ImportingFile,
+    WritingAcceptations,
+    UnableToCreateFile,
+    UnableToWriteFile,
+    NoViolations,
+    WastedBits,
+    NoLintFindings,
+    LintFindingsFound,
+    ViolationsFound,
+    FingerprintMismatch,
+    FingerprintMatches,
+    ContentsMatch,
+    ContentsDiffer,
+    SectionColumn,
+    ActualBitsColumn,
+    EntropyBitsColumn,
+    EfficiencyColumn,
+    BytesColumn,
+    ShareColumn,
+    TotalRow,
+    TableColumn,
+    TableNotDefined,
+    TableSummary,
+    TableLevel,
+    AlphabetCharacters,
+    NoConceptForText,
+    MissingFlashcardLanguages,
+    FlashcardsGenerated,
+    DefinedConceptsFound,
+    LanguageNotFound,
+    AcceptationLabel,
+    UnknownSegment,
+    SegmentsFound,
+    CharactersWithoutAlphabet,
+    OriginalBitsColumn,
+    RebuiltBitsColumn,
+    DeltaColumn,
+    TablesReused,
+    TablesRebuilt,
+    UnusedSymbolArray,
+    UnusedCorrelation,
+    UnusedCorrelationArray,
+    UnusedConcept,
+    NoOrphans,
+    OrphansFound,
+    LanguageColumn,
+    RepresentedColumn,
+    DefinedColumn,
+    MissingColumn,
+    SymbolArrayCount,
+    LanguageCount,
+    AlphabetCount,
+    ConversionCount,
+    ConceptCount,
+    CorrelationCount,
+    CorrelationArrayCount,
+    AcceptationCount,
+    DefinitionCount,
+    SymbolArrayLengths,
+    CorrelationArrayLengths,
+    AlphabetsPerCorrelation,
+    ComplementsPerDefinition,
+    MaximumDepth,
+    CycleFound,
+    CyclesFound,
+    ConceptsOutOfTree,
+    DuplicatedSymbolArrays,
+    RemovableSymbolArrays,
+    DuplicatedCorrelations,
+    RemovableCorrelations,
+    DuplicatedAcceptations,
+    RemovableAcceptations,
+    NoDuplicates,
+    UnknownList,
+    InvalidLanguage,
+    ConceptLabel,
+    AlphabetLabel,
+    ReferencedBy,
+    InvalidRange,
+    UnknownSection,
+    SectionsOnlyForDump,
+    SplitOnlyForJson,
+    FormatOnlyForDumpDiffAndRules,
+    SortOnlyForFlashcards,
+    TransliterateOnlyForDump,
+    InvalidDisplayLanguage,
+    NormalizeOnlyForExports,
+    UnknownNormalization,
+    UnknownSortOrder,
+    InvalidCollationLocale,
+    RomanizeOnlyForExports,
+    JsonOnlyForStats,
+    InvalidConversion,
+    UnknownRuleFormat,
+    UnknownDiffFormat,
+    UnknownDumpFormat,
+    SectionsWithRecordDump,
+    InvalidConversionTable,
+    UnableToImportConversionTable,
+    ImportingConversionTable,
+    ConversionNotFound,
+    NoConversions,
+    ConversionRequired,
+    ExportingConversion,
+    StreamOnlyForJson,
+    StreamWithPrettyOrSplit,
+    InvalidMemoryBudget,
+    MemoryBudgetExceeded,
+    SectionPeakMemory,
+    PeakMemory,
+    MissingOutputDirectory,
+    AlphabetNotFound,
+    AlphabetRequired,
+    TextsFound,
+    SearchResult,
+    SearchDistance,
+    MatchesFound,
+    UnableToServe,
+    ListeningOn,
+    UnableToAnswer,
+    UnableToAccept,
+    ProgressTemplate
+}
+
+impl Message {
+    /// Returns the English and Spanish texts for this message. Each `{}` is replaced by an
+    /// argument when formatting, in the same order in both languages.
+    fn catalog(self) -> (&'static str, &'static str) {
+        match self {
+            Message::UnknownImportFormat => ("Unknown import format {}", "Formato de importación desconocido: {}"),
+            Message::MissingValue => ("Missing value for {}", "Falta el valor de {}"),
+            Message::InputFileAlreadySet => ("Input file already set", "El fichero de entrada ya se ha indicado"),
+            Message::OutputFileAlreadySet => ("Output file already set", "El fichero de salida ya se ha indicado"),
+            Message::InvalidEdits => ("Invalid number of edits {}", "Número de ediciones no válido: {}"),
+            Message::InvalidAlphabet => ("Invalid alphabet {}", "Alfabeto no válido: {}"),
+            Message::InvalidPort => ("Invalid port {}", "Puerto no válido: {}"),
+            Message::InvalidLocale => ("Invalid locale {}, expected en or es", "Idioma no válido: {}, se esperaba en o es"),
+            Message::InvalidColorChoice => ("Invalid color choice {}, expected auto, always or never", "Opción de color no válida: {}, se esperaba auto, always o never"),
+            Message::InvalidArgument => ("Invalid argument {}", "Argumento no válido: {}"),
+            Message::MissingOutputFile => ("Missing output file: importing and re-encoding require -o <sdb-file>", "Falta el fichero de salida: para importar y recodificar se requiere -o <fichero-sdb>"),
+            Message::PrefixAndFuzzy => ("Options --prefix and --fuzzy cannot be used together", "Las opciones --prefix y --fuzzy no se pueden usar a la vez"),
+            Message::MissingInputFile => (
+                "Missing input file: try {} [dump|validate|fingerprint|serve|analyze|orphans|coverage|stats|hierarchy|tree|duplicates|sizes|json|graph|rules|inspect-tables|chars|flashcards|lint|diff <sdb-file>|search <pattern>|defined-from <text>|analyze-text <sentence>] -i <sdb-file>, or {} import [sqlite|csv|tsv] -i <input-file> -o <sdb-file>, or {} reencode -i <sdb-file> -o <sdb-file> [--rebuild-tables], or {} --check-equal <sdb-file> <sdb-file>",
+                "Falta el fichero de entrada: prueba {} [dump|validate|fingerprint|serve|analyze|orphans|coverage|stats|hierarchy|tree|duplicates|sizes|json|graph|rules|inspect-tables|chars|flashcards|lint|diff <fichero-sdb>|search <patrón>|defined-from <texto>|analyze-text <frase>] -i <fichero-sdb>, o {} import [sqlite|csv|tsv] -i <fichero-entrada> -o <fichero-sdb>, o {} reencode -i <fichero-sdb> -o <fichero-sdb> [--rebuild-tables], o {} --check-equal <fichero-sdb> <fichero-sdb>"
+            ),
+            Message::ReadingFile => ("Reading file {}", "Leyendo el fichero {}"),
+            Message::UnableToOpenFile => ("Unable to open file {}", "No se puede abrir el fichero {}"),
+            Message::UnableToReadArchive => ("Unable to read archive {}: {}", "No se puede leer el archivo comprimido {}: {}"),
+            Message::ArchiveEntryNotFound => ("Entry {} not found in archive {}", "No se encontró la entrada {} en el archivo comprimido {}"),
+            Message::NoSdbEntry => ("No SDB file found in archive {}", "No se encontró ningún fichero SDB en el archivo comprimido {}"),
+            Message::SeveralSdbEntries => (
+                "Several SDB files found in archive {}: {}. Choose one with -i <archive>#<entry>",
+                "Se encontraron varios ficheros SDB en el archivo comprimido {}: {}. Elige uno con -i <archivo>#<entrada>"
+            ),
+            Message::ReadingArchiveEntry => ("Reading entry {}", "Leyendo la entrada {}"),
+            Message::UnableToDownload => ("Unable to download {}: {}", "No se puede descargar {}: {}"),
+            Message::Downloading => ("Downloading {} into {}", "Descargando {} en {}"),
+            Message::UsingCachedDownload => ("Using cached download {}", "Usando la descarga guardada en {}"),
+            Message::InvalidOffset => ("Invalid offset {}", "Desplazamiento no válido: {}"),
+            Message::InvalidMaxTextLength => ("Invalid maximum text length {}", "Longitud máxima de texto no válida: {}"),
+            Message::HeaderFound => ("SDB header found at byte {}", "Cabecera SDB encontrada en el byte {}"),
+            Message::WhileReadingHeader => ("{} while reading the header", "{} al leer la cabecera"),
+            Message::DecodingWarnings => ("{} warnings found while decoding, the result may be incomplete", "Se encontraron {} avisos durante la decodificación, el resultado puede estar incompleto"),
+            Message::ErrorFound => ("Error found: {}", "Error encontrado: {}"),
+            Message::BadMagicHint => (
+                "The input is not an SDB file. SQLite databases and word lists can be converted with the import command. If the SDB content is preceded by other data, try --offset or --scan-magic",
+                "La entrada no es un fichero SDB. Las bases de datos SQLite y las listas de palabras se pueden convertir con el comando import. Si el contenido SDB va precedido de otros datos, prueba --offset o --scan-magic"
+            ),
+            Message::UnsupportedVersionHint => (
+                "The file was written with a newer version of the format. Try with a newer release of this tool",
+                "El fichero se escribió con una versión más reciente del formato. Prueba con una versión más reciente de esta herramienta"
+            ),
+            Message::CorruptedFileHint => (
+                "The file may be corrupted. Try --lenient to decode as much as possible, or --partial to keep the sections decoded before the error",
+                "El fichero puede estar dañado. Prueba --lenient para decodificar todo lo posible, o --partial para conservar las secciones decodificadas antes del error"
+            ),
+            Message::PartialResult => (
+                "Only the sections before {} were decoded, the result is incomplete",
+                "Solo se decodificaron las secciones anteriores a {}, el resultado está incompleto"
+            ),
+            #[cfg(not(feature = "sqlite"))]
+            Message::SqliteNotAvailable => (
+                "SQLite import is not available, as this binary was built without the sqlite feature",
+                "La importación de SQLite no está disponible, ya que este programa se compiló sin la característica sqlite"
+            ),
+            Message::FeatureNotAvailable => (
+                "{} is not available, as this binary was built without the {} feature",
+                "{} no está disponible, ya que este programa se compiló sin la característica {}"
+            ),
+            #[cfg(not(all(feature = "archives", feature = "download")))]
+            Message::InputNeedsFeature => (
+                "Unable to read {}, as this binary was built without the {} feature",
+                "No se puede leer {}, ya que este programa se compiló sin la característica {}"
+            ),
+            Message::UnableToReadFile => ("Unable to read file {}: {}", "No se puede leer el fichero {}: {}"),
+            Message::ImportingFile => ("Importing file {}", "Importando el fichero {}"),
+            Message::WritingAcceptations => ("Writing {} acceptations into {}", "Escribiendo {} acepciones en {}"),
+            Message::UnableToCreateFile => ("Unable to create file {}: {}", "No se puede crear el fichero {}: {}"),
+            Message::UnableToWriteFile => ("Unable to write file {}: {}", "No se puede escribir el fichero {}: {}"),
+            Message::NoViolations => ("No violations found", "No se encontraron infracciones"),
+            Message::WastedBits => ("about {} bits wasted", "unos {} bits desperdiciados"),
+            Message::NoLintFindings => ("No encoding waste found", "No se encontró ningún desperdicio en la codificación"),
+            Message::LintFindingsFound => ("{} findings, about {} bits wasted in total", "{} hallazgos, unos {} bits desperdiciados en total"),
+            Message::ViolationsFound => ("{} violations found", "Se encontraron {} infracciones"),
+            Message::FingerprintMismatch => ("Fingerprint mismatch: expected {}, but found {}", "La huella no coincide: se esperaba {}, pero se encontró {}"),
+            Message::FingerprintMatches => ("Fingerprint matches", "La huella coincide"),
+            Message::ContentsMatch => ("Contents of {} and {} match", "Los contenidos de {} y {} coinciden"),
+            Message::ContentsDiffer => ("Contents of {} and {} differ", "Los contenidos de {} y {} son distintos"),
+            Message::SectionColumn => ("Section", "Sección"),
+            Message::ActualBitsColumn => ("Actual bits", "Bits reales"),
+            Message::EntropyBitsColumn => ("Entropy bits", "Bits de entropía"),
+            Message::EfficiencyColumn => ("Efficiency", "Eficiencia"),
+            Message::BytesColumn => ("Bytes", "Bytes"),
+            Message::ShareColumn => ("Share", "Proporción"),
+            Message::TotalRow => ("Total", "Total"),
+            Message::TableColumn => ("Table", "Tabla"),
+            Message::TableNotDefined => ("{}: not defined", "{}: no definida"),
+            Message::TableSummary => ("{}: {} symbols, {} bits per symbol on average", "{}: {} símbolos, {} bits por símbolo de media"),
+            Message::TableLevel => ("{} bits: {} symbols", "{} bits: {} símbolos"),
+            Message::MissingFlashcardLanguages => ("Command flashcards requires --front-lang <code> and --back-lang <code>", "El comando flashcards requiere --front-lang <código> y --back-lang <código>"),
+            Message::FlashcardsGenerated => ("{} flashcards generated", "Se generaron {} tarjetas"),
+            Message::NoConceptForText => ("No concept found with text {}", "No se encontró ningún concepto con el texto {}"),
+            Message::DefinedConceptsFound => ("{} concepts defined from it", "{} conceptos definidos a partir de él"),
+            Message::LanguageNotFound => ("Language {} not found", "No se encontró el idioma {}"),
+            Message::AcceptationLabel => ("acceptation {}", "acepción {}"),
+            Message::UnknownSegment => ("unknown", "desconocido"),
+            Message::SegmentsFound => ("{} segments, {} of them unknown", "{} segmentos, {} de ellos desconocidos"),
+            Message::AlphabetCharacters => ("{}: {} different characters", "{}: {} caracteres distintos"),
+            Message::CharactersWithoutAlphabet => ("Characters not used by any alphabet: {}", "Caracteres no usados por ningún alfabeto: {}"),
+            Message::OriginalBitsColumn => ("Original bits", "Bits originales"),
+            Message::RebuiltBitsColumn => ("Rebuilt bits", "Bits reconstruidos"),
+            Message::DeltaColumn => ("Delta", "Diferencia"),
+            Message::TablesReused => ("Original tables were reused where possible", "Se reutilizaron las tablas originales cuando fue posible"),
+            Message::TablesRebuilt => ("All tables were rebuilt from the values written", "Todas las tablas se reconstruyeron a partir de los valores escritos"),
+            Message::UnusedSymbolArray => ("Symbol array {} ({}) is not used", "La cadena de símbolos {} ({}) no se usa"),
+            Message::UnusedCorrelation => ("Correlation {} is not used", "La correlación {} no se usa"),
+            Message::UnusedCorrelationArray => ("Correlation array {} is not used", "La lista de correlaciones {} no se usa"),
+            Message::UnusedConcept => ("Concept {} is not used", "El concepto {} no se usa"),
+            Message::NoOrphans => ("No orphans found", "No se encontraron entradas huérfanas"),
+            Message::OrphansFound => (
+                "{} symbol arrays, {} correlations, {} correlation arrays and {} concepts are not used",
+                "{} cadenas de símbolos, {} correlaciones, {} listas de correlaciones y {} conceptos no se usan"
+            ),
+            Message::LanguageColumn => ("Language", "Idioma"),
+            Message::RepresentedColumn => ("Represented", "Representados"),
+            Message::DefinedColumn => ("Defined", "Definidos"),
+            Message::MissingColumn => ("Missing", "Ausentes"),
+            Message::SymbolArrayCount => ("Symbol arrays: {}", "Cadenas de símbolos: {}"),
+            Message::LanguageCount => ("Languages: {}", "Idiomas: {}"),
+            Message::AlphabetCount => ("Alphabets: {}", "Alfabetos: {}"),
+            Message::ConversionCount => ("Conversions: {}", "Conversiones: {}"),
+            Message::ConceptCount => ("Concepts: {}", "Conceptos: {}"),
+            Message::CorrelationCount => ("Correlations: {}", "Correlaciones: {}"),
+            Message::CorrelationArrayCount => ("Correlation arrays: {}", "Listas de correlaciones: {}"),
+            Message::AcceptationCount => ("Acceptations: {}", "Acepciones: {}"),
+            Message::DefinitionCount => ("Definitions: {}", "Definiciones: {}"),
+            Message::SymbolArrayLengths => ("Symbol array lengths", "Longitud de las cadenas de símbolos"),
+            Message::CorrelationArrayLengths => ("Correlation array lengths", "Longitud de las listas de correlaciones"),
+            Message::AlphabetsPerCorrelation => ("Alphabets per correlation", "Alfabetos por correlación"),
+            Message::ComplementsPerDefinition => ("Complements per definition", "Complementos por definición"),
+            Message::MaximumDepth => ("Maximum depth: {}", "Profundidad máxima: {}"),
+            Message::CycleFound => ("Cycle found: {}", "Ciclo encontrado: {}"),
+            Message::ConceptsOutOfTree => ("{} defined concepts were left out of the tree as their bases lead to a cycle, run hierarchy to find it", "{} conceptos definidos se dejaron fuera del árbol porque sus bases llevan a un ciclo, ejecuta hierarchy para encontrarlo"),
+            Message::CyclesFound => ("{} cycles found among definitions", "Se encontraron {} ciclos entre las definiciones"),
+            Message::DuplicatedSymbolArrays => ("Duplicated symbol arrays: {}", "Cadenas de símbolos duplicadas: {}"),
+            Message::RemovableSymbolArrays => ("{} symbol arrays could be removed", "Se podrían eliminar {} cadenas de símbolos"),
+            Message::DuplicatedCorrelations => ("Duplicated correlations: {}", "Correlaciones duplicadas: {}"),
+            Message::RemovableCorrelations => ("{} correlations could be removed", "Se podrían eliminar {} correlaciones"),
+            Message::DuplicatedAcceptations => ("Duplicated acceptations: {}", "Acepciones duplicadas: {}"),
+            Message::RemovableAcceptations => ("{} acceptations could be removed", "Se podrían eliminar {} acepciones"),
+            Message::NoDuplicates => ("No duplicates found", "No se encontraron duplicados"),
+            Message::UnknownList => ("Unknown list {}, expected definitions, acceptations, conversions, correlations, symbol-arrays or texts", "Lista desconocida {}, se esperaba definitions, acceptations, conversions, correlations, symbol-arrays o texts"),
+            Message::InvalidLanguage => ("Invalid language {}, expected 2 lowercase letters", "Idioma no válido {}, se esperaban 2 letras minúsculas"),
+            Message::ConceptLabel => ("concept {}", "concepto {}"),
+            Message::AlphabetLabel => ("alphabet {}", "alfabeto {}"),
+            Message::UnknownSection => (
+                "Unknown section {}, expected symbol-arrays, languages, conversions, correlations, correlation-arrays, acceptations or definitions",
+                "Sección desconocida {}, se esperaba symbol-arrays, languages, conversions, correlations, correlation-arrays, acceptations o definitions"
+            ),
+            Message::SectionsOnlyForDump => ("Option --sections can only be used with the dump command", "La opción --sections solo puede usarse con el comando dump"),
+            Message::InvalidMemoryBudget => ("Invalid memory budget {}, expected a number of megabytes", "Límite de memoria no válido: {}, se esperaba un número de megabytes"),
+            Message::MemoryBudgetExceeded => (
+                "Memory budget given by --max-memory exceeded, try a higher one or decoding less sections with --sections",
+                "Se superó el límite de memoria indicado con --max-memory, prueba uno mayor o decodificar menos secciones con --sections"
+            ),
+            Message::SectionPeakMemory => ("Peak heap usage while reading {}: {} KiB", "Uso máximo de memoria al leer {}: {} KiB"),
+            Message::PeakMemory => ("Peak heap usage: {} KiB", "Uso máximo de memoria: {} KiB"),
+            Message::StreamOnlyForJson => ("Option --stream can only be used with the json command", "La opción --stream solo puede usarse con el comando json"),
+            Message::StreamWithPrettyOrSplit => ("Option --stream cannot be used together with --pretty or --split", "La opción --stream no se puede usar a la vez que --pretty o --split"),
+            Message::InvalidConversion => ("Invalid conversion {}", "Conversión no válida: {}"),
+            Message::UnknownRuleFormat => ("Unknown rule format {}, expected plain or google-ime", "Formato de reglas desconocido {}, se esperaba plain o google-ime"),
+            Message::UnknownDiffFormat => ("Unknown diff format {}, expected json-patch", "Formato de diferencias desconocido {}, se esperaba json-patch"),
+            Message::UnknownDumpFormat => ("Unknown dump format {}, expected text or dump", "Formato de volcado desconocido {}, se esperaba text o dump"),
+            Message::SectionsWithRecordDump => ("Option --sections cannot be used with --format dump, as all records are written", "La opción --sections no puede usarse con --format dump, ya que se escriben todos los registros"),
+            Message::InvalidConversionTable => ("Invalid conversion table {}, expected <source>:<target>:<csv-file>", "Tabla de conversión no válida {}, se esperaba <origen>:<destino>:<fichero-csv>"),
+            Message::UnableToImportConversionTable => ("Unable to import conversion table {}: {}", "No se pudo importar la tabla de conversión {}: {}"),
+            Message::ImportingConversionTable => ("Importing conversion table {} from alphabet {} to {}", "Importando la tabla de conversión {} del alfabeto {} al {}"),
+            Message::ConversionNotFound => ("Conversion {} not found, there are only {} conversions", "No se encontró la conversión {}, solo hay {} conversiones"),
+            Message::NoConversions => ("The database has no conversions", "La base de datos no tiene conversiones"),
+            Message::ConversionRequired => ("{} conversions found, choose one with --conversion <index>", "Se encontraron {} conversiones, elige una con --conversion <índice>"),
+            Message::ExportingConversion => ("Exporting conversion from {} to {}", "Exportando la conversión de {} a {}"),
+            Message::JsonOnlyForStats => ("Option --json can only be used with the stats and analyze commands", "La opción --json solo puede usarse con los comandos stats y analyze"),
+            Message::RomanizeOnlyForExports => ("Option --romanize can only be used with the json, flashcards and diff commands", "La opción --romanize solo puede usarse con los comandos json, flashcards y diff"),
+            Message::FormatOnlyForDumpDiffAndRules => ("Option --format can only be used with the dump, diff and rules commands", "La opción --format solo puede usarse con los comandos dump, diff y rules"),
+            Message::SplitOnlyForJson => ("Option --split can only be used with the json command", "La opción --split solo puede usarse con el comando json"),
+            Message::NormalizeOnlyForExports => ("Option --normalize can only be used with the json, flashcards, diff, graph and rules commands", "La opción --normalize solo puede usarse con los comandos json, flashcards, diff, graph y rules"),
+            Message::UnknownNormalization => ("Unknown normalization form {}, expected nfc or nfkc", "Forma de normalización desconocida {}, se esperaba nfc o nfkc"),
+            Message::InvalidDisplayLanguage => ("Invalid display language {}, expected a language code like es or an alphabet like ja:1", "Idioma de visualización no válido {}, se esperaba un código de idioma como es o un alfabeto como ja:1"),
+            Message::TransliterateOnlyForDump => ("Option --transliterate can only be used with the dump command", "La opción --transliterate solo puede usarse con el comando dump"),
+            Message::SortOnlyForFlashcards => ("Option --sort can only be used with the flashcards command", "La opción --sort solo puede usarse con el comando flashcards"),
+            Message::UnknownSortOrder => ("Unknown sort order {}, expected concept, text or icu:<locale>", "Orden desconocido {}, se esperaba concept, text o icu:<idioma>"),
+            Message::InvalidCollationLocale => ("Invalid locale {} for sorting", "Idioma no válido para ordenar: {}"),
+            Message::MissingOutputDirectory => ("Missing output directory: --split requires -o <directory>", "Falta el directorio de salida: --split requiere -o <directorio>"),
+            Message::InvalidRange => ("Invalid range {}, expected <start>..<end>, where any of them can be omitted", "Rango no válido {}, se esperaba <inicio>..<fin>, donde cualquiera de ellos puede omitirse"),
+            Message::ReferencedBy => ("referenced by {} correlation arrays", "referenciada por {} listas de correlaciones"),
+            Message::AlphabetNotFound => ("Alphabet {} not found, there are only {} alphabets", "No se encontró el alfabeto {}, solo hay {} alfabetos"),
+            Message::AlphabetRequired => ("Texts can only be listed for an alphabet given with --alphabet", "Los textos solo se pueden listar para un alfabeto indicado con --alphabet"),
+            Message::TextsFound => ("{} texts found for alphabet {}", "Se encontraron {} textos para el alfabeto {}"),
+            Message::SearchResult => ("{} [{}] acceptation {}, concept {}", "{} [{}] acepción {}, concepto {}"),
+            Message::SearchDistance => (", distance {}", ", distancia {}"),
+            Message::MatchesFound => ("{} matches found", "Se encontraron {} coincidencias"),
+            Message::UnableToServe => ("Unable to serve on {}:{}: {}", "No se puede servir en {}:{}: {}"),
+            Message::ListeningOn => ("Listening on http://{}:{}", "Escuchando en http://{}:{}"),
+            Message::UnableToAnswer => ("Unable to answer request: {}", "No se puede responder a la petición: {}"),
+            Message::UnableToAccept => ("Unable to accept connection: {}", "No se puede aceptar la conexión: {}"),
+            Message::ProgressTemplate => ("{spinner} Reading {msg}: {pos}/{len} records", "{spinner} Leyendo {msg}: {pos}/{len} registros")
+        }
+    }
+
+    /// Returns the text of this message in the current locale.
+    pub fn text(self) -> &'static str {
+        let (english, spanish) = self.catalog();
+        match locale() {
+            Locale::English => english,
+            Locale::Spanish => spanish
+        }
+    }
+
+    /// Returns the text of this message in the current locale, replacing each `{}` by the next
+    /// argument.
+    pub fn format(self, args: &[&dyn Display]) -> String {
+        let mut parts = self.text().split("{}");
+        let mut result = String::from(parts.next().unwrap_or(""));
+        let mut args = args.iter();
+        for part in parts {
+            if let Some(arg) = args.next() {
+                let _ = write!(result, "{}", arg);
+            }
+
+            result.push_str(part);
+        }
+
+        result
+    }
+}
+
+/// Name of the given section in the current locale.
+pub fn section_name(section: Section) -> &'static str {
+    let (english, spanish) = match section {
+        Section::SymbolArrays => ("symbol arrays", "cadenas de símbolos"),
+        Section::Languages => ("languages", "idiomas"),
+        Section::Conversions => ("conversions", "conversiones"),
+        Section::Correlations => ("correlations", "correlaciones"),
+        Section::CorrelationArrays => ("correlation arrays", "listas de correlaciones"),
+        Section::Acceptations => ("acceptations", "acepciones"),
+        Section::Definitions => ("definitions", "definiciones")
+    };
+
+    match locale() {
+        Locale::English => english,
+        Locale::Spanish => spanish
+    }
+}
+
+/// Name of the given table in the current locale.
+pub fn table_name(table: Table) -> &'static str {
+    let (english, spanish) = match table {
+        Table::Chars => ("characters", "caracteres"),
+        Table::SymbolArrayLengths => ("symbol array lengths", "longitudes de cadenas de símbolos"),
+        Table::CorrelationLengths => ("correlation lengths", "longitudes de correlaciones"),
+        Table::CorrelationArrayLengths => ("correlation array lengths", "longitudes de listas de correlaciones"),
+        Table::AcceptationSetLengths => ("acceptation set lengths", "longitudes de conjuntos de acepciones"),
+        Table::DefinitionLengths => ("definition lengths", "longitudes de definiciones")
+    };
+
+    match locale() {
+        Locale::English => english,
+        Locale::Spanish => spanish
+    }
+}
@@ -0,0 +1,83 @@
+//! Byte input used by the decoder. With the `std` feature this is just `std::io`. Without it, a
+//! minimal replacement is provided, so that the decoder can run with `alloc` only, reading from
+//! byte slices or from any source implementing [Read].
+
+#[cfg(feature = "std")]
+pub use std::io::{Bytes, Error, Read};
+
+#[cfg(not(feature = "std"))]
+pub use self::no_std::{Bytes, Error, Read};
+
+#[cfg(not(feature = "std"))]
+mod no_std {
+    use core::fmt::{Display, Formatter};
+
+    /// Failure of the underlying source of bytes.
+    #[derive(Debug)]
+    pub struct Error {
+        message: &'static str
+    }
+
+    impl Error {
+        pub fn new(message: &'static str) -> Self {
+            Self {
+                message
+            }
+        }
+    }
+
+    impl Display for Error {
+        fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+            f.write_str(self.message)
+        }
+    }
+
+    impl core::error::Error for Error {}
+
+    /// Source of bytes, analogous to `std::io::Read`.
+    pub trait Read {
+        /// Fills the given buffer with the next bytes, returning how many of them were filled.
+        /// Returns 0 once the end of the source is reached.
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error>;
+
+        fn bytes(self) -> Bytes<Self> where Self: Sized {
+            Bytes {
+                inner: self
+            }
+        }
+    }
+
+    impl Read for &[u8] {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+            let length = buf.len().min(self.len());
+            let (head, tail) = self.split_at(length);
+            buf[..length].copy_from_slice(head);
+            *self = tail;
+            Ok(length)
+        }
+    }
+
+    impl<R: Read + ?Sized> Read for &mut R {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+            (**self).read(buf)
+        }
+    }
+
+    /// Iterator over the bytes of a source, analogous to `std::io::Bytes`.
+    pub struct Bytes<R> {
+        inner: R
+    }
+
+    impl<R: Read> Iterator for Bytes<R> {
+        type Item = Result<u8, Error>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            let mut byte = 0;
+            match self.inner.read(core::slice::from_mut(&mut byte)) {
+                Ok(0) => None,
+                Ok(_) => Some(Ok(byte)),
+                Err(err) => Some(Err(err))
+            }
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use std::fmt::{Display, Formatter, Write};
+
+const INDENTATION: &str = "  ";
+
+/// Minimal JSON document model, enough to serialise the decoded content.
+#[derive(Clone, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(i64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>)
+}
+
+impl From<usize> for JsonValue {
+    fn from(value: usize) -> Self {
+        JsonValue::Number(i64::try_from(value).unwrap())
+    }
+}
+
+impl From<&str> for JsonValue {
+    fn from(value: &str) -> Self {
+        JsonValue::String(value.to_string())
+    }
+}
+
+impl From<String> for JsonValue {
+    fn from(value: String) -> Self {
+        JsonValue::String(value)
+    }
+}
+
+impl<T: Into<JsonValue>> From<Option<T>> for JsonValue {
+    fn from(value: Option<T>) -> Self {
+        match value {
+            Some(value) => value.into(),
+            None => JsonValue::Null
+        }
+    }
+}
+
+impl<T: Into<JsonValue>> From<Vec<T>> for JsonValue {
+    fn from(values: Vec<T>) -> Self {
+        JsonValue::Array(values.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Builds a JSON object keeping the order in which the fields are given.
+pub fn object<const N: usize>(fields: [(&str, JsonValue); N]) -> JsonValue {
+    JsonValue::Object(fields.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+}
+
+pub fn write_escaped_string(f: &mut impl Write, text: &str) -> std::fmt::Result {
+    f.write_char('"')?;
+    for ch in text.chars() {
+        match ch {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            ch if (ch as u32) < 0x20 => write!(f, "\\u{:04x}", ch as u32)?,
+            ch => f.write_char(ch)?
+        }
+    }
+
+    f.write_char('"')
+}
+
+impl JsonValue {
+    fn write_indentation(f: &mut impl Write, level: usize) -> std::fmt::Result {
+        f.write_char('\n')?;
+        for _ in 0..level {
+            f.write_str(INDENTATION)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_pretty(&self, f: &mut impl Write, level: usize) -> std::fmt::Result {
+        match self {
+            JsonValue::Array(values) if !values.is_empty() => {
+                f.write_char('[')?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        f.write_char(',')?;
+                    }
+                    Self::write_indentation(f, level + 1)?;
+                    value.write_pretty(f, level + 1)?;
+                }
+                Self::write_indentation(f, level)?;
+                f.write_char(']')
+            },
+            JsonValue::Object(fields) if !fields.is_empty() => {
+                f.write_char('{')?;
+                for (index, (key, value)) in fields.iter().enumerate() {
+                    if index > 0 {
+                        f.write_char(',')?;
+                    }
+                    Self::write_indentation(f, level + 1)?;
+                    write_escaped_string(f, key)?;
+                    f.write_str(": ")?;
+                    value.write_pretty(f, level + 1)?;
+                }
+                Self::write_indentation(f, level)?;
+                f.write_char('}')
+            },
+            value => write!(f, "{}", value)
+        }
+    }
+
+    /// Serialises the value placing each array item and object field in its own line, indented
+    /// according to its depth. Use [ToString::to_string] for a single line without spaces.
+    pub fn to_pretty_string(&self) -> String {
+        let mut text = String::new();
+        self.write_pretty(&mut text, 0).expect("Writing into a String should not fail");
+        text
+    }
+}
+
+impl Display for JsonValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JsonValue::Null => f.write_str("null"),
+            JsonValue::Bool(value) => write!(f, "{}", value),
+            JsonValue::Number(value) => write!(f, "{}", value),
+            JsonValue::String(value) => write_escaped_string(f, value),
+            JsonValue::Array(values) => {
+                f.write_char('[')?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                f.write_char(']')
+            },
+            JsonValue::Object(fields) => {
+                f.write_char('{')?;
+                for (index, (key, value)) in fields.iter().enumerate() {
+                    if index > 0 {
+                        f.write_char(',')?;
+                    }
+                    write_escaped_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
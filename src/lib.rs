@@ -0,0 +1,56 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod file_utils;
+pub mod huffman;
+pub mod io;
+pub mod sdb;
+
+#[cfg(feature = "std")]
+pub mod analysis;
+
+#[cfg(feature = "std")]
+pub mod builder;
+
+#[cfg(feature = "std")]
+pub mod conversion;
+
+#[cfg(feature = "export")]
+pub mod diff;
+
+#[cfg(feature = "export")]
+pub mod export;
+
+#[cfg(feature = "std")]
+pub mod fingerprint;
+
+#[cfg(feature = "std")]
+pub mod json;
+
+#[cfg(feature = "std")]
+pub mod lint;
+
+#[cfg(feature = "search")]
+pub mod search;
+
+#[cfg(feature = "std")]
+pub mod validation;
+
+#[cfg(feature = "std")]
+pub mod word_list;
+
+#[cfg(feature = "std")]
+pub mod writer;
+
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+#[cfg(feature = "async")]
+pub mod async_reader;
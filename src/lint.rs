@@ -0,0 +1,171 @@
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use crate::huffman::{DefinedHuffmanTable, HuffmanEncoder, RangedNaturalUsizeHuffmanTable};
+use crate::sdb::SdbReadResult;
+use crate::writer::Table;
+
+/// Known inefficiency of the format found in a database.
+pub enum Finding {
+    /// A length table assigns codes to values that are never valid lengths, like negative ones,
+    /// taking code space from the lengths actually found.
+    ImpossibleLengths {
+        table: Table,
+        values: Vec<i32>
+    },
+
+    /// Correlation keys are encoded among the alphabets of all languages, although correlations
+    /// cannot mix languages, so encoding the language first would narrow the keys down.
+    LanguageWideKeys {
+        correlations: usize
+    },
+
+    /// Empty correlations are encoded through the correlation lengths table, although there can
+    /// only be one of them, and a single bit would be enough to tell whether it is present.
+    EmptyCorrelations {
+        count: usize
+    }
+}
+
+impl Display for Finding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Finding::ImpossibleLengths { table, values } => {
+                let values: Vec<String> = values.iter().map(i32::to_string).collect();
+                write!(f, "Table of {} assigns codes to lengths that cannot be found: {}", table, values.join(", "))
+            },
+            Finding::LanguageWideKeys { correlations } => write!(f, "Keys of {} correlations are encoded among the alphabets of all languages, although correlations cannot mix languages", correlations),
+            Finding::EmptyCorrelations { count } => write!(f, "{} empty correlations are encoded as any other length, although a single bit would be enough for the only one needed", count)
+        }
+    }
+}
+
+/// Finding together with the estimated number of bits it wastes within the file.
+pub struct LintFinding {
+    pub finding: Finding,
+    pub wasted_bits: usize
+}
+
+/// Number of bits taken by the given sorted alphabet indexes when encoded as the keys of a
+/// correlation, given the number of alphabets they are chosen from.
+fn key_bits(keys: &[usize], alphabet_count: usize) -> usize {
+    let mut bits = 0;
+    let mut min = 0;
+    for (position, key) in keys.iter().enumerate() {
+        let table = RangedNaturalUsizeHuffmanTable::new(min, alphabet_count - keys.len() + position);
+        bits += table.find_symbol(*key).map_or(0, |(code_bits, _)| code_bits as usize);
+        min = key + 1;
+    }
+
+    bits
+}
+
+/// Checks the symbols of the given length table out of the valid range. Values encoded with it
+/// would be shorter if that code space were given to the valid ones, which is estimated from the
+/// fraction of the code space taken by the invalid symbols.
+fn lint_length_table(table: Table, defined: Option<&DefinedHuffmanTable<i32>>, valid: (i32, i32), encoded_values: usize, findings: &mut Vec<LintFinding>) {
+    let defined = match defined {
+        Some(defined) => defined,
+        None => return
+    };
+
+    let mut values = Vec::new();
+    let mut invalid_space = 0f64;
+    for (bits, level) in defined.levels() {
+        for value in level.iter().filter(|value| **value < valid.0 || **value > valid.1) {
+            values.push(*value);
+            invalid_space += 1.0 / 2f64.powi(bits as i32);
+        }
+    }
+
+    if !values.is_empty() {
+        values.sort();
+        let wasted_bits = if invalid_space < 1.0 {
+            (encoded_values as f64 * -(1.0 - invalid_space).log2()).ceil() as usize
+        }
+        else {
+            0
+        };
+
+        findings.push(LintFinding {
+            finding: Finding::ImpossibleLengths { table, values },
+            wasted_bits
+        });
+    }
+}
+
+/// Compares the bits taken by the keys of each correlation with the bits they would take if
+/// the language were encoded first, and the keys chosen among its alphabets only.
+fn lint_correlation_keys(result: &SdbReadResult, findings: &mut Vec<LintFinding>) {
+    let alphabet_count = result.alphabet_count();
+    let languages: Vec<_> = result.languages().collect();
+    if languages.is_empty() {
+        return;
+    }
+
+    let language_table = RangedNaturalUsizeHuffmanTable::new(0, languages.len() - 1);
+    let mut correlations = 0;
+    let mut wasted_bits = 0;
+    for correlation in result.correlations.iter() {
+        let keys: BTreeSet<usize> = correlation.keys().map(|alphabet| alphabet.index()).collect();
+        let keys: Vec<usize> = keys.into_iter().collect();
+        let (first, last) = match (keys.first(), keys.last()) {
+            (Some(first), Some(last)) => (*first, *last),
+            _ => continue
+        };
+
+        let language_index = match languages.iter().position(|language| language.alphabets.contains(&first) && language.alphabets.contains(&last)) {
+            Some(language_index) => language_index,
+            None => continue
+        };
+
+        let language = &languages[language_index];
+        let local_keys: Vec<usize> = keys.iter().map(|key| key - language.alphabets.start).collect();
+        let language_bits = language_table.find_symbol(language_index).map_or(0, |(bits, _)| bits as usize);
+        let narrowed_bits = language_bits + key_bits(&local_keys, language.alphabet_count);
+        let actual_bits = key_bits(&keys, alphabet_count);
+        if actual_bits > narrowed_bits {
+            correlations += 1;
+            wasted_bits += actual_bits - narrowed_bits;
+        }
+    }
+
+    if correlations > 0 {
+        findings.push(LintFinding {
+            finding: Finding::LanguageWideKeys { correlations },
+            wasted_bits
+        });
+    }
+}
+
+/// Compares the bits taken by the lengths of the empty correlations with a single bit telling
+/// whether the only empty correlation needed is present.
+fn lint_empty_correlations(result: &SdbReadResult, findings: &mut Vec<LintFinding>) {
+    let count = result.correlations.iter().filter(|correlation| correlation.is_empty()).count();
+    let length_bits = match &result.tables.correlation_lengths {
+        Some(table) => table.find_symbol(0).map_or(0, |(bits, _)| bits as usize),
+        None => 0
+    };
+
+    let wasted_bits = (count * length_bits).saturating_sub(1);
+    if count > 0 && wasted_bits > 0 {
+        findings.push(LintFinding {
+            finding: Finding::EmptyCorrelations { count },
+            wasted_bits
+        });
+    }
+}
+
+/// Looks for the known inefficiencies of the format within the given database, estimating the
+/// bits wasted by each of them. Tables are only checked if they were decoded from a file.
+pub fn lint(result: &SdbReadResult) -> Vec<LintFinding> {
+    let mut findings = Vec::new();
+    let alphabet_count = i32::try_from(result.alphabet_count()).unwrap_or(i32::MAX);
+    let correlation_array_count = i32::try_from(result.correlation_arrays.len()).unwrap_or(i32::MAX);
+    let acceptation_sets = result.acceptations.iter().map(|acceptation| acceptation.concept).collect::<BTreeSet<usize>>().len();
+    lint_length_table(Table::CorrelationLengths, result.tables.correlation_lengths.as_ref(), (0, alphabet_count), result.correlations.len(), &mut findings);
+    lint_length_table(Table::CorrelationArrayLengths, result.tables.correlation_array_lengths.as_ref(), (0, i32::MAX), result.correlation_arrays.len(), &mut findings);
+    lint_length_table(Table::AcceptationSetLengths, result.tables.acceptation_set_lengths.as_ref(), (1, correlation_array_count), acceptation_sets, &mut findings);
+    lint_correlation_keys(result, &mut findings);
+    lint_empty_correlations(result, &mut findings);
+    findings
+}
@@ -1,112 +1,2250 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::BTreeMap;
 use std::env;
-use std::fs::File;
-use std::io::Read;
-use huffman::InputBitStream;
-use crate::sdb::{CorrelationArrayIndex, SdbReader, SdbReadResult};
+use std::fs::{self, File};
+#[cfg(feature = "download")]
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{stderr, stdout, BufRead, BufReader, BufWriter, Read, Seek};
+#[cfg(feature = "download")]
+use std::io::{self, Cursor};
+#[cfg(any(feature = "download", feature = "export"))]
+use std::io::Write;
+use std::path::Path;
+#[cfg(feature = "download")]
+use std::path::PathBuf;
+use std::ops::Range;
+use std::process::ExitCode;
+#[cfg(feature = "export")]
+use std::thread;
+use color::{ColorChoice, Palette};
+#[cfg(feature = "archives")]
+use flate2::bufread::MultiGzDecoder;
+use i18n::{tr, Locale, Message};
+use langbook_sdb_dump::analysis::{self, Duplicates};
+#[cfg(feature = "export")]
+use langbook_sdb_dump::export::{self, CardOrder, ExportDriver, ExportOptions, JsonStreamWriter, RuleFormat, Shard};
+#[cfg(feature = "normalization")]
+use langbook_sdb_dump::export::Normalization;
+#[cfg(feature = "export")]
+use langbook_sdb_dump::diff;
+use langbook_sdb_dump::file_utils;
+use langbook_sdb_dump::file_utils::{ReadError, ReadErrorKind};
+use langbook_sdb_dump::fingerprint::{self, Fingerprint};
+use langbook_sdb_dump::huffman::{DefinedHuffmanTable, InputBitStream};
+#[cfg(feature = "export")]
+use langbook_sdb_dump::json::JsonValue;
+use langbook_sdb_dump::lint;
+use langbook_sdb_dump::sdb::{Acceptation, Alphabet, Conversion, DecodingMode, DisplayPreference, LanguageCode, SdbReader, SdbReadResult, Section, DEFAULT_MAX_SYMBOL_ARRAY_LENGTH, SDB_HEADER};
+#[cfg(feature = "search")]
+use langbook_sdb_dump::search::{SearchIndex, SearchMode};
+use langbook_sdb_dump::validation;
+use langbook_sdb_dump::word_list;
+use langbook_sdb_dump::writer::{self, Table, TableSize};
+use memory::{SectionPeaks, TrackingAllocator};
+use progress::ProgressReporter;
+#[cfg(any(feature = "archives", feature = "export", feature = "search"))]
+use tracing::debug;
+use tracing::{error, info, warn};
+use tracing_subscriber::EnvFilter;
+#[cfg(feature = "archives")]
+use zip::ZipArchive;
 
-pub mod file_utils;
-pub mod huffman;
-pub mod sdb;
+mod color;
+mod i18n;
+mod memory;
+mod progress;
+#[cfg(feature = "server")]
+mod server;
 
+/// Formats from which a database can be imported to generate an SDB file.
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum ImportFormat {
+    Sqlite,
+    Csv,
+    Tsv
+}
+
+impl ImportFormat {
+    fn from_name(name: &str) -> Result<Self, String> {
+        match name {
+            "sqlite" => Ok(ImportFormat::Sqlite),
+            "csv" => Ok(ImportFormat::Csv),
+            "tsv" => Ok(ImportFormat::Tsv),
+            _ => Err(tr!(Message::UnknownImportFormat, name))
+        }
+    }
+}
+
+/// Entries printed by the dump command.
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum DumpList {
+    Definitions,
+    Acceptations,
+    Conversions,
+    Correlations,
+    SymbolArrays,
+    Texts
+}
+
+impl DumpList {
+    fn from_name(name: &str) -> Result<Self, String> {
+        match name {
+            "definitions" => Ok(DumpList::Definitions),
+            "acceptations" => Ok(DumpList::Acceptations),
+            "conversions" => Ok(DumpList::Conversions),
+            "correlations" => Ok(DumpList::Correlations),
+            "symbol-arrays" => Ok(DumpList::SymbolArrays),
+            "texts" => Ok(DumpList::Texts),
+            _ => Err(tr!(Message::UnknownList, name))
+        }
+    }
+}
+
+/// Formats in which the differences between two files can be written.
+#[derive(Copy, Clone)]
+enum DiffFormat {
+    /// Operations as described by RFC 6902, to be applied on the JSON export.
+    JsonPatch
+}
+
+/// Formats in which the dump command can write the decoded content.
+#[derive(Copy, Clone)]
+enum DumpFormat {
+    /// Listings meant to be read, as selected with --list or --sections.
+    Text,
+
+    /// All records, one per line, meant to be compared in version control.
+    Records
+}
+
+fn parse_dump_format(name: &str) -> Result<DumpFormat, String> {
+    match name {
+        "text" => Ok(DumpFormat::Text),
+        "dump" => Ok(DumpFormat::Records),
+        _ => Err(tr!(Message::UnknownDumpFormat, name))
+    }
+}
+
+fn parse_diff_format(name: &str) -> Result<DiffFormat, String> {
+    match name {
+        "json-patch" => Ok(DiffFormat::JsonPatch),
+        _ => Err(tr!(Message::UnknownDiffFormat, name))
+    }
+}
+
+#[cfg(feature = "export")]
+fn parse_rule_format(name: &str) -> Result<RuleFormat, String> {
+    match name {
+        "plain" => Ok(RuleFormat::Plain),
+        "google-ime" => Ok(RuleFormat::GoogleIme),
+        _ => Err(tr!(Message::UnknownRuleFormat, name))
+    }
+}
+
+#[cfg(feature = "normalization")]
+fn parse_normalization(name: &str) -> Result<Normalization, String> {
+    match name {
+        "nfc" => Ok(Normalization::Nfc),
+        "nfkc" => Ok(Normalization::Nfkc),
+        _ => Err(tr!(Message::UnknownNormalization, name))
+    }
+}
+
+#[cfg(feature = "export")]
+fn parse_card_order(name: &str) -> Result<CardOrder, String> {
+    match name.strip_prefix("icu:") {
+        #[cfg(feature = "collation")]
+        Some(locale) => locale.parse().map(CardOrder::Collation).map_err(|_| tr!(Message::InvalidCollationLocale, locale)),
+        #[cfg(not(feature = "collation"))]
+        Some(_) => Err(tr!(Message::FeatureNotAvailable, "--sort icu", "collation")),
+        None => match name {
+            "concept" => Ok(CardOrder::Concept),
+            "text" => Ok(CardOrder::CodePoint),
+            _ => Err(tr!(Message::UnknownSortOrder, name))
+        }
+    }
+}
+
+/// Conversion to be set from a CSV or TSV file before writing the database.
+struct ConversionTable {
+    source: usize,
+    target: usize,
+    file_name: String
+}
+
+/// Parses a conversion table like 3:0:table.csv, with the indexes of the source and target
+/// alphabets followed by the file with the pairs.
+fn parse_conversion_table(text: &str) -> Result<ConversionTable, String> {
+    let invalid = || tr!(Message::InvalidConversionTable, text);
+    let mut parts = text.splitn(3, ':');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(source), Some(target), Some(file_name)) if !file_name.is_empty() => Ok(ConversionTable {
+            source: source.parse().map_err(|_| invalid())?,
+            target: target.parse().map_err(|_| invalid())?,
+            file_name: String::from(file_name)
+        }),
+        _ => Err(invalid())
+    }
+}
+
+/// Parses a comma-separated list of section names, like symbol-arrays,acceptations.
+fn parse_sections(text: &str) -> Result<Vec<Section>, String> {
+    text.split(',').map(|name| match name {
+        "symbol-arrays" => Ok(Section::SymbolArrays),
+        "languages" => Ok(Section::Languages),
+        "conversions" => Ok(Section::Conversions),
+        "correlations" => Ok(Section::Correlations),
+        "correlation-arrays" => Ok(Section::CorrelationArrays),
+        "acceptations" => Ok(Section::Acceptations),
+        "definitions" => Ok(Section::Definitions),
+        _ => Err(tr!(Message::UnknownSection, name))
+    }).collect()
+}
+
+/// Parses a range like 3..10, where the end is excluded. Any of the bounds can be omitted, meaning
+/// from the first or until the last entry.
+fn parse_range(text: &str) -> Result<Range<usize>, String> {
+    let invalid = || tr!(Message::InvalidRange, text);
+    let parse_bound = |bound: &str, default: usize| match bound {
+        "" => Ok(default),
+        bound => bound.parse().map_err(|_| invalid())
+    };
+
+    let (start, end) = text.split_once("..").ok_or_else(invalid)?;
+    Ok(parse_bound(start, 0)?..parse_bound(end, usize::MAX)?)
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum Command {
+    Dump,
+    Validate,
+    Fingerprint,
+    Serve,
+    Analyze,
+    Orphans,
+    Coverage,
+    Stats,
+    Hierarchy,
+    Tree,
+    Duplicates,
+    Sizes,
+    Search,
+    Json,
+    Graph,
+    Rules,
+    InspectTables,
+    Chars,
+    Flashcards,
+    Lint,
+    DefinedFrom,
+    AnalyzeText,
+    Schema,
+    Reencode,
+    CheckEqual,
+    Diff,
+    Import(ImportFormat)
+}
+
+impl Command {
+    /// Feature this binary was built without, but needed to run the command, if any.
+    fn missing_feature(self) -> Option<&'static str> {
+        match self {
+            Command::Json | Command::Graph | Command::Flashcards | Command::Rules | Command::Schema | Command::Diff if !cfg!(feature = "export") => Some("export"),
+            Command::Search | Command::AnalyzeText if !cfg!(feature = "search") => Some("search"),
+            Command::Serve if !cfg!(feature = "server") => Some("server"),
+            _ => None
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "dump" => Some(Command::Dump),
+            "validate" => Some(Command::Validate),
+            "fingerprint" => Some(Command::Fingerprint),
+            "serve" => Some(Command::Serve),
+            "analyze" => Some(Command::Analyze),
+            "orphans" => Some(Command::Orphans),
+            "coverage" => Some(Command::Coverage),
+            "stats" => Some(Command::Stats),
+            "hierarchy" => Some(Command::Hierarchy),
+            "tree" => Some(Command::Tree),
+            "duplicates" => Some(Command::Duplicates),
+            "sizes" => Some(Command::Sizes),
+            "json" => Some(Command::Json),
+            "graph" => Some(Command::Graph),
+            "rules" => Some(Command::Rules),
+            "inspect-tables" => Some(Command::InspectTables),
+            "chars" => Some(Command::Chars),
+            "flashcards" => Some(Command::Flashcards),
+            "lint" => Some(Command::Lint),
+            "reencode" => Some(Command::Reencode),
+            _ => None
+        }
+    }
+}
+
+// Options of the commands this binary was built without are still parsed, so that they are
+// rejected together with their command instead of as unknown arguments
+#[cfg_attr(not(all(feature = "download", feature = "export", feature = "search", feature = "server")), allow(dead_code))]
 struct Params {
-    input_file_name: String
+    command: Command,
+    input_file_name: String,
+    output_file_name: Option<String>,
+    other_input_file_name: Option<String>,
+    list: DumpList,
+    language: Option<LanguageCode>,
+    front_language: Option<LanguageCode>,
+    back_language: Option<LanguageCode>,
+    range: Range<usize>,
+    sections: Vec<Section>,
+    show_progress: bool,
+    color: ColorChoice,
+    mode: DecodingMode,
+    partial: bool,
+    offset: usize,
+    scan_magic: bool,
+    max_text_length: usize,
+    expected_fingerprint: Option<Fingerprint>,
+    semantic_fingerprint: bool,
+    show_histograms: bool,
+    json_output: bool,
+    pretty: bool,
+    rebuild_tables: bool,
+    split: bool,
+    stream: bool,
+    #[cfg(feature = "export")]
+    export_options: ExportOptions,
+    #[cfg(feature = "export")]
+    card_order: CardOrder,
+    report_memory: bool,
+    max_memory: Option<usize>,
+    pattern: Option<String>,
+    max_distance: Option<usize>,
+    prefix_only: bool,
+    alphabet: Option<usize>,
+    conversion: Option<usize>,
+    transliterate: bool,
+    display: Vec<DisplayPreference>,
+    #[cfg(feature = "export")]
+    rule_format: RuleFormat,
+    diff_format: DiffFormat,
+    dump_format: DumpFormat,
+    conversion_tables: Vec<ConversionTable>,
+    host: String,
+    port: u16,
+    cache_dir: Option<String>
+}
+
+fn next_value(args: &mut impl Iterator<Item = String>, option: &str) -> Result<String, String> {
+    args.next().ok_or_else(|| tr!(Message::MissingValue, option))
 }
 
 fn obtain_arguments() -> Result<Params, String> {
-    let mut next_is_input = false;
+    let mut command: Option<Command> = None;
     let mut input_file_name: Option<String> = None;
-    let mut is_first = true;
-    for arg in env::args() {
-        if is_first {
-            is_first = false;
-        }
-        else if next_is_input {
-            next_is_input = false;
-            input_file_name = Some(arg);
-        }
-        else if arg == "-i" {
+    let mut output_file_name: Option<String> = None;
+    let mut other_input_file_name: Option<String> = None;
+    let mut list = DumpList::Definitions;
+    let mut language: Option<LanguageCode> = None;
+    let mut front_language: Option<LanguageCode> = None;
+    let mut back_language: Option<LanguageCode> = None;
+    let mut range = 0..usize::MAX;
+    let mut sections: Vec<Section> = Vec::new();
+    let mut show_progress = true;
+    let mut color = ColorChoice::Auto;
+    let mut mode = DecodingMode::Strict;
+    let mut partial = false;
+    let mut offset = 0;
+    let mut scan_magic = false;
+    let mut max_text_length = DEFAULT_MAX_SYMBOL_ARRAY_LENGTH;
+    let mut expected_fingerprint: Option<Fingerprint> = None;
+    let mut show_histograms = false;
+    let mut json_output = false;
+    let mut semantic_fingerprint = false;
+    let mut pretty = false;
+    let mut rebuild_tables = false;
+    let mut split = false;
+    let mut stream = false;
+    let mut romanize = false;
+    let mut transliterate = false;
+    let mut display: Vec<DisplayPreference> = Vec::new();
+    let mut report_memory = false;
+    let mut max_memory: Option<usize> = None;
+    let mut pattern: Option<String> = None;
+    let mut max_distance: Option<usize> = None;
+    let mut prefix_only = false;
+    let mut alphabet: Option<usize> = None;
+    let mut conversion: Option<usize> = None;
+    let mut format: Option<String> = None;
+    let mut sort: Option<String> = None;
+    let mut normalize: Option<String> = None;
+    let mut conversion_tables: Vec<ConversionTable> = Vec::new();
+    let mut host = String::from("127.0.0.1");
+    let mut port: u16 = 8080;
+    let mut cache_dir: Option<String> = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let had_command = command.is_some();
+        if arg == "-i" {
             if input_file_name.is_none() {
-                next_is_input = true
+                input_file_name = Some(next_value(&mut args, &arg)?);
+            }
+            else {
+                return Err(String::from(tr!(Message::InputFileAlreadySet)));
+            }
+        }
+        else if arg == "-o" {
+            if output_file_name.is_none() {
+                output_file_name = Some(next_value(&mut args, &arg)?);
             }
             else {
-                return Err(String::from("Input file already set"));
+                return Err(String::from(tr!(Message::OutputFileAlreadySet)));
+            }
+        }
+        else if arg == "--list" {
+            list = DumpList::from_name(&next_value(&mut args, &arg)?)?;
+        }
+        else if arg == "--language" || arg == "--lang" {
+            let value = next_value(&mut args, &arg)?;
+            language = Some(value.parse().map_err(|_| tr!(Message::InvalidLanguage, value))?);
+        }
+        else if arg == "--front-lang" {
+            let value = next_value(&mut args, &arg)?;
+            front_language = Some(value.parse().map_err(|_| tr!(Message::InvalidLanguage, value))?);
+        }
+        else if arg == "--back-lang" {
+            let value = next_value(&mut args, &arg)?;
+            back_language = Some(value.parse().map_err(|_| tr!(Message::InvalidLanguage, value))?);
+        }
+        else if arg == "--sections" {
+            sections = parse_sections(&next_value(&mut args, &arg)?)?;
+        }
+        else if arg == "--range" {
+            range = parse_range(&next_value(&mut args, &arg)?)?;
+        }
+        else if arg == "--no-progress" {
+            show_progress = false;
+        }
+        else if arg == "--color" {
+            color = next_value(&mut args, &arg)?.parse()?;
+        }
+        else if arg == "--locale" {
+            // Already applied by requested_locale, but still validated here
+            next_value(&mut args, &arg)?.parse::<Locale>()?;
+        }
+        else if arg == "--lenient" {
+            mode = DecodingMode::Lenient;
+        }
+        else if arg == "--partial" {
+            partial = true;
+        }
+        else if arg == "--offset" {
+            let value = next_value(&mut args, &arg)?;
+            offset = value.parse().map_err(|_| tr!(Message::InvalidOffset, value))?;
+        }
+        else if arg == "--scan-magic" {
+            scan_magic = true;
+        }
+        else if arg == "--max-text-length" {
+            let value = next_value(&mut args, &arg)?;
+            max_text_length = value.parse().map_err(|_| tr!(Message::InvalidMaxTextLength, value))?;
+        }
+        else if arg == "--verify" {
+            expected_fingerprint = Some(Fingerprint::try_from(next_value(&mut args, &arg)?.as_str())?);
+        }
+        else if arg == "--semantic" {
+            semantic_fingerprint = true;
+        }
+        else if arg == "--json" {
+            json_output = true;
+        }
+        else if arg == "--histogram" {
+            show_histograms = true;
+        }
+        else if arg == "--pretty" {
+            pretty = true;
+        }
+        else if arg == "--compact" {
+            pretty = false;
+        }
+        else if arg == "--report-memory" {
+            report_memory = true;
+        }
+        else if arg == "--max-memory" {
+            let value = next_value(&mut args, &arg)?;
+            max_memory = Some(value.parse().map_err(|_| tr!(Message::InvalidMemoryBudget, value))?);
+        }
+        else if arg == "--stream" {
+            stream = true;
+        }
+        else if arg == "--romanize" {
+            romanize = true;
+        }
+        else if arg == "--transliterate" {
+            transliterate = true;
+        }
+        else if arg == "--display-lang" {
+            let value = next_value(&mut args, &arg)?;
+            display = value.split(',').map(|name| name.parse().map_err(|_| tr!(Message::InvalidDisplayLanguage, name))).collect::<Result<_, _>>()?;
+        }
+        else if arg == "--split" {
+            split = true;
+        }
+        else if arg == "--rebuild-tables" {
+            rebuild_tables = true;
+        }
+        else if arg == "--fuzzy" {
+            let value = next_value(&mut args, &arg)?;
+            max_distance = Some(value.parse().map_err(|_| tr!(Message::InvalidEdits, value))?);
+        }
+        else if arg == "--prefix" {
+            prefix_only = true;
+        }
+        else if arg == "--alphabet" {
+            let value = next_value(&mut args, &arg)?;
+            alphabet = Some(value.parse().map_err(|_| tr!(Message::InvalidAlphabet, value))?);
+        }
+        else if arg == "--conversion" {
+            let value = next_value(&mut args, &arg)?;
+            conversion = Some(value.parse().map_err(|_| tr!(Message::InvalidConversion, value))?);
+        }
+        else if arg == "--format" {
+            format = Some(next_value(&mut args, &arg)?);
+        }
+        else if arg == "--sort" {
+            sort = Some(next_value(&mut args, &arg)?);
+        }
+        else if arg == "--normalize" {
+            normalize = Some(next_value(&mut args, &arg)?);
+        }
+        else if arg == "--conversion-table" {
+            conversion_tables.push(parse_conversion_table(&next_value(&mut args, &arg)?)?);
+        }
+        else if arg == "--host" {
+            host = next_value(&mut args, &arg)?;
+        }
+        else if arg == "--cache-dir" {
+            cache_dir = Some(next_value(&mut args, &arg)?);
+        }
+        else if arg == "--port" {
+            let value = next_value(&mut args, &arg)?;
+            port = value.parse().map_err(|_| tr!(Message::InvalidPort, value))?;
+        }
+        else if command.is_none() && arg == "--schema" {
+            command = Some(Command::Schema);
+        }
+        else if command.is_none() && arg == "search" {
+            command = Some(Command::Search);
+            pattern = Some(next_value(&mut args, &arg)?);
+        }
+        else if command.is_none() && arg == "defined-from" {
+            command = Some(Command::DefinedFrom);
+            pattern = Some(next_value(&mut args, &arg)?);
+        }
+        else if command.is_none() && arg == "analyze-text" {
+            command = Some(Command::AnalyzeText);
+            pattern = Some(next_value(&mut args, &arg)?);
+        }
+        else if command.is_none() && arg == "diff" {
+            command = Some(Command::Diff);
+            other_input_file_name = Some(next_value(&mut args, &arg)?);
+        }
+        else if command.is_none() && arg == "--check-equal" {
+            if input_file_name.is_some() {
+                return Err(String::from(tr!(Message::InputFileAlreadySet)));
             }
+
+            command = Some(Command::CheckEqual);
+            input_file_name = Some(next_value(&mut args, &arg)?);
+            other_input_file_name = Some(next_value(&mut args, &arg)?);
+        }
+        else if command.is_none() && arg == "import" {
+            command = Some(Command::Import(ImportFormat::from_name(&next_value(&mut args, &arg)?)?));
+        }
+        else if command.is_none() && Command::from_name(&arg).is_some() {
+            command = Command::from_name(&arg);
         }
         else {
-            let mut s = String::from("Invalid argument ");
-            s.push_str(&arg);
-            return Err(s);
+            return Err(tr!(Message::InvalidArgument, arg));
+        }
+
+        if !had_command {
+            if let Some(feature) = command.and_then(Command::missing_feature) {
+                return Err(tr!(Message::FeatureNotAvailable, arg.as_str(), feature));
+            }
         }
     }
 
-    match input_file_name {
-        Some(name) => Ok(Params {
-            input_file_name: name
-        }),
+    if matches!(command, Some(Command::Import(_) | Command::Reencode)) && output_file_name.is_none() {
+        return Err(String::from(tr!(Message::MissingOutputFile)));
+    }
+
+    if command == Some(Command::Flashcards) && (front_language.is_none() || back_language.is_none()) {
+        return Err(String::from(tr!(Message::MissingFlashcardLanguages)));
+    }
+
+    if json_output && !matches!(command, Some(Command::Stats | Command::Analyze)) {
+        return Err(String::from(tr!(Message::JsonOnlyForStats)));
+    }
+
+    if json_output && !cfg!(feature = "export") {
+        return Err(tr!(Message::FeatureNotAvailable, "--json", "export"));
+    }
+
+    if romanize && !matches!(command, Some(Command::Json | Command::Flashcards | Command::Diff)) {
+        return Err(String::from(tr!(Message::RomanizeOnlyForExports)));
+    }
+
+    if normalize.is_some() && !matches!(command, Some(Command::Json | Command::Flashcards | Command::Diff | Command::Graph | Command::Rules)) {
+        return Err(String::from(tr!(Message::NormalizeOnlyForExports)));
+    }
+
+    if normalize.is_some() && !cfg!(feature = "normalization") {
+        return Err(tr!(Message::FeatureNotAvailable, "--normalize", "normalization"));
+    }
+
+    #[cfg(feature = "normalization")]
+    let normalization = match normalize.as_deref() {
+        Some(name) => Some(parse_normalization(name)?),
+        None => None
+    };
+
+    if sort.is_some() && command != Some(Command::Flashcards) {
+        return Err(String::from(tr!(Message::SortOnlyForFlashcards)));
+    }
+
+    #[cfg(feature = "export")]
+    let card_order = match sort.as_deref() {
+        Some(name) => parse_card_order(name)?,
+        None => CardOrder::Concept
+    };
+
+    if transliterate && command.is_some_and(|command| command != Command::Dump) {
+        return Err(String::from(tr!(Message::TransliterateOnlyForDump)));
+    }
+
+    if split && command != Some(Command::Json) {
+        return Err(String::from(tr!(Message::SplitOnlyForJson)));
+    }
+
+    if stream && command != Some(Command::Json) {
+        return Err(String::from(tr!(Message::StreamOnlyForJson)));
+    }
+
+    if stream && (pretty || split) {
+        return Err(String::from(tr!(Message::StreamWithPrettyOrSplit)));
+    }
+
+    if split && output_file_name.is_none() {
+        return Err(String::from(tr!(Message::MissingOutputDirectory)));
+    }
+
+    if !sections.is_empty() && command.is_some_and(|command| command != Command::Dump) {
+        return Err(String::from(tr!(Message::SectionsOnlyForDump)));
+    }
+
+    if prefix_only && max_distance.is_some() {
+        return Err(String::from(tr!(Message::PrefixAndFuzzy)));
+    }
+
+    // Formats are only known once the command is, as each command understands different ones
+    #[cfg(feature = "export")]
+    let mut rule_format = RuleFormat::Plain;
+    let mut diff_format = DiffFormat::JsonPatch;
+    let mut dump_format = DumpFormat::Text;
+    if let Some(name) = format.as_deref() {
+        match command {
+            None | Some(Command::Dump) => dump_format = parse_dump_format(name)?,
+            Some(Command::Diff) => diff_format = parse_diff_format(name)?,
+            // Without export, the rules command is already rejected above
+            #[cfg(feature = "export")]
+            Some(Command::Rules) => rule_format = parse_rule_format(name)?,
+            _ => return Err(String::from(tr!(Message::FormatOnlyForDumpDiffAndRules)))
+        }
+    }
+
+    if matches!(dump_format, DumpFormat::Records) && !sections.is_empty() {
+        return Err(String::from(tr!(Message::SectionsWithRecordDump)));
+    }
+
+    if matches!(dump_format, DumpFormat::Records) && !cfg!(feature = "export") {
+        return Err(tr!(Message::FeatureNotAvailable, "--format dump", "export"));
+    }
+
+    let input_file_name = match input_file_name {
+        Some(name) => name,
+        None if command == Some(Command::Schema) => String::new(),
         None => {
-            let mut s = String::from("Missing input file: try ");
-            s.push_str(&env::args().next().expect("wtf?"));
-            s.push_str(" -i <sdb-file>");
-            Err(s)
-        }
-    }
-}
-
-fn main() {
-    match obtain_arguments() {
-        Err(text) => println!("{}", text),
-        Ok(params) => {
-            println!("Reading file {}", params.input_file_name);
-            match File::open(&params.input_file_name) {
-                Err(_) => println!("Unable to open file {}", params.input_file_name),
-                Ok(file) => {
-                    let mut bytes = file.bytes();
-                    match file_utils::assert_next_is_same_text(&mut bytes, "SDB\x01").and_then(|_| {
-                        SdbReader::new(InputBitStream::from(&mut bytes)).read()
-                    }) {
-                        Ok(result) => {
-                            println!("Symbol arrays read - {} entries", result.symbol_arrays.len());
-                            println!("Languages read - {} languages found" , result.languages.len());
-                            println!("Conversions read - {} conversions found" , result.conversions.len());
-                            println!("Found {} concepts", result.max_concept);
-                            println!("Correlations read - {} correlations found", result.correlations.len());
-                            println!("Correlation arrays read - {} correlation arrays found", result.correlation_arrays.len());
-                            println!("Acceptations read - {} acceptations found", result.acceptations.len());
-                            println!("Definitions read - {} definitions found", result.definitions.len());
-
-                            fn concept_to_string(result: &SdbReadResult, concept: usize) -> String {
-                                for acc in result.acceptations.iter() {
-                                    if acc.concept == concept {
-                                        return result.get_complete_correlation(acc.correlation_array_index).into_values().reduce(|a, b| {
-                                            let mut c = String::new();
-                                            c.push_str(&a);
-                                            c.push('/');
-                                            c.push_str(&b);
-                                            c
-                                        }).unwrap()
-                                    }
-                                }
-
-                                panic!("No suitable string found for concept {}", concept);
-                            }
-
-                            for (concept, definition) in result.definitions.iter() {
-                                let mut text = String::new();
-                                text.push_str(&concept_to_string(&result, *concept));
-                                text.push_str(": ");
-                                text.push_str(&concept_to_string(&result, definition.base_concept));
-                                for complement in definition.complements.iter() {
-                                    text.push_str(" + ");
-                                    text.push_str(&concept_to_string(&result, *complement));
-                                }
-
-                                println!("  {}", text);
-                            }
-                        },
-                        Err(err) => println!("Error found: {}", err.message)
-                    }
+            let program = env::args().next().expect("wtf?");
+            return Err(tr!(Message::MissingInputFile, program, program, program, program));
+        }
+    };
+
+    Ok(Params {
+        command: command.unwrap_or(Command::Dump),
+        input_file_name,
+        output_file_name,
+        other_input_file_name,
+        list,
+        language,
+        front_language,
+        back_language,
+        range,
+        sections,
+        show_progress,
+        color,
+        mode,
+        partial,
+        offset,
+        scan_magic,
+        max_text_length,
+        expected_fingerprint,
+        semantic_fingerprint,
+        show_histograms,
+        json_output,
+        pretty,
+        rebuild_tables,
+        split,
+        stream,
+        #[cfg(feature = "export")]
+        export_options: ExportOptions {
+            romanize,
+            #[cfg(feature = "normalization")]
+            normalization
+        },
+        #[cfg(feature = "export")]
+        card_order,
+        report_memory,
+        max_memory,
+        pattern,
+        max_distance,
+        prefix_only,
+        alphabet,
+        conversion,
+        transliterate,
+        display,
+        #[cfg(feature = "export")]
+        rule_format,
+        diff_format,
+        dump_format,
+        conversion_tables,
+        host,
+        port,
+        cache_dir
+    })
+}
+
+fn init_logging(color: ColorChoice) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(stderr)
+        .with_ansi(color.is_enabled_for(&stderr()))
+        .with_target(false)
+        .without_time()
+        .init();
+}
+
+/// Callback receiving each acceptation as soon as it is decoded, instead of storing it.
+type AcceptationHandler<'h> = &'h mut dyn FnMut(&SdbReadResult, &Acceptation) -> Result<(), ReadError>;
+
+/// First bytes of any gzip file.
+const GZIP_MAGIC: [u8; 2] = [0x1F, 0x8B];
+
+/// First bytes of any ZIP archive, which starts with the header of its first entry.
+const ZIP_MAGIC: [u8; 4] = [0x50, 0x4B, 0x03, 0x04];
+
+/// Splits an input name like `archive.zip#inner.sdb` into the file name and the name of the entry
+/// within the archive. Names of existing files are never split, even if they contain `#`.
+fn split_entry_name(name: &str) -> (&str, Option<&str>) {
+    if !Path::new(name).exists() {
+        if let Some((file_name, entry_name)) = name.rsplit_once('#') {
+            return (file_name, Some(entry_name));
+        }
+    }
+
+    (name, None)
+}
+
+/// Returns the name of the only SDB file within the archive, whether compressed with gzip or not.
+#[cfg(feature = "archives")]
+fn find_sdb_entry<R: Read + Seek>(archive: &ZipArchive<R>, file_name: &str) -> Result<String, String> {
+    let mut names: Vec<&str> = archive.file_names().filter(|name| name.ends_with(".sdb") || name.ends_with(".sdb.gz")).collect();
+    names.sort();
+    match names.as_slice() {
+        [name] => Ok(String::from(*name)),
+        [] => Err(tr!(Message::NoSdbEntry, file_name)),
+        _ => Err(tr!(Message::SeveralSdbEntries, file_name, names.join(", ")))
+    }
+}
+
+/// Decodes the given entry of a ZIP archive, streaming it without extracting it. If no entry name
+/// is given, the only SDB file within the archive is decoded.
+#[cfg(feature = "archives")]
+fn read_archive<R: Read + Seek>(params: &Params, input: R, file_name: &str, entry_name: Option<&str>, handler: Option<AcceptationHandler>) -> Option<SdbReadResult> {
+    let result = ZipArchive::new(input).map_err(|err| tr!(Message::UnableToReadArchive, file_name, err)).and_then(|mut archive| {
+        let entry_name = match entry_name {
+            Some(name) => String::from(name),
+            None => find_sdb_entry(&archive, file_name)?
+        };
+
+        let entry = archive.by_name(&entry_name).map_err(|_| tr!(Message::ArchiveEntryNotFound, entry_name, file_name))?;
+        info!("{}", tr!(Message::ReadingArchiveEntry, entry_name));
+        Ok(decode_input(params, BufReader::new(entry), &entry_name, handler))
+    });
+
+    match result {
+        Ok(result) => result,
+        Err(text) => {
+            error!("{}", text);
+            None
+        }
+    }
+}
+
+#[cfg(not(feature = "archives"))]
+fn read_archive<R: Read + Seek>(_params: &Params, _input: R, file_name: &str, _entry_name: Option<&str>, _handler: Option<AcceptationHandler>) -> Option<SdbReadResult> {
+    error!("{}", tr!(Message::InputNeedsFeature, file_name, "archives"));
+    None
+}
+
+/// Decodes the given content, decompressing it on the fly if it is compressed with gzip, as is
+/// common for downloaded databases.
+fn decode_input<R: BufRead>(params: &Params, mut input: R, name: &str, handler: Option<AcceptationHandler>) -> Option<SdbReadResult> {
+    let is_gzip = match input.fill_buf() {
+        Ok(bytes) => bytes.starts_with(&GZIP_MAGIC),
+        Err(err) => {
+            error!("{}", tr!(Message::ErrorFound, err));
+            return None;
+        }
+    };
+
+    if is_gzip {
+        decode_gzip(params, input, name, handler)
+    }
+    else {
+        decode_sdb(params, input, handler)
+    }
+}
+
+#[cfg(feature = "archives")]
+fn decode_gzip<R: BufRead>(params: &Params, input: R, _name: &str, handler: Option<AcceptationHandler>) -> Option<SdbReadResult> {
+    debug!("Decompressing gzip input");
+    decode_sdb(params, BufReader::new(MultiGzDecoder::new(input)), handler)
+}
+
+#[cfg(not(feature = "archives"))]
+fn decode_gzip<R: BufRead>(_params: &Params, _input: R, name: &str, _handler: Option<AcceptationHandler>) -> Option<SdbReadResult> {
+    error!("{}", tr!(Message::InputNeedsFeature, name, "archives"));
+    None
+}
+
+/// Suggestion for the user to get past the given kind of error, if any.
+fn error_hint(kind: ReadErrorKind, mode: DecodingMode) -> Option<String> {
+    match kind {
+        ReadErrorKind::Io | ReadErrorKind::Cancelled => None,
+        ReadErrorKind::BadMagic => Some(String::from(tr!(Message::BadMagicHint))),
+        ReadErrorKind::UnsupportedVersion(version) if version > SDB_HEADER.as_bytes()[SDB_HEADER.len() - 1] => Some(String::from(tr!(Message::UnsupportedVersionHint))),
+        ReadErrorKind::UnsupportedVersion(_) => None,
+        ReadErrorKind::InvalidSymbol { .. } | ReadErrorKind::RangeViolation { .. } if mode == DecodingMode::Strict => Some(String::from(tr!(Message::CorruptedFileHint))),
+        ReadErrorKind::InvalidSymbol { .. } | ReadErrorKind::RangeViolation { .. } => None
+    }
+}
+
+fn decode_sdb<R: BufRead>(params: &Params, mut input: R, handler: Option<AcceptationHandler>) -> Option<SdbReadResult> {
+    let mut bytes = (&mut input).bytes();
+    let mut progress = ProgressReporter::new();
+    let mut peaks = SectionPeaks::new();
+    let header_result = (0..params.offset).try_for_each(|_| file_utils::read_u8(&mut bytes).map(|_| ()))
+        .and_then(|_| if params.scan_magic {
+            let skipped = file_utils::find_header(&mut bytes, SDB_HEADER)?;
+            info!("{}", tr!(Message::HeaderFound, params.offset + skipped));
+            Ok(())
+        }
+        else {
+            file_utils::read_header(&mut bytes, SDB_HEADER).map(|_| ())
+        })
+        .map_err(|err| ReadError::new(err.kind, tr!(Message::WhileReadingHeader, err.message)));
+    let read_result = header_result.map(|_| {
+        let mut reader = SdbReader::new(InputBitStream::from(&mut input)).with_mode(params.mode)
+            .with_sections(&params.sections)
+            .with_max_symbol_array_length(params.max_text_length);
+        if let Some(handler) = handler {
+            reader = reader.with_acceptation_handler(handler);
+        }
+
+        if params.show_progress || params.report_memory {
+            reader.with_progress(|section, processed, total| {
+                if params.show_progress {
+                    progress.update(section, processed, total);
+                }
+
+                if params.report_memory {
+                    peaks.update(section);
+                }
+            }).read_partial()
+        }
+        else {
+            reader.read_partial()
+        }
+    });
+    progress.finish();
+
+    if params.report_memory {
+        for (section, peak) in peaks.finish() {
+            info!("{}", tr!(Message::SectionPeakMemory, i18n::section_name(*section), format_kibibytes(*peak)));
+        }
+    }
+
+    // In lenient mode, errors are already reported as warnings
+    let read_result = read_result.and_then(|partial| match partial.error {
+        Some(err) if params.mode == DecodingMode::Strict => {
+            if !params.partial {
+                return Err(err);
+            }
+
+            error!("{}", tr!(Message::ErrorFound, err.message));
+            if let Some(section) = partial.failed_section {
+                warn!("{}", tr!(Message::PartialResult, i18n::section_name(section)));
+            }
+
+            Ok(partial.result)
+        },
+        _ => Ok(partial.result)
+    });
+
+    match read_result {
+        Ok(result) => {
+            if !result.warnings.is_empty() {
+                warn!("{}", tr!(Message::DecodingWarnings, result.warnings.len()));
+            }
+
+            Some(result)
+        },
+        Err(err) => {
+            error!("{}", tr!(Message::ErrorFound, err.message));
+            if let Some(hint) = error_hint(err.kind, params.mode) {
+                info!("{}", hint);
+            }
+
+            None
+        }
+    }
+}
+
+/// Decodes the given SDB file, ZIP archive or gzip compressed file.
+fn read_file(params: &Params, file_name: &str, entry_name: Option<&str>, handler: Option<AcceptationHandler>) -> Option<SdbReadResult> {
+    let mut input = match File::open(file_name) {
+        Ok(file) => BufReader::new(file),
+        Err(_) => {
+            error!("{}", tr!(Message::UnableToOpenFile, file_name));
+            return None;
+        }
+    };
+
+    let is_archive = match input.fill_buf() {
+        Ok(bytes) => bytes.starts_with(&ZIP_MAGIC),
+        Err(err) => {
+            error!("{}", tr!(Message::ErrorFound, err));
+            return None;
+        }
+    };
+
+    if is_archive || entry_name.is_some() {
+        read_archive(params, input, file_name, entry_name, handler)
+    }
+    else {
+        decode_input(params, input, file_name, handler)
+    }
+}
+
+/// Decodes the content of a stream that cannot be sought, like a download. SDB files are decoded
+/// while they arrive, but ZIP archives must be kept in memory until complete, as their directory
+/// is at the end.
+#[cfg(feature = "download")]
+fn read_stream<R: Read>(params: &Params, stream: R, name: &str, entry_name: Option<&str>, handler: Option<AcceptationHandler>) -> Option<SdbReadResult> {
+    let mut input = BufReader::new(stream);
+    let is_archive = match input.fill_buf() {
+        Ok(bytes) => bytes.starts_with(&ZIP_MAGIC),
+        Err(err) => {
+            error!("{}", tr!(Message::ErrorFound, err));
+            return None;
+        }
+    };
+
+    if is_archive || entry_name.is_some() {
+        let mut content = Vec::new();
+        match input.read_to_end(&mut content) {
+            Ok(_) => read_archive(params, Cursor::new(content), name, entry_name, handler),
+            Err(err) => {
+                error!("{}", tr!(Message::UnableToDownload, name, err));
+                None
+            }
+        }
+    }
+    else {
+        decode_input(params, input, name, handler)
+    }
+}
+
+fn is_url(name: &str) -> bool {
+    name.starts_with("http://") || name.starts_with("https://")
+}
+
+/// Name of the file where the content of the given URL is cached. It keeps the last segment of
+/// the URL for it to be recognizable, prefixed by a hash of the whole URL to avoid collisions.
+#[cfg(feature = "download")]
+fn cache_file_name(url: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    url.hash(&mut hasher);
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let last_segment = path.rsplit('/').next().unwrap_or("");
+    format!("{:016x}-{}", hasher.finish(), last_segment)
+}
+
+/// Downloads the given URL into the cache directory, unless it was already downloaded, and
+/// returns the path of the cached file.
+#[cfg(feature = "download")]
+fn download_to_cache(url: &str, cache_dir: &str) -> Result<PathBuf, String> {
+    let path = Path::new(cache_dir).join(cache_file_name(url));
+    if path.exists() {
+        info!("{}", tr!(Message::UsingCachedDownload, path.display()));
+        return Ok(path);
+    }
+
+    fs::create_dir_all(cache_dir).map_err(|err| tr!(Message::UnableToCreateFile, cache_dir, err))?;
+    info!("{}", tr!(Message::Downloading, url, path.display()));
+    let response = ureq::get(url).call().map_err(|err| tr!(Message::UnableToDownload, url, err))?;
+
+    // Downloads are written into a temporary file first, so that an interrupted download is
+    // never taken as a cached one
+    let partial_path = path.with_extension("part");
+    let file = File::create(&partial_path).map_err(|err| tr!(Message::UnableToCreateFile, partial_path.display(), err))?;
+    let mut writer = BufWriter::new(file);
+    let copy_result = io::copy(&mut response.into_body().into_reader(), &mut writer)
+        .and_then(|_| writer.flush())
+        .and_then(|_| fs::rename(&partial_path, &path));
+    match copy_result {
+        Ok(()) => Ok(path),
+        Err(err) => {
+            let _ = fs::remove_file(&partial_path);
+            Err(tr!(Message::UnableToDownload, url, err))
+        }
+    }
+}
+
+/// Decodes the content of the given URL, streaming it into the decoder, or reading it from the
+/// cache directory if one is given.
+#[cfg(feature = "download")]
+fn read_url(params: &Params, url: &str, entry_name: Option<&str>, handler: Option<AcceptationHandler>) -> Option<SdbReadResult> {
+    match &params.cache_dir {
+        Some(cache_dir) => match download_to_cache(url, cache_dir) {
+            Ok(path) => read_file(params, &path.to_string_lossy(), entry_name, handler),
+            Err(text) => {
+                error!("{}", text);
+                None
+            }
+        },
+        None => match ureq::get(url).call() {
+            Ok(response) => read_stream(params, response.into_body().into_reader(), url, entry_name, handler),
+            Err(err) => {
+                error!("{}", tr!(Message::UnableToDownload, url, err));
+                None
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "download"))]
+fn read_url(_params: &Params, url: &str, _entry_name: Option<&str>, _handler: Option<AcceptationHandler>) -> Option<SdbReadResult> {
+    error!("{}", tr!(Message::InputNeedsFeature, url, "download"));
+    None
+}
+
+fn format_kibibytes(bytes: usize) -> String {
+    format!("{:.1}", bytes as f64 / 1024.0)
+}
+
+fn read_database(params: &Params, handler: Option<AcceptationHandler>) -> Option<SdbReadResult> {
+    read_database_named(params, &params.input_file_name, handler)
+}
+
+/// Reads the given file or URL, which may refer to an entry within an archive.
+fn read_database_named(params: &Params, input_file_name: &str, handler: Option<AcceptationHandler>) -> Option<SdbReadResult> {
+    info!("{}", tr!(Message::ReadingFile, input_file_name));
+    if is_url(input_file_name) {
+        // Fragments are never sent to the server, so they can always be taken as entry names
+        match input_file_name.rsplit_once('#') {
+            Some((url, entry_name)) => read_url(params, url, Some(entry_name), handler),
+            None => read_url(params, input_file_name, None, handler)
+        }
+    }
+    else {
+        let (file_name, entry_name) = split_entry_name(input_file_name);
+        read_file(params, file_name, entry_name, handler)
+    }
+}
+
+/// Writes the differences from the input file to the file given with the diff command, in the
+/// requested format.
+#[cfg(feature = "export")]
+fn write_diff(params: &Params, result: &SdbReadResult, other_result: &SdbReadResult) -> Result<(), String> {
+    match params.diff_format {
+        DiffFormat::JsonPatch => write_output(params, &json_to_string(params, &diff::diff_json(result, other_result, &params.export_options)))
+    }
+}
+
+/// Reads both input files and checks whether their semantic fingerprints match, that is, whether
+/// they have the same content regardless of how it was encoded and indexed.
+fn check_equal(params: &Params) -> bool {
+    let other_input_file_name = params.other_input_file_name.as_deref().expect("Second file should be set with the command");
+    let (result, other_result) = match (read_database(params, None), read_database_named(params, other_input_file_name, None)) {
+        (Some(result), Some(other_result)) => (result, other_result),
+        _ => return false
+    };
+
+    let fingerprint = fingerprint::semantic_fingerprint(&result);
+    let other_fingerprint = fingerprint::semantic_fingerprint(&other_result);
+    if fingerprint == other_fingerprint {
+        info!("{}", tr!(Message::ContentsMatch, params.input_file_name, other_input_file_name));
+        true
+    }
+    else {
+        error!("{}", tr!(Message::ContentsDiffer, params.input_file_name, other_input_file_name));
+        error!("{}", tr!(Message::FingerprintMismatch, fingerprint, other_fingerprint));
+        false
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn import_sqlite(file_name: &str) -> Result<SdbReadResult, String> {
+    langbook_sdb_dump::sqlite::import(std::path::Path::new(file_name))
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn import_sqlite(_file_name: &str) -> Result<SdbReadResult, String> {
+    Err(String::from(tr!(Message::SqliteNotAvailable)))
+}
+
+fn import_word_list(file_name: &str, delimiter: char) -> Result<SdbReadResult, String> {
+    let content = fs::read_to_string(file_name).map_err(|err| tr!(Message::UnableToReadFile, file_name, err))?;
+    word_list::import(&content, delimiter)
+}
+
+/// Sets the conversions given by --conversion-table, reading their pairs from CSV files, or TSV
+/// files if their names end with .tsv.
+fn import_conversion_tables(params: &Params, result: &mut SdbReadResult) -> Result<(), String> {
+    for table in params.conversion_tables.iter() {
+        info!("{}", tr!(Message::ImportingConversionTable, table.file_name, table.source, table.target));
+        let content = fs::read_to_string(&table.file_name).map_err(|err| tr!(Message::UnableToReadFile, table.file_name, err))?;
+        let delimiter = if table.file_name.ends_with(".tsv") {
+            '\t'
+        }
+        else {
+            ','
+        };
+
+        word_list::import_conversion(result, table.source, table.target, &content, delimiter)
+            .map_err(|err| tr!(Message::UnableToImportConversionTable, table.file_name, err))?;
+    }
+
+    Ok(())
+}
+
+/// Imports the input file and writes its content as a new SDB file.
+fn import_database(params: &Params, format: ImportFormat) -> Result<(), String> {
+    info!("{}", tr!(Message::ImportingFile, params.input_file_name));
+    let mut result = match format {
+        ImportFormat::Sqlite => import_sqlite(&params.input_file_name)?,
+        ImportFormat::Csv => import_word_list(&params.input_file_name, ',')?,
+        ImportFormat::Tsv => import_word_list(&params.input_file_name, '\t')?
+    };
+
+    import_conversion_tables(params, &mut result)?;
+
+    let output_file_name = params.output_file_name.as_deref().expect("Output file should be checked when parsing arguments");
+    info!("{}", tr!(Message::WritingAcceptations, result.acceptations.len(), output_file_name));
+    let file = File::create(output_file_name).map_err(|err| tr!(Message::UnableToCreateFile, output_file_name, err))?;
+    match writer::write_database(BufWriter::new(file), &result) {
+        Ok(_) => Ok(()),
+        Err(err) => {
+            let _ = fs::remove_file(output_file_name);
+            Err(tr!(Message::UnableToWriteFile, output_file_name, err))
+        }
+    }
+}
+
+/// Writes the decoded database as a new SDB file and prints the size of each table, both when
+/// reusing the original table and when rebuilding it.
+fn reencode_database(params: &Params, mut result: SdbReadResult) -> Result<(), String> {
+    import_conversion_tables(params, &mut result)?;
+    let output_file_name = params.output_file_name.as_deref().expect("Output file should be checked when parsing arguments");
+    info!("{}", tr!(Message::WritingAcceptations, result.acceptations.len(), output_file_name));
+    let file = File::create(output_file_name).map_err(|err| tr!(Message::UnableToCreateFile, output_file_name, err))?;
+    match writer::reencode_database(BufWriter::new(file), &result, !params.rebuild_tables) {
+        Ok((_, table_sizes)) => {
+            print_table_sizes(&table_sizes, params.rebuild_tables);
+            Ok(())
+        },
+        Err(err) => {
+            let _ = fs::remove_file(output_file_name);
+            Err(tr!(Message::UnableToWriteFile, output_file_name, err))
+        }
+    }
+}
+
+fn print_table_sizes(table_sizes: &[TableSize], rebuild_tables: bool) {
+    println!("{:<40} {:>18} {:>18} {:>10}", tr!(Message::TableColumn), tr!(Message::OriginalBitsColumn), tr!(Message::RebuiltBitsColumn), tr!(Message::DeltaColumn));
+    for size in table_sizes {
+        let name = i18n::table_name(size.table);
+        match size.original_bits {
+            Some(original_bits) => {
+                let delta = size.rebuilt_bits as i64 - original_bits as i64;
+                println!("{:<40} {:>18} {:>18} {:>+10}", name, original_bits, size.rebuilt_bits, delta);
+            },
+            None => println!("{:<40} {:>18} {:>18} {:>10}", name, "-", size.rebuilt_bits, "-")
+        }
+    }
+
+    if rebuild_tables {
+        println!("{}", tr!(Message::TablesRebuilt));
+    }
+    else {
+        println!("{}", tr!(Message::TablesReused));
+    }
+}
+
+/// Writes the given text into the output file, or into the standard output if no output file was
+/// given.
+#[cfg(feature = "export")]
+fn write_output(params: &Params, text: &str) -> Result<(), String> {
+    match &params.output_file_name {
+        Some(file_name) => fs::write(file_name, format!("{}\n", text)).map_err(|err| tr!(Message::UnableToWriteFile, file_name, err)),
+        None => {
+            println!("{}", text);
+            Ok(())
+        }
+    }
+}
+
+/// Returns the conversion given by --conversion, which can be omitted if there is only one.
+fn chosen_conversion<'a>(params: &Params, result: &'a SdbReadResult) -> Result<&'a Conversion, String> {
+    match params.conversion {
+        Some(index) => result.conversions.get(index).ok_or_else(|| tr!(Message::ConversionNotFound, index, result.conversions.len())),
+        None => match result.conversions.len() {
+            0 => Err(String::from(tr!(Message::NoConversions))),
+            1 => Ok(&result.conversions[0]),
+            count => Err(tr!(Message::ConversionRequired, count))
+        }
+    }
+}
+
+#[cfg(feature = "export")]
+fn export_rules(params: &Params, result: &SdbReadResult) -> Result<(), String> {
+    let conversion = chosen_conversion(params, result)?;
+    info!("{}", tr!(Message::ExportingConversion, result.alphabet_label(conversion.source), result.alphabet_label(conversion.target)));
+    let rules = export::export_conversion_rules(result, conversion, params.rule_format, &params.export_options);
+    write_output(params, rules.trim_end_matches('\n'))
+}
+
+#[cfg(feature = "export")]
+fn json_to_string(params: &Params, value: &JsonValue) -> String {
+    if params.pretty {
+        value.to_pretty_string()
+    }
+    else {
+        value.to_string()
+    }
+}
+
+/// Exports the database as a JSON document while it is decoded, so that acceptations are never
+/// kept in memory. Returns whether the whole document could be written.
+#[cfg(feature = "export")]
+fn stream_json(params: &Params) -> bool {
+    let output: Box<dyn Write> = match &params.output_file_name {
+        Some(file_name) => match File::create(file_name) {
+            Ok(file) => Box::new(BufWriter::new(file)),
+            Err(err) => {
+                error!("{}", tr!(Message::UnableToCreateFile, file_name, err));
+                return false;
+            }
+        },
+        None => Box::new(BufWriter::new(stdout().lock()))
+    };
+
+    let mut driver = ExportDriver::new(JsonStreamWriter::new(output, params.export_options));
+    let mut handler = |result: &SdbReadResult, acceptation: &Acceptation| driver.visit_acceptation(result, acceptation)
+        .map_err(ReadError::from);
+
+    let written = read_database(params, Some(&mut handler)).map(|result| driver.finish(&result)
+        .and_then(|writer| {
+            let mut output = writer.into_inner();
+            output.write_all(b"\n").and_then(|_| output.flush())
+        }));
+
+    match written {
+        Some(Ok(())) => true,
+        Some(Err(err)) => {
+            error!("{}", err);
+            false
+        },
+        None => false
+    }
+}
+
+/// Writes each shard of the exported document into its own file within the output directory.
+/// Shards are built and serialized in parallel, as that takes longer than decoding for big
+/// databases.
+#[cfg(feature = "export")]
+fn write_shards(params: &Params, result: &SdbReadResult) -> Result<(), String> {
+    let directory = Path::new(params.output_file_name.as_deref().expect("Output directory should be checked when parsing arguments"));
+    fs::create_dir_all(directory).map_err(|err| tr!(Message::UnableToCreateFile, directory.display(), err))?;
+    thread::scope(|scope| {
+        let handles: Vec<_> = Shard::ALL.into_iter().map(|shard| scope.spawn(move || {
+            let path = directory.join(format!("{}.json", shard.name()));
+            let text = json_to_string(params, &export::export_shard(result, shard, &params.export_options));
+            debug!("Writing {} into {}", shard.name(), path.display());
+            fs::write(&path, format!("{}\n", text)).map_err(|err| tr!(Message::UnableToWriteFile, path.display(), err))
+        })).collect();
+
+        handles.into_iter().try_for_each(|handle| handle.join().expect("Export threads should not panic"))
+    })
+}
+
+fn dump_definitions(result: &SdbReadResult, display: &[DisplayPreference], palette: &Palette) {
+    for concept in result.definitions.keys() {
+        println!("  {}", definition_to_string(result, *concept, display, palette));
+    }
+}
+
+/// Returns the given defined concept followed by its base concept and its complements.
+fn definition_to_string(result: &SdbReadResult, concept: usize, display: &[DisplayPreference], palette: &Palette) -> String {
+    let definition = &result.definitions[&concept];
+    let mut text = String::new();
+    text.push_str(&palette.headword(&result.concept_to_string_with(concept, display)));
+    text.push_str(&palette.punctuation(": "));
+    text.push_str(&palette.reference(&result.concept_to_string_with(definition.base_concept, display)));
+    for complement in definition.complements.iter() {
+        text.push_str(&palette.punctuation(" + "));
+        text.push_str(&palette.reference(&result.concept_to_string_with(*complement, display)));
+    }
+
+    text
+}
+
+/// Prints, for each concept with the given text, all concepts defined from it, either as their
+/// base concept or as one of their complements. Returns false if no concept has that text.
+fn print_defined_from(result: &SdbReadResult, text: &str, display: &[DisplayPreference], palette: &Palette) -> bool {
+    let concepts = result.find_concepts(text);
+    if concepts.is_empty() {
+        error!("{}", tr!(Message::NoConceptForText, text));
+        return false;
+    }
+
+    let mut count = 0;
+    for concept in concepts {
+        println!("{} {}", palette.headword(&result.concept_to_string_with(concept, display)), palette.reference(&tr!(Message::ConceptLabel, concept)));
+        let mut defined = result.concepts_with_base(concept);
+        defined.extend(result.concepts_with_complement(concept));
+        defined.sort();
+        defined.dedup();
+        for defined_concept in defined.iter() {
+            println!("  {}", definition_to_string(result, *defined_concept, display, palette));
+        }
+
+        count += defined.len();
+    }
+
+    info!("{}", tr!(Message::DefinedConceptsFound, count));
+    true
+}
+
+/// Prints the segments in which the given sentence is split by matching whole texts of
+/// acceptations, with the acceptations and concepts found for each of them. If a language is
+/// given, only texts in that language are matched. Returns false if the language is not found.
+#[cfg(feature = "search")]
+fn print_text_analysis(result: &SdbReadResult, sentence: &str, language: Option<LanguageCode>, display: &[DisplayPreference], palette: &Palette) -> bool {
+    let alphabets = match language {
+        None => None,
+        Some(code) => match result.languages().find(|info| info.code == code) {
+            Some(info) => Some(info.alphabets),
+            None => {
+                error!("{}", tr!(Message::LanguageNotFound, code));
+                return false;
+            }
+        }
+    };
+
+    let segments = SearchIndex::new(result).segment(sentence, alphabets);
+    let mut unknown_count = 0;
+    for segment in segments.iter() {
+        let description = if segment.acceptations.is_empty() {
+            unknown_count += 1;
+            palette.punctuation(tr!(Message::UnknownSegment))
+        }
+        else {
+            let acceptations: Vec<String> = segment.acceptations.iter().map(|acceptation| {
+                let concept = result.acceptations[*acceptation].concept;
+                format!("{} {}", palette.reference(&tr!(Message::AcceptationLabel, acceptation)), palette.punctuation(&format!("({})", result.concept_to_string_with(concept, display))))
+            }).collect();
+            acceptations.join(&palette.punctuation(", "))
+        };
+
+        println!("  {}{}{}", palette.headword(&segment.text), palette.punctuation(": "), description);
+    }
+
+    info!("{}", tr!(Message::SegmentsFound, segments.len(), unknown_count));
+    true
+}
+
+fn dump_languages(result: &SdbReadResult, palette: &Palette) {
+    for language in result.languages() {
+        let alphabets: Vec<String> = language.alphabets.map(|index| tr!(Message::AlphabetLabel, index)).collect();
+        println!("  {}{}{}", palette.headword(&language.code.to_string()), palette.punctuation(": "), alphabets.join(&palette.punctuation(", ")));
+    }
+}
+
+/// Prints each correlation array with the indexes of the correlations it concatenates.
+fn dump_correlation_arrays(result: &SdbReadResult, palette: &Palette) {
+    for (index, array) in result.correlation_arrays.iter().enumerate() {
+        let correlations: Vec<String> = array.iter().map(|correlation| palette.reference(&correlation.index().to_string())).collect();
+        println!("  {}{}{}", index, palette.punctuation(": "), correlations.join(&palette.punctuation(" + ")));
+    }
+}
+
+/// Prints the content of each of the given sections, in the order they are found in the file.
+fn dump_sections(result: &SdbReadResult, params: &Params, transliteration: Option<&Conversion>, palette: &Palette) {
+    let mut sections = params.sections.clone();
+    sections.sort();
+    sections.dedup();
+    for section in sections {
+        println!("{}", palette.headword(&format!("{}:", i18n::section_name(section))));
+        match section {
+            Section::SymbolArrays => dump_symbol_arrays(result, &params.range, palette),
+            Section::Languages => dump_languages(result, palette),
+            Section::Conversions => dump_conversions(result, palette),
+            Section::Correlations => dump_correlations(result, transliteration, palette),
+            Section::CorrelationArrays => dump_correlation_arrays(result, palette),
+            Section::Acceptations => dump_acceptations(result, params.language, transliteration, palette),
+            Section::Definitions => dump_definitions(result, &params.display, palette)
+        }
+    }
+}
+
+/// Formats a text followed by the label of its alphabet. If a conversion from that alphabet is
+/// given, the converted text is placed between brackets right after the text.
+fn alphabet_text(result: &SdbReadResult, alphabet: Alphabet, text: &str, transliteration: Option<&Conversion>, palette: &Palette) -> String {
+    let mut formatted = palette.headword(text);
+    let converted = transliteration.filter(|conversion| conversion.source == alphabet)
+        .and_then(|conversion| conversion.convert(&result.symbol_arrays, text));
+    if let Some(converted) = converted {
+        formatted.push_str(&palette.punctuation(&format!(" [{}]", converted)));
+    }
+
+    formatted.push_str(&palette.punctuation(&format!(" ({})", result.alphabet_label(alphabet))));
+    formatted
+}
+
+/// Prints every acceptation with its concept and its texts in all alphabets. If a language is
+/// given, acceptations with texts in other languages are skipped.
+fn dump_acceptations(result: &SdbReadResult, language: Option<LanguageCode>, transliteration: Option<&Conversion>, palette: &Palette) {
+    for (index, (acc, texts)) in result.acceptations.iter().zip(result.complete_correlations()).enumerate() {
+        let code = texts.keys().next().and_then(|alphabet| result.language_code_of(*alphabet));
+
+        if language.is_some() && code != language {
+            continue;
+        }
+
+        let mut text = format!("{} ", index);
+        let alphabet_texts: Vec<String> = texts.iter().map(|(alphabet, text)| alphabet_text(result, *alphabet, text, transliteration, palette)).collect();
+        text.push_str(&alphabet_texts.join(&palette.punctuation(", ")));
+        text.push_str(&palette.punctuation(": "));
+        text.push_str(&palette.reference(&tr!(Message::ConceptLabel, acc.concept)));
+        println!("  {}", text);
+    }
+}
+
+/// Prints each conversion followed by its pairs, in the order they are encoded.
+fn dump_conversions(result: &SdbReadResult, palette: &Palette) {
+    for conversion in result.conversions.iter() {
+        println!("{}{}{}", palette.headword(&result.alphabet_label(conversion.source)), palette.punctuation(" → "), palette.headword(&result.alphabet_label(conversion.target)));
+        for (source, target) in conversion.text_pairs(&result.symbol_arrays) {
+            println!("  {}{}{}", source, palette.punctuation(" → "), palette.reference(target));
+        }
+    }
+}
+
+/// Prints each correlation with its text for each alphabet, and the number of correlation arrays
+/// including it.
+fn dump_correlations(result: &SdbReadResult, transliteration: Option<&Conversion>, palette: &Palette) {
+    let mut references = vec![0; result.correlations.len()];
+    for array in result.correlation_arrays.iter() {
+        let mut correlations: Vec<usize> = array.iter().map(|correlation| correlation.index()).collect();
+        correlations.sort_unstable();
+        correlations.dedup();
+        for correlation in correlations {
+            if let Some(count) = references.get_mut(correlation) {
+                *count += 1;
+            }
+        }
+    }
+
+    for (index, correlation) in result.correlations.iter().enumerate() {
+        let texts: Vec<String> = correlation.iter().map(|(alphabet, symbol_array)| {
+            let text = result.symbol_arrays.get(symbol_array.index()).unwrap_or("??");
+            alphabet_text(result, *alphabet, text, transliteration, palette)
+        }).collect();
+
+        println!("  {} {}{}{}", index, texts.join(&palette.punctuation(", ")), palette.punctuation(": "), palette.reference(&tr!(Message::ReferencedBy, references[index])));
+    }
+}
+
+/// Prints the symbol arrays within the given range of indexes, escaping quotes, backslashes and
+/// control characters.
+fn dump_symbol_arrays(result: &SdbReadResult, range: &Range<usize>, palette: &Palette) {
+    for (index, text) in result.symbol_arrays.iter().enumerate().skip(range.start).take(range.len()) {
+        println!("  {} {}", index, palette.headword(&format!("{:?}", text)));
+    }
+}
+
+/// Prints, one per line, all texts used by correlations for the given alphabet. Returns false if
+/// the alphabet is missing or does not exist.
+fn dump_texts(result: &SdbReadResult, alphabet: Option<usize>) -> bool {
+    let alphabet = match alphabet {
+        None => {
+            error!("{}", tr!(Message::AlphabetRequired));
+            return false;
+        },
+        Some(index) => match result.alphabet(index) {
+            Some(alphabet) => alphabet,
+            None => {
+                error!("{}", tr!(Message::AlphabetNotFound, index, result.alphabet_count()));
+                return false;
+            }
+        }
+    };
+
+    let mut count = 0;
+    for text in result.texts_in_alphabet(alphabet) {
+        println!("{}", text);
+        count += 1;
+    }
+
+    info!("{}", tr!(Message::TextsFound, count, result.alphabet_label(alphabet)));
+    true
+}
+
+/// Prints all violations found and returns whether the database is valid.
+fn validate_database(result: &SdbReadResult) -> bool {
+    let violations = validation::validate(result);
+    for violation in violations.iter() {
+        println!("{}", violation);
+    }
+
+    if violations.is_empty() {
+        info!("{}", tr!(Message::NoViolations));
+        true
+    }
+    else {
+        error!("{}", tr!(Message::ViolationsFound, violations.len()));
+        false
+    }
+}
+
+/// Prints the known inefficiencies of the format found in the database, with the bits estimated
+/// to be wasted by each of them. Returns false if there is any.
+fn print_lint(result: &SdbReadResult) -> bool {
+    let findings = lint::lint(result);
+    for finding in findings.iter() {
+        println!("{} ({})", finding.finding, tr!(Message::WastedBits, finding.wasted_bits));
+    }
+
+    if findings.is_empty() {
+        info!("{}", tr!(Message::NoLintFindings));
+        true
+    }
+    else {
+        let wasted_bits: usize = findings.iter().map(|finding| finding.wasted_bits).sum();
+        warn!("{}", tr!(Message::LintFindingsFound, findings.len(), wasted_bits));
+        false
+    }
+}
+
+/// Prints the fingerprint of the database and returns whether it matches the expected one, if any.
+/// The semantic fingerprint is used instead if requested.
+fn print_fingerprint(result: &SdbReadResult, expected: Option<Fingerprint>, semantic: bool) -> bool {
+    let actual = if semantic {
+        fingerprint::semantic_fingerprint(result)
+    }
+    else {
+        fingerprint::fingerprint(result)
+    };
+
+    println!("{}", actual);
+    match expected {
+        Some(expected) if expected != actual => {
+            error!("{}", tr!(Message::FingerprintMismatch, expected, actual));
+            false
+        },
+        Some(_) => {
+            info!("{}", tr!(Message::FingerprintMatches));
+            true
+        },
+        None => true
+    }
+}
+
+/// Prints the space used by each section, compared to the entropy of its values.
+fn print_analysis(result: &SdbReadResult) {
+    let analysis = analysis::analyze(result);
+    println!("{:<24} {:>16} {:>16} {:>10}", tr!(Message::SectionColumn), tr!(Message::ActualBitsColumn), tr!(Message::EntropyBitsColumn), tr!(Message::EfficiencyColumn));
+    for section in analysis.iter() {
+        println!("{:<24} {:>16} {:>16.0} {:>9.1}%", i18n::section_name(section.section), section.actual_bits, section.entropy_bits, section.efficiency() * 100.0);
+    }
+
+    let actual_bits: usize = analysis.iter().map(|section| section.actual_bits).sum();
+    let entropy_bits: f64 = analysis.iter().map(|section| section.entropy_bits).sum();
+    println!("{:<24} {:>16} {:>16.0}", tr!(Message::TotalRow), actual_bits, entropy_bits);
+}
+
+/// Prints the space taken by each section, in bytes and as a percentage of all sections.
+fn print_sizes(result: &SdbReadResult) {
+    let total_bits: usize = result.section_bits.iter().map(|(_, bits)| *bits).sum();
+    println!("{:<24} {:>12} {:>12}", tr!(Message::SectionColumn), tr!(Message::BytesColumn), tr!(Message::ShareColumn));
+    for (section, bits) in result.section_bits.iter() {
+        let share = if total_bits > 0 {
+            *bits as f64 / total_bits as f64
+        }
+        else {
+            0.0
+        };
+
+        println!("{:<24} {:>12.1} {:>11.1}%", i18n::section_name(*section), *bits as f64 / 8.0, share * 100.0);
+    }
+
+    println!("{:<24} {:>12.1}", tr!(Message::TotalRow), total_bits as f64 / 8.0);
+}
+
+/// Prints the number of symbols for each code length of the given table, if defined in the file.
+fn print_defined_table<S>(table: Table, defined: Option<&DefinedHuffmanTable<S>>) {
+    match defined {
+        None => println!("{}", tr!(Message::TableNotDefined, i18n::table_name(table))),
+        Some(defined) => {
+            println!("{}", tr!(Message::TableSummary, i18n::table_name(table), defined.symbol_count(), format!("{:.2}", defined.average_code_length())));
+            for (bits, level) in defined.levels().filter(|(_, level)| !level.is_empty()) {
+                println!("  {}", tr!(Message::TableLevel, bits, level.len()));
+            }
+        }
+    }
+}
+
+/// Prints all Huffman tables defined within the file.
+fn print_defined_tables(result: &SdbReadResult) {
+    let tables = &result.tables;
+    print_defined_table(Table::Chars, tables.chars.as_ref());
+    print_defined_table(Table::SymbolArrayLengths, tables.symbol_array_lengths.as_ref());
+    print_defined_table(Table::CorrelationLengths, tables.correlation_lengths.as_ref());
+    print_defined_table(Table::CorrelationArrayLengths, tables.correlation_array_lengths.as_ref());
+    print_defined_table(Table::AcceptationSetLengths, tables.acceptation_set_lengths.as_ref());
+    print_defined_table(Table::DefinitionLengths, tables.definition_lengths.as_ref());
+}
+
+/// Prints the frequency of each character in the texts of each alphabet, or only in the given
+/// one. Returns false if the given alphabet does not exist.
+fn print_character_frequencies(result: &SdbReadResult, alphabet: Option<usize>, palette: &Palette) -> bool {
+    if let Some(index) = alphabet {
+        if result.alphabet(index).is_none() {
+            error!("{}", tr!(Message::AlphabetNotFound, index, result.alphabet_count()));
+            return false;
+        }
+    }
+
+    let frequencies = analysis::character_frequencies(result);
+    for alphabet_frequencies in frequencies.iter().filter(|entry| alphabet.is_none_or(|index| entry.alphabet.index() == index)) {
+        let label = result.alphabet_label(alphabet_frequencies.alphabet);
+        println!("{}", palette.headword(&tr!(Message::AlphabetCharacters, label, alphabet_frequencies.characters.len())));
+        for (ch, count) in alphabet_frequencies.characters.iter() {
+            println!("  {:<8} U+{:04X} {:>10}", format!("{:?}", ch), *ch as u32, count);
+        }
+    }
+
+    if alphabet.is_none() {
+        let unused = analysis::characters_without_alphabet(result, &frequencies);
+        if !unused.is_empty() {
+            let unused: Vec<String> = unused.iter().map(|ch| format!("{:?} U+{:04X}", ch, *ch as u32)).collect();
+            println!("{}", tr!(Message::CharactersWithoutAlphabet, unused.join(", ")));
+        }
+    }
+
+    true
+}
+
+/// Prints all entries not referenced from other sections and returns whether there is none.
+fn print_orphans(result: &SdbReadResult) -> bool {
+    let orphans = analysis::find_orphans(result);
+    for index in orphans.symbol_arrays.iter() {
+        println!("{}", tr!(Message::UnusedSymbolArray, index, &result.symbol_arrays[*index]));
+    }
+
+    for index in orphans.correlations.iter() {
+        println!("{}", tr!(Message::UnusedCorrelation, index));
+    }
+
+    for index in orphans.correlation_arrays.iter() {
+        println!("{}", tr!(Message::UnusedCorrelationArray, index));
+    }
+
+    for concept in orphans.concepts.iter() {
+        println!("{}", tr!(Message::UnusedConcept, concept));
+    }
+
+    if orphans.is_empty() {
+        info!("{}", tr!(Message::NoOrphans));
+        true
+    }
+    else {
+        warn!("{}", tr!(Message::OrphansFound, orphans.symbol_arrays.len(), orphans.correlations.len(), orphans.correlation_arrays.len(), orphans.concepts.len()));
+        false
+    }
+}
+
+/// Prints, for each language, how many concepts can be expressed in it.
+fn print_coverage(result: &SdbReadResult, language: Option<LanguageCode>) {
+    println!("{:<8} {:>13} {:>10} {:>10}", tr!(Message::LanguageColumn), tr!(Message::RepresentedColumn), tr!(Message::DefinedColumn), tr!(Message::MissingColumn));
+    for coverage in analysis::language_coverage(result).into_iter().filter(|coverage| language.is_none_or(|code| coverage.language == code)) {
+        println!("{:<8} {:>13} {:>10} {:>10}", coverage.language.to_string(), coverage.represented, coverage.defined, coverage.missing);
+    }
+}
+
+fn print_histogram(title: &str, histogram: &analysis::Histogram) {
+    const MAX_BAR_LENGTH: usize = 40;
+    println!();
+    println!("{}", title);
+    let max_count = histogram.values().copied().max().unwrap_or(0);
+    for (length, count) in histogram.iter() {
+        let bar_length = (count * MAX_BAR_LENGTH).div_ceil(max_count);
+        println!("{:>6} {:>8} {}", length, count, "#".repeat(bar_length));
+    }
+}
+
+/// Prints the number of entries in each section, and optionally the distribution of their lengths.
+fn print_stats(result: &SdbReadResult, show_histograms: bool) {
+    println!("{}", tr!(Message::SymbolArrayCount, result.symbol_arrays.len()));
+    println!("{}", tr!(Message::LanguageCount, result.languages.len()));
+    println!("{}", tr!(Message::AlphabetCount, result.alphabet_count()));
+    println!("{}", tr!(Message::ConversionCount, result.conversions.len()));
+    println!("{}", tr!(Message::ConceptCount, result.max_concept));
+    println!("{}", tr!(Message::CorrelationCount, result.correlations.len()));
+    println!("{}", tr!(Message::CorrelationArrayCount, result.correlation_arrays.len()));
+    println!("{}", tr!(Message::AcceptationCount, result.acceptations.len()));
+    println!("{}", tr!(Message::DefinitionCount, result.definitions.len()));
+
+    if show_histograms {
+        let histograms = analysis::histograms(result);
+        print_histogram(tr!(Message::SymbolArrayLengths), &histograms.symbol_array_lengths);
+        print_histogram(tr!(Message::CorrelationArrayLengths), &histograms.correlation_array_lengths);
+        print_histogram(tr!(Message::AlphabetsPerCorrelation), &histograms.alphabets_per_correlation);
+        print_histogram(tr!(Message::ComplementsPerDefinition), &histograms.complements_per_definition);
+    }
+}
+
+fn chain_to_string(result: &SdbReadResult, chain: &[usize], display: &[DisplayPreference], separator: &str) -> String {
+    chain.iter().map(|concept| result.concept_to_string_with(*concept, display)).collect::<Vec<String>>().join(separator)
+}
+
+/// Prints the depth of the definitions and its deepest chains, and returns whether no cycle was found.
+fn print_hierarchy(result: &SdbReadResult, display: &[DisplayPreference]) -> bool {
+    const CHAIN_COUNT: usize = 10;
+    let hierarchy = analysis::definition_hierarchy(result);
+    println!("{}", tr!(Message::MaximumDepth, hierarchy.max_depth()));
+    for chain in hierarchy.deepest_chains(result, CHAIN_COUNT) {
+        println!("  {}", chain_to_string(result, &chain, display, " > "));
+    }
+
+    for cycle in hierarchy.cycles.iter() {
+        let mut chain = cycle.clone();
+        chain.push(cycle[0]);
+        println!("{}", tr!(Message::CycleFound, chain_to_string(result, &chain, display, " > ")));
+    }
+
+    if hierarchy.cycles.is_empty() {
+        true
+    }
+    else {
+        error!("{}", tr!(Message::CyclesFound, hierarchy.cycles.len()));
+        false
+    }
+}
+
+/// Prints the definitions as a tree rooted at each base concept that is not defined itself, with
+/// each defined concept indented below its base and followed by its complements. Returns false if
+/// any defined concept is left out because it is not reachable from any root due to a cycle.
+fn print_tree(result: &SdbReadResult, display: &[DisplayPreference], palette: &Palette) -> bool {
+    let mut children: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (concept, definition) in result.definitions.iter() {
+        children.entry(definition.base_concept).or_default().push(*concept);
+    }
+
+    let mut printed = 0;
+    for root in children.keys().filter(|concept| !result.definitions.contains_key(concept)) {
+        println!("{}", palette.headword(&result.concept_to_string_with(*root, display)));
+        let mut pending: Vec<(usize, usize)> = children[root].iter().rev().map(|concept| (*concept, 1)).collect();
+        while let Some((concept, depth)) = pending.pop() {
+            let mut text = palette.headword(&result.concept_to_string_with(concept, display));
+            for complement in result.definitions[&concept].complements.iter() {
+                text.push_str(&palette.punctuation(" + "));
+                text.push_str(&palette.reference(&result.concept_to_string_with(*complement, display)));
+            }
+
+            println!("{}{}", "  ".repeat(depth), text);
+            printed += 1;
+            if let Some(defined) = children.get(&concept) {
+                pending.extend(defined.iter().rev().map(|defined_concept| (*defined_concept, depth + 1)));
+            }
+        }
+    }
+
+    let left_out = result.definitions.len() - printed;
+    if left_out == 0 {
+        true
+    }
+    else {
+        error!("{}", tr!(Message::ConceptsOutOfTree, left_out));
+        false
+    }
+}
+
+fn print_duplicate_groups(groups: &[Vec<usize>], duplicated: Message, removable: Message) {
+    for group in groups.iter() {
+        let indexes: Vec<String> = group.iter().map(usize::to_string).collect();
+        println!("{}", tr!(duplicated, indexes.join(", ")));
+    }
+
+    if !groups.is_empty() {
+        info!("{}", tr!(removable, Duplicates::removable_count(groups)));
+    }
+}
+
+/// Prints all groups of entries sharing the same content and returns whether there is none.
+fn print_duplicates(result: &SdbReadResult) -> bool {
+    let duplicates = analysis::find_duplicates(result);
+    print_duplicate_groups(&duplicates.symbol_arrays, Message::DuplicatedSymbolArrays, Message::RemovableSymbolArrays);
+    print_duplicate_groups(&duplicates.correlations, Message::DuplicatedCorrelations, Message::RemovableCorrelations);
+    print_duplicate_groups(&duplicates.acceptations, Message::DuplicatedAcceptations, Message::RemovableAcceptations);
+
+    if duplicates.is_empty() {
+        info!("{}", tr!(Message::NoDuplicates));
+        true
+    }
+    else {
+        false
+    }
+}
+
+/// Prints all acceptations containing the given pattern, starting with it, or within the given
+/// number of edits, together with their concept, language and definition. The pattern is also
+/// converted back through all conversions, so that texts in their source alphabets are found too.
+#[cfg(feature = "search")]
+fn print_search_results(result: &SdbReadResult, params: &Params) -> bool {
+    let pattern = params.pattern.as_deref().expect("Pattern should be set with the command");
+    let alphabet = match params.alphabet {
+        None => None,
+        Some(index) => match result.alphabet(index) {
+            Some(alphabet) => Some(alphabet),
+            None => {
+                error!("{}", tr!(Message::AlphabetNotFound, index, result.alphabet_count()));
+                return false;
+            }
+        }
+    };
+
+    let mode = match params.max_distance {
+        _ if params.prefix_only => SearchMode::Prefix,
+        Some(max_distance) => SearchMode::Fuzzy(max_distance),
+        None => SearchMode::Substring
+    };
+
+    let mut patterns = vec![String::from(pattern)];
+    for converted in result.convert_back_all(pattern) {
+        debug!("Also searching {}", converted);
+        patterns.push(converted);
+    }
+
+    let mut matches = SearchIndex::new(result).find(&patterns, mode, alphabet);
+    if let Some(language) = params.language {
+        matches.retain(|search_match| result.language_code_of(search_match.alphabet) == Some(language));
+    }
+
+    for search_match in matches.iter() {
+        let concept = result.acceptations[search_match.acceptation].concept;
+        let mut text = tr!(Message::SearchResult, search_match.text, result.alphabet_label(search_match.alphabet), search_match.acceptation, result.concept_to_string_with(concept, &params.display));
+        if params.max_distance.is_some() {
+            text.push_str(&tr!(Message::SearchDistance, search_match.distance));
+        }
+
+        if let Some(definition) = result.definitions.get(&concept) {
+            text.push_str(": ");
+            text.push_str(&result.concept_to_string_with(definition.base_concept, &params.display));
+            for complement in definition.complements.iter() {
+                text.push_str(" + ");
+                text.push_str(&result.concept_to_string_with(*complement, &params.display));
+            }
+        }
+
+        println!("{}", text);
+    }
+
+    info!("{}", tr!(Message::MatchesFound, matches.len()));
+    true
+}
+
+/// Runs a command that works on an existing SDB file.
+fn run_command(params: &Params, result: SdbReadResult) -> ExitCode {
+    match params.command {
+        #[cfg(feature = "export")]
+        Command::Dump if matches!(params.dump_format, DumpFormat::Records) => match write_output(params, export::export_records(&result).trim_end()) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                error!("{}", err);
+                ExitCode::FAILURE
+            }
+        },
+        Command::Dump => {
+            let palette = Palette::new(params.color.is_enabled_for(&stdout()));
+            let transliteration = if params.transliterate {
+                match chosen_conversion(params, &result) {
+                    Ok(conversion) => Some(conversion),
+                    Err(err) => {
+                        error!("{}", err);
+                        return ExitCode::FAILURE;
+                    }
+                }
+            }
+            else {
+                None
+            };
+
+            if !params.sections.is_empty() {
+                dump_sections(&result, params, transliteration, &palette);
+            }
+            else {
+                match params.list {
+                    DumpList::Definitions => dump_definitions(&result, &params.display, &palette),
+                    DumpList::Acceptations => dump_acceptations(&result, params.language, transliteration, &palette),
+                    DumpList::Conversions => dump_conversions(&result, &palette),
+                    DumpList::Correlations => dump_correlations(&result, transliteration, &palette),
+                    DumpList::SymbolArrays => dump_symbol_arrays(&result, &params.range, &palette),
+                    DumpList::Texts => if !dump_texts(&result, params.alphabet) {
+                        return ExitCode::FAILURE;
+                    }
+                }
+            }
+
+            ExitCode::SUCCESS
+        },
+        Command::Validate => {
+            if validate_database(&result) {
+                ExitCode::SUCCESS
+            }
+            else {
+                ExitCode::FAILURE
+            }
+        },
+        Command::Fingerprint => {
+            if print_fingerprint(&result, params.expected_fingerprint, params.semantic_fingerprint) {
+                ExitCode::SUCCESS
+            }
+            else {
+                ExitCode::FAILURE
+            }
+        },
+        #[cfg(feature = "server")]
+        Command::Serve => match server::serve(result, &params.host, params.port) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                error!("{}", tr!(Message::UnableToServe, params.host, params.port, err));
+                ExitCode::FAILURE
+            }
+        },
+        #[cfg(feature = "export")]
+        Command::Analyze if params.json_output => match write_output(params, &json_to_string(params, &export::export_analysis(&result))) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                error!("{}", err);
+                ExitCode::FAILURE
+            }
+        },
+        Command::Analyze => {
+            print_analysis(&result);
+            ExitCode::SUCCESS
+        },
+        Command::Orphans => {
+            if print_orphans(&result) {
+                ExitCode::SUCCESS
+            }
+            else {
+                ExitCode::FAILURE
+            }
+        },
+        Command::Lint => {
+            if print_lint(&result) {
+                ExitCode::SUCCESS
+            }
+            else {
+                ExitCode::FAILURE
+            }
+        },
+        Command::Sizes => {
+            print_sizes(&result);
+            ExitCode::SUCCESS
+        },
+        Command::InspectTables => {
+            print_defined_tables(&result);
+            ExitCode::SUCCESS
+        },
+        Command::DefinedFrom => {
+            let palette = Palette::new(params.color.is_enabled_for(&stdout()));
+            let text = params.pattern.as_deref().expect("Text should be set with the command");
+            if print_defined_from(&result, text, &params.display, &palette) {
+                ExitCode::SUCCESS
+            }
+            else {
+                ExitCode::FAILURE
+            }
+        },
+        #[cfg(feature = "search")]
+        Command::AnalyzeText => {
+            let palette = Palette::new(params.color.is_enabled_for(&stdout()));
+            let sentence = params.pattern.as_deref().expect("Sentence should be set with the command");
+            if print_text_analysis(&result, sentence, params.language, &params.display, &palette) {
+                ExitCode::SUCCESS
+            }
+            else {
+                ExitCode::FAILURE
+            }
+        },
+        Command::Chars => {
+            let palette = Palette::new(params.color.is_enabled_for(&stdout()));
+            if print_character_frequencies(&result, params.alphabet, &palette) {
+                ExitCode::SUCCESS
+            }
+            else {
+                ExitCode::FAILURE
+            }
+        },
+        Command::Coverage => {
+            print_coverage(&result, params.language);
+            ExitCode::SUCCESS
+        },
+        #[cfg(feature = "export")]
+        Command::Stats if params.json_output => match write_output(params, &json_to_string(params, &export::export_stats(&result, params.show_histograms))) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                error!("{}", err);
+                ExitCode::FAILURE
+            }
+        },
+        Command::Stats => {
+            print_stats(&result, params.show_histograms);
+            ExitCode::SUCCESS
+        },
+        Command::Hierarchy => {
+            if print_hierarchy(&result, &params.display) {
+                ExitCode::SUCCESS
+            }
+            else {
+                ExitCode::FAILURE
+            }
+        },
+        Command::Tree => {
+            let palette = Palette::new(params.color.is_enabled_for(&stdout()));
+            if print_tree(&result, &params.display, &palette) {
+                ExitCode::SUCCESS
+            }
+            else {
+                ExitCode::FAILURE
+            }
+        },
+        Command::Duplicates => {
+            if print_duplicates(&result) {
+                ExitCode::SUCCESS
+            }
+            else {
+                ExitCode::FAILURE
+            }
+        },
+        #[cfg(feature = "search")]
+        Command::Search => {
+            if print_search_results(&result, params) {
+                ExitCode::SUCCESS
+            }
+            else {
+                ExitCode::FAILURE
+            }
+        },
+        #[cfg(feature = "export")]
+        Command::Json if params.split => match write_shards(params, &result) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                error!("{}", err);
+                ExitCode::FAILURE
+            }
+        },
+        #[cfg(feature = "export")]
+        Command::Diff => {
+            let other_input_file_name = params.other_input_file_name.as_deref().expect("Second file should be set with the command");
+            match read_database_named(params, other_input_file_name, None) {
+                None => ExitCode::FAILURE,
+                Some(other_result) => match write_diff(params, &result, &other_result) {
+                    Ok(()) => ExitCode::SUCCESS,
+                    Err(err) => {
+                        error!("{}", err);
+                        ExitCode::FAILURE
+                    }
+                }
+            }
+        },
+        #[cfg(feature = "export")]
+        Command::Json => match write_output(params, &json_to_string(params, &export::export_json(&result, &params.export_options))) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                error!("{}", err);
+                ExitCode::FAILURE
+            }
+        },
+        #[cfg(feature = "export")]
+        Command::Graph => match write_output(params, &json_to_string(params, &export::export_concept_graph(&result, &params.export_options))) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                error!("{}", err);
+                ExitCode::FAILURE
+            }
+        },
+        #[cfg(feature = "export")]
+        Command::Flashcards => {
+            let front_language = params.front_language.expect("Front language should be set with the command");
+            let back_language = params.back_language.expect("Back language should be set with the command");
+            let sort_alphabet = match params.alphabet {
+                None => None,
+                Some(index) => match result.alphabet(index) {
+                    Some(alphabet) => Some(alphabet),
+                    None => {
+                        error!("{}", tr!(Message::AlphabetNotFound, index, result.alphabet_count()));
+                        return ExitCode::FAILURE;
+                    }
+                }
+            };
+
+            let cards = export::export_flashcards(&result, front_language, back_language, &params.export_options, &params.card_order, sort_alphabet);
+            info!("{}", tr!(Message::FlashcardsGenerated, cards.lines().count()));
+            match write_output(params, cards.trim_end_matches('\n')) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(err) => {
+                    error!("{}", err);
+                    ExitCode::FAILURE
+                }
+            }
+        },
+        #[cfg(feature = "export")]
+        Command::Rules => match export_rules(params, &result) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                error!("{}", err);
+                ExitCode::FAILURE
+            }
+        },
+        Command::Reencode => match reencode_database(params, result) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                error!("{}", err);
+                ExitCode::FAILURE
+            }
+        },
+        Command::Schema | Command::Import(_) | Command::CheckEqual => unreachable!("Import, schema and check-equal commands are run before reading a single SDB file"),
+        #[cfg(not(all(feature = "export", feature = "search", feature = "server")))]
+        _ => unreachable!("Commands needing a feature this binary was built without are rejected when parsing the arguments")
+    }
+}
+
+/// Returns the locale given with --locale, if any and valid. This is looked for before parsing
+/// the rest of the arguments, so that any error found while parsing them is already localized.
+fn requested_locale() -> Option<Locale> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    args.windows(2).find(|pair| pair[0] == "--locale").and_then(|pair| pair[1].parse().ok())
+}
+
+#[global_allocator]
+static ALLOCATOR: TrackingAllocator = TrackingAllocator;
+
+fn main() -> ExitCode {
+    i18n::set_locale(requested_locale().unwrap_or_else(Locale::from_env));
+    let arguments = obtain_arguments();
+    init_logging(arguments.as_ref().map_or(ColorChoice::Auto, |params| params.color));
+    if let Ok(params) = &arguments {
+        if params.report_memory || params.max_memory.is_some() {
+            memory::enable();
+        }
+
+        if let Some(megabytes) = params.max_memory {
+            memory::set_budget(megabytes.saturating_mul(1024 * 1024));
+        }
+    }
+
+    let exit_code = run(&arguments);
+    if let Ok(Params { report_memory: true, .. }) = &arguments {
+        info!("{}", tr!(Message::PeakMemory, format_kibibytes(memory::total_peak())));
+    }
+
+    exit_code
+}
+
+fn run(arguments: &Result<Params, String>) -> ExitCode {
+    match arguments {
+        Err(text) => {
+            error!("{}", text);
+            ExitCode::FAILURE
+        },
+        Ok(params) => match params.command {
+            #[cfg(feature = "export")]
+            Command::Schema => match write_output(params, export::SCHEMA.trim_end()) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(err) => {
+                    error!("{}", err);
+                    ExitCode::FAILURE
+                }
+            },
+            Command::CheckEqual => {
+                if check_equal(params) {
+                    ExitCode::SUCCESS
+                }
+                else {
+                    ExitCode::FAILURE
+                }
+            },
+            Command::Import(format) => match import_database(params, format) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(err) => {
+                    error!("{}", err);
+                    ExitCode::FAILURE
+                }
+            },
+            #[cfg(feature = "export")]
+            // Streaming writes the same document without keeping acceptations in memory, so it
+            // is also used whenever a memory budget is given and the output allows it
+            Command::Json if params.stream || (params.max_memory.is_some() && !params.pretty && !params.split) => {
+                if stream_json(params) {
+                    ExitCode::SUCCESS
+                }
+                else {
+                    if let Some(file_name) = &params.output_file_name {
+                        let _ = fs::remove_file(file_name);
+                    }
+
+                    ExitCode::FAILURE
                 }
+            },
+            _ => match read_database(params, None) {
+                None => ExitCode::FAILURE,
+                Some(result) => run_command(params, result)
             }
         }
     }
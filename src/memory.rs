@@ -0,0 +1,182 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::io::{stderr, Write};
+use std::ptr;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use langbook_sdb_dump::sdb::Section;
+use crate::i18n::{tr, Message};
+
+/// Whether allocations are being tracked. Until enabled, the allocator only checks this flag, so
+/// that runs without --report-memory or --max-memory do not pay for the shared counters.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Bytes currently allocated in the heap, since tracking was enabled.
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+
+/// Maximum number of bytes allocated at once since the last call to take_peak.
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+/// Maximum number of bytes allocated at once since the process started.
+static TOTAL_PEAK: AtomicUsize = AtomicUsize::new(0);
+
+/// Maximum number of bytes that can be allocated at once.
+static BUDGET: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+/// Message printed once the budget is exceeded, already localized when the budget is set, as
+/// nothing can be allocated at that point.
+static BUDGET_MESSAGE: OnceLock<String> = OnceLock::new();
+
+/// Whether the message about the budget was already printed.
+static BUDGET_REPORTED: AtomicBool = AtomicBool::new(false);
+
+/// Allocator delegating into the system one, keeping track of the heap usage to report its peak
+/// and to refuse allocations exceeding the budget. Refused allocations are handled by the
+/// standard library, that aborts the process right after the message about the budget is
+/// printed, instead of the process being killed by the system once it runs out of memory.
+pub struct TrackingAllocator;
+
+/// Records the given number of bytes as allocated, and returns false, without recording them,
+/// if that would exceed the budget.
+fn grow(size: usize) -> bool {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return true;
+    }
+
+    let current = CURRENT.fetch_add(size, Ordering::Relaxed) + size;
+    if current > BUDGET.load(Ordering::Relaxed) {
+        // The budget is removed, so that the standard library can still allocate while it
+        // reports the refused allocation and aborts
+        CURRENT.fetch_sub(size, Ordering::Relaxed);
+        BUDGET.store(usize::MAX, Ordering::Relaxed);
+        if !BUDGET_REPORTED.swap(true, Ordering::Relaxed) {
+            if let Some(message) = BUDGET_MESSAGE.get() {
+                let _ = stderr().write_all(message.as_bytes());
+            }
+        }
+
+        return false;
+    }
+
+    PEAK.fetch_max(current, Ordering::Relaxed);
+    TOTAL_PEAK.fetch_max(current, Ordering::Relaxed);
+    true
+}
+
+fn shrink(size: usize) {
+    if ENABLED.load(Ordering::Relaxed) {
+        // Memory allocated before tracking was enabled may be released afterwards
+        let _ = CURRENT.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |current| Some(current.saturating_sub(size)));
+    }
+}
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if !grow(layout.size()) {
+            return ptr::null_mut();
+        }
+
+        let ptr = System.alloc(layout);
+        if ptr.is_null() {
+            shrink(layout.size());
+        }
+
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        if !grow(layout.size()) {
+            return ptr::null_mut();
+        }
+
+        let ptr = System.alloc_zeroed(layout);
+        if ptr.is_null() {
+            shrink(layout.size());
+        }
+
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        shrink(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if new_size > layout.size() {
+            if !grow(new_size - layout.size()) {
+                return ptr::null_mut();
+            }
+
+            let new_ptr = System.realloc(ptr, layout, new_size);
+            if new_ptr.is_null() {
+                shrink(new_size - layout.size());
+            }
+
+            new_ptr
+        }
+        else {
+            let new_ptr = System.realloc(ptr, layout, new_size);
+            if !new_ptr.is_null() {
+                shrink(layout.size() - new_size);
+            }
+
+            new_ptr
+        }
+    }
+}
+
+/// Starts tracking allocations. Memory allocated before is not accounted for.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Sets the maximum number of bytes that can be allocated at once.
+pub fn set_budget(bytes: usize) {
+    let _ = BUDGET_MESSAGE.set(format!("{}\n", tr!(Message::MemoryBudgetExceeded)));
+    BUDGET.store(bytes, Ordering::Relaxed);
+}
+
+/// Returns the maximum number of bytes allocated at once since the last call, and starts
+/// measuring again from the current usage.
+pub fn take_peak() -> usize {
+    PEAK.swap(CURRENT.load(Ordering::Relaxed), Ordering::Relaxed)
+}
+
+/// Returns the maximum number of bytes allocated at once since the process started.
+pub fn total_peak() -> usize {
+    TOTAL_PEAK.load(Ordering::Relaxed)
+}
+
+/// Tracks the heap usage peak for each section while it is being decoded.
+pub struct SectionPeaks {
+    section: Option<Section>,
+    peaks: Vec<(Section, usize)>
+}
+
+impl SectionPeaks {
+    pub fn new() -> Self {
+        Self {
+            section: None,
+            peaks: Vec::new()
+        }
+    }
+
+    /// Records the peak of the previous section, if the given one is a different one.
+    pub fn update(&mut self, section: Section) {
+        if self.section != Some(section) {
+            self.finish();
+            take_peak();
+            self.section = Some(section);
+        }
+    }
+
+    /// Records the peak of the current section, if any, and returns the peaks of all sections
+    /// in the order they were decoded.
+    pub fn finish(&mut self) -> &[(Section, usize)] {
+        if let Some(section) = self.section.take() {
+            self.peaks.push((section, take_peak()));
+        }
+
+        &self.peaks
+    }
+}
@@ -0,0 +1,42 @@
+use std::time::Duration;
+use indicatif::{ProgressBar, ProgressStyle};
+use langbook_sdb_dump::sdb::Section;
+use crate::i18n::{self, tr, Message};
+
+/// Shows in the terminal the section being decoded and the number of records already processed,
+/// out of the records within that section.
+pub struct ProgressReporter {
+    bar: Option<ProgressBar>,
+    section: Option<Section>
+}
+
+impl ProgressReporter {
+    pub fn new() -> Self {
+        Self {
+            bar: None,
+            section: None
+        }
+    }
+
+    pub fn update(&mut self, section: Section, processed: usize, total: usize) {
+        if self.section != Some(section) {
+            self.finish();
+            let bar = ProgressBar::new(u64::try_from(total).unwrap());
+            bar.set_style(ProgressStyle::with_template(tr!(Message::ProgressTemplate)).unwrap());
+            bar.set_message(i18n::section_name(section));
+            bar.enable_steady_tick(Duration::from_millis(100));
+            self.bar = Some(bar);
+            self.section = Some(section);
+        }
+
+        if let Some(bar) = &self.bar {
+            bar.set_position(u64::try_from(processed).unwrap());
+        }
+    }
+
+    pub fn finish(&mut self) {
+        if let Some(bar) = self.bar.take() {
+            bar.finish_and_clear();
+        }
+    }
+}
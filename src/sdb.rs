@@ -1,11 +1,39 @@
-use std::collections::{HashMap, HashSet};
-use std::fmt::{Display, Formatter, Write};
-use std::hash::{Hash, Hasher};
-use crate::file_utils::ReadError;
-use crate::huffman::{HuffmanTable, InputBitStream, IntegerNumberHuffmanTable, NaturalNumberHuffmanTable, NaturalUsizeHuffmanTable, RangedIntegerHuffmanTable, RangedNaturalUsizeHuffmanTable};
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter, Write};
+use core::ops::{Index, Range};
+use core::str::FromStr;
+use core::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use tracing::{debug, info, instrument, warn};
+use crate::file_utils::{ReadError, ReadErrorKind};
+use crate::io::Read;
+use crate::huffman::{DefinedHuffmanTable, HuffmanTable, InputBitStream, IntegerNumberHuffmanTable, NaturalNumberHuffmanTable, NaturalUsizeHuffmanTable, RangedIntegerHuffmanTable, RangedNaturalUsizeHuffmanTable};
 
-struct LanguageCode {
-    code: u16
+/// Bytes every SDB file starts with. The last one is the version of the format.
+pub const SDB_HEADER: &str = "SDB\x01";
+
+/// Maximum number of characters accepted for a symbol array unless changed with
+/// [SdbReader::with_max_symbol_array_length].
+pub const DEFAULT_MAX_SYMBOL_ARRAY_LENGTH: usize = 1 << 16;
+
+/// Maximum number of entries allocated in advance for a count read from the file. Larger counts
+/// are only allocated as their entries are decoded, so that a corrupted count cannot exhaust the
+/// memory before the end of the file is reached.
+const MAX_PREALLOCATED_ENTRIES: usize = 1 << 16;
+
+fn preallocation(count: usize) -> usize {
+    count.min(MAX_PREALLOCATED_ENTRIES)
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub struct LanguageCode {
+    pub(crate) code: u16
 }
 
 impl LanguageCode {
@@ -20,60 +48,206 @@ impl LanguageCode {
     }
 }
 
+impl TryFrom<&str> for LanguageCode {
+    type Error = String;
+
+    fn try_from(text: &str) -> Result<Self, Self::Error> {
+        let mut chars = text.chars();
+        match (chars.next(), chars.next(), chars.next()) {
+            (Some(first @ 'a'..='z'), Some(second @ 'a'..='z'), None) => Ok(Self::new((first as u32 - 'a' as u32) * 26 + (second as u32 - 'a' as u32))),
+            _ => Err(format!("Invalid language code {}, expected 2 lowercase letters", text))
+        }
+    }
+}
+
+impl FromStr for LanguageCode {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Self::try_from(text)
+    }
+}
+
+impl PartialEq<str> for LanguageCode {
+    fn eq(&self, other: &str) -> bool {
+        LanguageCode::try_from(other).is_ok_and(|code| code == *self)
+    }
+}
+
+impl PartialEq<&str> for LanguageCode {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
 impl Display for LanguageCode {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         f.write_char(char::try_from(u32::from(self.code) / 26 + ('a' as u32)).expect(""))?;
         f.write_char(char::try_from(u32::from(self.code) % 26 + ('a' as u32)).expect(""))
     }
 }
 
 pub struct Language {
-    code: LanguageCode,
-    number_of_alphabets: usize
+    pub code: LanguageCode,
+    pub number_of_alphabets: usize
 }
 
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct SymbolArrayIndex {
-    index: usize
+    pub(crate) index: usize
 }
 
-#[derive(Copy, Clone)]
-pub struct Alphabet {
-    index: usize
+impl SymbolArrayIndex {
+    /// Position of the symbol array within the symbol arrays section.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl From<usize> for SymbolArrayIndex {
+    /// Builds an index without checking that the symbol array exists. Use
+    /// [SdbReadResult::symbol_array_index] to check it against a decoded database.
+    fn from(index: usize) -> Self {
+        Self {
+            index
+        }
+    }
+}
+
+impl From<SymbolArrayIndex> for usize {
+    fn from(index: SymbolArrayIndex) -> usize {
+        index.index
+    }
+}
+
+/// Storage for all the symbol arrays within the database.
+///
+/// Instead of allocating a String per symbol array, all texts are concatenated into a single
+/// String, keeping only the offset where each of them ends.
+#[derive(Default)]
+pub struct SymbolArrays {
+    text: String,
+    ends: Vec<usize>
+}
+
+impl SymbolArrays {
+    fn with_capacity(symbol_array_count: usize) -> Self {
+        Self {
+            text: String::new(),
+            ends: Vec::with_capacity(symbol_array_count)
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&str> {
+        let end = *self.ends.get(index)?;
+        let start = if index == 0 {
+            0
+        }
+        else {
+            self.ends[index - 1]
+        };
+
+        Some(&self.text[start..end])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        (0..self.len()).map(|index| &self[index])
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn push(&mut self, text: &str) {
+        self.text.push_str(text);
+        self.ends.push(self.text.len());
+    }
 }
 
-impl PartialEq<Self> for Alphabet {
-    fn eq(&self, other: &Self) -> bool {
-        self.index == other.index
+impl Index<usize> for SymbolArrays {
+    type Output = str;
+
+    fn index(&self, index: usize) -> &str {
+        self.get(index).expect("Symbol array index out of bounds")
     }
 }
 
-impl Eq for Alphabet {
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Alphabet {
+    pub(crate) index: usize
 }
 
-impl Hash for Alphabet {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.index.hash(state)
+impl Alphabet {
+    /// Position of this alphabet, counting the alphabets of all languages in order.
+    pub fn index(&self) -> usize {
+        self.index
     }
 }
 
 pub struct Conversion {
-    source: Alphabet,
-    target: Alphabet,
-    pairs: Vec<(SymbolArrayIndex, SymbolArrayIndex)>
+    pub source: Alphabet,
+    pub target: Alphabet,
+    pub pairs: Vec<(SymbolArrayIndex, SymbolArrayIndex)>
 }
 
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct CorrelationIndex {
-    index: usize
+    pub(crate) index: usize
 }
 
-#[derive(Copy, Clone, Eq, PartialEq)]
+impl CorrelationIndex {
+    /// Position of the correlation within the correlations section.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl From<usize> for CorrelationIndex {
+    /// Builds an index without checking that the correlation exists. Use
+    /// [SdbReadResult::correlation_index] to check it against a decoded database.
+    fn from(index: usize) -> Self {
+        Self {
+            index
+        }
+    }
+}
+
+impl From<CorrelationIndex> for usize {
+    fn from(index: CorrelationIndex) -> usize {
+        index.index
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct CorrelationArrayIndex {
-    index: usize
+    pub(crate) index: usize
 }
 
-impl Hash for CorrelationArrayIndex {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.index.hash(state)
+impl CorrelationArrayIndex {
+    /// Position of the correlation array within the correlation arrays section.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl From<usize> for CorrelationArrayIndex {
+    /// Builds an index without checking that the correlation array exists. Use
+    /// [SdbReadResult::correlation_array_index] to check it against a decoded database.
+    fn from(index: usize) -> Self {
+        Self {
+            index
+        }
+    }
+}
+
+impl From<CorrelationArrayIndex> for usize {
+    fn from(index: CorrelationArrayIndex) -> usize {
+        index.index
     }
 }
 
@@ -84,11 +258,154 @@ pub struct Acceptation {
 
 pub struct Definition {
     pub base_concept: usize,
-    pub complements: HashSet<usize>
+    pub complements: BTreeSet<usize>
+}
+
+/// Sections in which the database is split, in the same order they are found in the file.
+#[derive(Copy, Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Section {
+    SymbolArrays,
+    Languages,
+    Conversions,
+    Correlations,
+    CorrelationArrays,
+    Acceptations,
+    Definitions
+}
+
+impl Display for Section {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Section::SymbolArrays => "symbol arrays",
+            Section::Languages => "languages",
+            Section::Conversions => "conversions",
+            Section::Correlations => "correlations",
+            Section::CorrelationArrays => "correlation arrays",
+            Section::Acceptations => "acceptations",
+            Section::Definitions => "definitions"
+        })
+    }
+}
+
+/// How the reader should behave when it finds values that are not valid for the format.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DecodingMode {
+    /// Returns an error as soon as an invalid value is found.
+    Strict,
+
+    /// Records a warning for each invalid value found and tries to continue decoding. If decoding
+    /// cannot continue, the sections decoded so far are returned.
+    Lenient
+}
+
+/// Record being decoded, used to give some context when an error is found.
+struct SectionPosition {
+    section: Section,
+    record: usize,
+    record_count: Option<usize>
+}
+
+impl Display for SectionPosition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self.record_count {
+            None => write!(f, "{} (before its length was known)", self.section),
+            Some(count) if self.record < count => write!(f, "{} (record {} of {})", self.section, self.record + 1, count),
+            Some(count) => write!(f, "{} (after its {} records)", self.section, count)
+        }
+    }
+}
+
+/// Flag to stop a decoding from another thread, like the user interface of an application. Clones
+/// share the same flag, so that one of them can be given to the reader while the others are kept
+/// to cancel it.
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests any decoding using this token to stop as soon as it finishes its current record.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, AtomicOrdering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(AtomicOrdering::Relaxed)
+    }
+}
+
+/// Callback receiving the section being decoded, the records processed and the records within it.
+type ProgressCallback<'a> = dyn FnMut(Section, usize, usize) + 'a;
+
+/// Callback receiving each acceptation decoded, together with the sections decoded before it.
+type AcceptationHandler<'a> = dyn FnMut(&SdbReadResult, &Acceptation) -> Result<(), ReadError> + 'a;
+
+/// Callback decoding an extra section, receiving the stream positioned at its start, and all the
+/// standard sections already decoded. Any data decoded is expected to be kept by the callback.
+pub type SectionHandler<'a, R> = dyn FnMut(&mut InputBitStream<'a, R>, &SdbReadResult) -> Result<(), ReadError> + 'a;
+
+/// Handlers for extra sections appended after the standard ones, so that extensions of the format
+/// can reuse this decoder. Each handler is registered for a version of the format, as declared in
+/// the header, and a position, counting from 0 for the section right after the definitions.
+///
+/// Sections cannot be located without decoding the ones before them, so extra sections are only
+/// decoded up to the first position without handler for the version of the file.
+pub struct SectionRegistry<'a, R: Read> {
+    handlers: BTreeMap<(u8, usize), Box<SectionHandler<'a, R>>>
 }
 
-pub struct SdbReader<'a> {
-    stream: InputBitStream<'a>,
+impl<'a, R: Read> SectionRegistry<'a, R> {
+    pub fn new() -> Self {
+        Self {
+            handlers: BTreeMap::new()
+        }
+    }
+
+    /// Registers the handler for the extra section at the given position in files declaring the
+    /// given version, replacing any handler previously registered for them.
+    pub fn register(&mut self, version: u8, position: usize, handler: impl FnMut(&mut InputBitStream<'a, R>, &SdbReadResult) -> Result<(), ReadError> + 'a) {
+        self.handlers.insert((version, position), Box::new(handler));
+    }
+
+    /// Takes the handlers for the given version, in order, up to the first missing position.
+    fn take_handlers(self, version: u8) -> Vec<Box<SectionHandler<'a, R>>> {
+        let mut handlers = Vec::new();
+        for ((handler_version, position), handler) in self.handlers {
+            if handler_version == version && position == handlers.len() {
+                handlers.push(handler);
+            }
+        }
+
+        handlers
+    }
+}
+
+impl<'a, R: Read> Default for SectionRegistry<'a, R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct SdbReader<'a, R: Read> {
+    stream: InputBitStream<'a, R>,
+    position: Option<SectionPosition>,
+    section_start: usize,
+    section_bits: Vec<(Section, usize)>,
+    tables: DefinedTables,
+    progress: Option<Box<ProgressCallback<'a>>>,
+    cancellation: Option<CancellationToken>,
+    acceptation_handler: Option<Box<AcceptationHandler<'a>>>,
+    extra_section_handlers: Vec<Box<SectionHandler<'a, R>>>,
+    extra_section: Option<usize>,
+    mode: DecodingMode,
+    last_section: Option<Section>,
+    skip_symbol_arrays: bool,
+    max_symbol_array_length: usize,
+    warnings: Vec<String>,
     natural3_table: NaturalNumberHuffmanTable,
     natural4_table: NaturalNumberHuffmanTable,
     natural8_table: NaturalNumberHuffmanTable,
@@ -97,21 +414,116 @@ pub struct SdbReader<'a> {
     natural8_usize_table: NaturalUsizeHuffmanTable
 }
 
+/// Tables defined within the file, each one used to encode a kind of value. They are kept so
+/// that the content can be encoded again with the same tables. Tables are missing when the
+/// section defining them is empty, or the content was not read from a file.
+#[derive(Clone, Default)]
+pub struct DefinedTables {
+    pub chars: Option<DefinedHuffmanTable<char>>,
+    pub symbol_array_lengths: Option<DefinedHuffmanTable<u32>>,
+    pub correlation_lengths: Option<DefinedHuffmanTable<i32>>,
+    pub correlation_array_lengths: Option<DefinedHuffmanTable<i32>>,
+    pub acceptation_set_lengths: Option<DefinedHuffmanTable<i32>>,
+    pub definition_lengths: Option<DefinedHuffmanTable<u32>>
+}
+
+#[derive(Default)]
 pub struct SdbReadResult {
-    pub symbol_arrays: Vec<String>,
+    pub symbol_arrays: SymbolArrays,
     pub languages: Vec<Language>,
     pub conversions: Vec<Conversion>,
     pub max_concept: usize,
-    pub correlations: Vec<HashMap<Alphabet, SymbolArrayIndex>>,
+    pub correlations: Vec<BTreeMap<Alphabet, SymbolArrayIndex>>,
     pub correlation_arrays: Vec<Vec<CorrelationIndex>>,
     pub acceptations: Vec<Acceptation>,
-    pub definitions: HashMap<usize, Definition>
+    pub definitions: BTreeMap<usize, Definition>,
+
+    /// Number of bits used by each section decoded, in the same order they were found. The size of
+    /// the tables is included in the section using them. This is empty when not read from a file.
+    pub section_bits: Vec<(Section, usize)>,
+
+    pub tables: DefinedTables,
+
+    /// Invalid values found while decoding in lenient mode. This is always empty in strict mode.
+    pub warnings: Vec<String>
+}
+
+/// A language together with the range of alphabets it owns.
+pub struct LanguageInfo {
+    pub code: LanguageCode,
+    pub alphabet_count: usize,
+
+    /// Absolute indexes of the alphabets of this language.
+    pub alphabets: Range<usize>
+}
+
+/// Entry within a list of preferences used to choose the text shown for a concept.
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum DisplayPreference {
+    /// Texts in all the alphabets of the language, joined with slashes.
+    Language(LanguageCode),
+
+    /// Text in the alphabet at the given position within the language, as in alphabet labels
+    /// like "ja:1".
+    Alphabet(LanguageCode, usize)
+}
+
+impl FromStr for DisplayPreference {
+    type Err = String;
+
+    /// Parses either a language code, like "es", or an alphabet label, like "ja:1".
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.split_once(':') {
+            None => Ok(DisplayPreference::Language(LanguageCode::try_from(text)?)),
+            Some((code, position)) => match position.parse() {
+                Ok(position) => Ok(DisplayPreference::Alphabet(LanguageCode::try_from(code)?, position)),
+                Err(_) => Err(format!("Invalid alphabet position {}", position))
+            }
+        }
+    }
+}
+
+/// Acceptations of a concept, with their texts already resolved.
+pub struct ConceptEntry<'a> {
+    pub concept: usize,
+
+    /// Acceptations of the concept in the order they are found, each one with its texts sorted by
+    /// alphabet, as returned by get_complete_correlation.
+    pub acceptations: Vec<(&'a Acceptation, BTreeMap<Alphabet, String>)>
+}
+
+/// Sections decoded before finding an error, if any.
+pub struct PartialReadResult {
+    /// All sections decoded before the error. The section where it was found, and all the ones
+    /// after it, are left empty.
+    pub result: SdbReadResult,
+
+    /// Error that stopped the decoding, if any.
+    pub error: Option<ReadError>,
+
+    /// Section being decoded when the error was found, or None if it was found before any, or
+    /// within an extra section.
+    pub failed_section: Option<Section>
 }
 
-impl<'a> SdbReader<'a> {
-    pub fn new(stream: InputBitStream<'a>) -> Self {
+impl<'a, R: Read> SdbReader<'a, R> {
+    pub fn new(stream: InputBitStream<'a, R>) -> Self {
         Self {
             stream,
+            position: None,
+            section_start: 0,
+            section_bits: Vec::new(),
+            tables: DefinedTables::default(),
+            progress: None,
+            cancellation: None,
+            acceptation_handler: None,
+            extra_section_handlers: Vec::new(),
+            extra_section: None,
+            mode: DecodingMode::Strict,
+            last_section: None,
+            skip_symbol_arrays: false,
+            max_symbol_array_length: DEFAULT_MAX_SYMBOL_ARRAY_LENGTH,
+            warnings: Vec::new(),
             natural3_table: NaturalNumberHuffmanTable::create_with_alignment(3),
             natural4_table: NaturalNumberHuffmanTable::create_with_alignment(4),
             natural8_table: NaturalNumberHuffmanTable::create_with_alignment(8),
@@ -121,27 +533,210 @@ impl<'a> SdbReader<'a> {
         }
     }
 
-    fn read_symbol_arrays(&mut self, symbol_array_count: usize, symbol_arrays_length_table: impl HuffmanTable<u32>, chars_table: impl HuffmanTable<char>) -> Result<Vec<String>, ReadError> {
-        let mut symbol_arrays: Vec<String> = Vec::with_capacity(symbol_array_count);
-        for _ in 0..symbol_array_count {
-            let length = self.stream.read_symbol(&symbol_arrays_length_table)?;
-            let mut array = String::new();
+    /// Sets a callback to be called each time a record is decoded, receiving the section being
+    /// read, the number of records within that section already processed, and the total number
+    /// of records within that section.
+    pub fn with_progress(mut self, callback: impl FnMut(Section, usize, usize) + 'a) -> Self {
+        self.progress = Some(Box::new(callback));
+        self
+    }
+
+    /// Sets a token to be checked after each record decoded. Once cancelled, the decoding stops
+    /// with a [ReadErrorKind::Cancelled] error, even in lenient mode.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Sets a handler to be called with each acceptation as soon as it is decoded, together with
+    /// the sections decoded so far. Acceptations given to the handler are not stored in the
+    /// result, so that they do not need to be kept in memory. Any error returned by the handler
+    /// stops the decoding.
+    pub fn with_acceptation_handler(mut self, handler: impl FnMut(&SdbReadResult, &Acceptation) -> Result<(), ReadError> + 'a) -> Self {
+        self.acceptation_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Decodes the extra sections registered for the given version, which must be the one declared
+    /// in the header, after the standard ones. Extra sections are not decoded when the decoding
+    /// is stopped earlier through [SdbReader::with_sections].
+    pub fn with_section_registry(mut self, registry: SectionRegistry<'a, R>, version: u8) -> Self {
+        self.extra_section_handlers = registry.take_handlers(version);
+        self
+    }
+
+    /// Sets how invalid values should be handled. By default, the reader is strict.
+    pub fn with_mode(mut self, mode: DecodingMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Stops decoding once all the given sections have been read. Sections can only be located by
+    /// decoding the ones before them, so previous sections are decoded too, but later ones are
+    /// skipped and left empty in the result. All sections are decoded if none is given.
+    ///
+    /// As an exception, when only languages and correlation arrays are given, no text is needed,
+    /// so symbol arrays are skipped without decoding their texts and left empty too.
+    pub fn with_sections(mut self, sections: &[Section]) -> Self {
+        self.last_section = sections.iter().max().copied();
+        self.skip_symbol_arrays = !sections.is_empty() && sections.iter().all(|section| matches!(section, Section::Languages | Section::CorrelationArrays));
+        self
+    }
+
+    /// Sets the maximum number of characters accepted for a symbol array. Longer ones stop the
+    /// decoding with an error, even in lenient mode, as their characters cannot be skipped
+    /// without reading them.
+    pub fn with_max_symbol_array_length(mut self, max_length: usize) -> Self {
+        self.max_symbol_array_length = max_length;
+        self
+    }
+
+    /// Reads the length of the next symbol array, failing if it exceeds the maximum allowed.
+    fn read_symbol_array_length(&mut self, table: &impl HuffmanTable<u32>, index: usize) -> Result<usize, ReadError> {
+        let length = usize::try_from(self.stream.read_symbol(table)?).unwrap_or(usize::MAX);
+        if length > self.max_symbol_array_length {
+            return Err(ReadError::range_violation(format!("Symbol array {} has {} characters, but the maximum allowed is {}", index, length, self.max_symbol_array_length)));
+        }
+
+        Ok(length)
+    }
+
+    /// Whether decoding should stop after the given section.
+    fn is_last_section(&self, section: Section) -> bool {
+        self.last_section == Some(section)
+    }
+
+    /// Reports an invalid value. In strict mode this returns an error. In lenient mode the message
+    /// is stored as a warning, and the caller is expected to replace the value and continue.
+    fn violation(&mut self, message: String) -> Result<(), ReadError> {
+        match self.mode {
+            DecodingMode::Strict => Err(ReadError::range_violation(message)),
+            DecodingMode::Lenient => {
+                warn!("{}", message);
+                self.warnings.push(message);
+                Ok(())
+            }
+        }
+    }
+
+    /// Converts a length read from a table of signed integers, replacing negative values by 0.
+    fn length_from_i32(&mut self, value: i32, what: &str) -> Result<usize, ReadError> {
+        match usize::try_from(value) {
+            Ok(length) => Ok(length),
+            Err(_) => {
+                self.violation(format!("Negative length {} found for {}", value, what))?;
+                Ok(0)
+            }
+        }
+    }
+
+    /// Records the number of bits used by the section being decoded, if any.
+    fn end_section(&mut self) {
+        if let Some(position) = &self.position {
+            let bits_read = self.stream.bits_read();
+            self.section_bits.push((position.section, bits_read - self.section_start));
+            self.section_start = bits_read;
+        }
+    }
+
+    fn begin_section(&mut self, section: Section) {
+        self.end_section();
+        self.position = Some(SectionPosition {
+            section,
+            record: 0,
+            record_count: None
+        });
+    }
+
+    fn set_record_count(&mut self, record_count: usize) {
+        if let Some(position) = &mut self.position {
+            position.record_count = Some(record_count);
+        }
+    }
+
+    /// Adds the section and record being decoded, and the number of bytes read, to the given error.
+    fn locate_error(&self, err: ReadError) -> ReadError {
+        let bytes_read = self.stream.bytes_read();
+        let kind = match (err.kind, &self.position) {
+            (ReadErrorKind::InvalidSymbol { section: None }, Some(position)) => ReadErrorKind::InvalidSymbol {
+                section: Some(position.section)
+            },
+            (ReadErrorKind::RangeViolation { section: None, index: None }, Some(position)) => ReadErrorKind::RangeViolation {
+                section: Some(position.section),
+                index: position.record_count.filter(|count| position.record < *count).map(|_| position.record)
+            },
+            (kind, _) => kind
+        };
+
+        ReadError::new(kind, match (&self.position, self.extra_section) {
+            (Some(position), _) => format!("{} while reading {}, {} bytes read after the header", err.message, position, bytes_read),
+            (None, Some(extra_section)) => format!("{} while reading extra section {}, {} bytes read after the header", err.message, extra_section, bytes_read),
+            (None, None) => format!("{}, {} bytes read after the header", err.message, bytes_read)
+        })
+    }
+
+    /// Reports the records processed within the current section, and checks whether the decoding
+    /// has been cancelled.
+    fn report_progress(&mut self, section: Section, processed: usize) -> Result<(), ReadError> {
+        let mut total = processed;
+        if let Some(position) = &mut self.position {
+            position.record = processed;
+            total = position.record_count.unwrap_or(processed);
+        }
+
+        if let Some(callback) = &mut self.progress {
+            callback(section, processed, total);
+        }
+
+        match &self.cancellation {
+            Some(token) if token.is_cancelled() => Err(ReadError::new(ReadErrorKind::Cancelled, "Decoding cancelled")),
+            _ => Ok(())
+        }
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    fn read_symbol_arrays(&mut self, symbol_array_count: usize, symbol_arrays_length_table: impl HuffmanTable<u32>, chars_table: impl HuffmanTable<char>) -> Result<SymbolArrays, ReadError> {
+        if self.skip_symbol_arrays {
+            for index in 0..symbol_array_count {
+                let length = self.read_symbol_array_length(&symbol_arrays_length_table, index)?;
+                self.stream.skip_symbols(&chars_table, length)?;
+                self.report_progress(Section::SymbolArrays, index + 1)?;
+            }
+
+            info!("Symbol arrays skipped - {} entries", symbol_array_count);
+            return Ok(SymbolArrays::default());
+        }
+
+        let mut symbol_arrays = SymbolArrays::with_capacity(preallocation(symbol_array_count));
+        for index in 0..symbol_array_count {
+            let length = self.read_symbol_array_length(&symbol_arrays_length_table, index)?;
             for _ in 0..length {
-                array.push(self.stream.read_symbol(&chars_table)?);
+                symbol_arrays.text.push(self.stream.read_symbol(&chars_table)?);
             }
-            symbol_arrays.push(array);
+            symbol_arrays.ends.push(symbol_arrays.text.len());
+            self.report_progress(Section::SymbolArrays, index + 1)?;
         }
 
+        symbol_arrays.text.shrink_to_fit();
+        info!("Symbol arrays read - {} entries", symbol_arrays.len());
+
         Ok(symbol_arrays)
     }
 
+    #[instrument(level = "debug", skip_all)]
     fn read_languages(&mut self) -> Result<Vec<Language>, ReadError> {
+        self.begin_section(Section::Languages);
         let language_count = self.stream.read_symbol(&self.natural8_usize_table)?;
+        self.set_record_count(language_count);
 
         let last_valid_lang_code = 26 * 26 - 1;
         let mut first_valid_lang_code = 0;
-        let mut languages: Vec<Language> = Vec::with_capacity(language_count);
-        for _ in 0..language_count {
+        let mut languages: Vec<Language> = Vec::with_capacity(preallocation(language_count));
+        for index in 0..language_count {
+            if first_valid_lang_code > last_valid_lang_code {
+                return Err(ReadError::range_violation(format!("Language {} found after the last valid language code", index)));
+            }
+
             let table = RangedIntegerHuffmanTable::new(first_valid_lang_code, last_valid_lang_code);
             let raw_lang_code = self.stream.read_symbol(&table)?;
             let code = LanguageCode::new(raw_lang_code);
@@ -151,19 +746,33 @@ impl<'a> SdbReader<'a> {
             languages.push(Language {
                 code,
                 number_of_alphabets
-            })
+            });
+            self.report_progress(Section::Languages, languages.len())?;
         }
 
+        info!("Languages read - {} languages found", languages.len());
         Ok(languages)
     }
 
+    #[instrument(level = "debug", skip_all)]
     fn read_conversions(&mut self, alphabet_count: usize, symbol_array_count: usize) -> Result<Vec<Conversion>, ReadError> {
+        self.begin_section(Section::Conversions);
         let number_of_conversions = self.stream.read_symbol(&self.natural8_usize_table)?;
+        self.set_record_count(number_of_conversions);
+        debug!(number_of_conversions);
+        if number_of_conversions == 0 {
+            return Ok(Vec::new());
+        }
+
+        if alphabet_count == 0 || symbol_array_count == 0 {
+            return Err(ReadError::range_violation("Conversions found, but there are no alphabets or symbol arrays to refer to"));
+        }
+
         let symbol_array_table = RangedIntegerHuffmanTable::new(0, u32::try_from(symbol_array_count - 1).unwrap());
         let max_valid_alphabet = alphabet_count - 1;
         let mut min_source_alphabet = 0usize;
         let mut min_target_alphabet = 0usize;
-        let mut conversions: Vec<Conversion> = Vec::with_capacity(number_of_conversions);
+        let mut conversions: Vec<Conversion> = Vec::with_capacity(preallocation(number_of_conversions));
         for _ in 0..number_of_conversions {
             let source_alphabet_table = RangedNaturalUsizeHuffmanTable::new(min_source_alphabet, max_valid_alphabet);
             let source_alphabet_index = self.stream.read_symbol(&source_alphabet_table)?;
@@ -176,6 +785,10 @@ impl<'a> SdbReader<'a> {
                 min_source_alphabet = source_alphabet_index;
             }
 
+            if min_target_alphabet > max_valid_alphabet {
+                return Err(ReadError::range_violation(format!("Conversion {} found for source alphabet {} after the last valid target alphabet", conversions.len(), source_alphabet_index)));
+            }
+
             let target_alphabet_table = RangedNaturalUsizeHuffmanTable::new(min_target_alphabet, max_valid_alphabet);
             let target_alphabet_index = self.stream.read_symbol(&target_alphabet_table)?;
             let target_alphabet = Alphabet {
@@ -185,7 +798,8 @@ impl<'a> SdbReader<'a> {
             min_target_alphabet = target_alphabet_index + 1;
 
             let pair_count = self.stream.read_symbol(&self.natural8_usize_table)?;
-            let mut pairs: Vec<(SymbolArrayIndex, SymbolArrayIndex)> = Vec::with_capacity(pair_count);
+            debug!(source = source_alphabet_index, target = target_alphabet_index, pair_count, "Reading conversion");
+            let mut pairs: Vec<(SymbolArrayIndex, SymbolArrayIndex)> = Vec::with_capacity(preallocation(pair_count));
             for _ in 0..pair_count {
                 let source = SymbolArrayIndex {
                     index: usize::try_from(self.stream.read_symbol(&symbol_array_table)?).unwrap()
@@ -201,29 +815,42 @@ impl<'a> SdbReader<'a> {
                 source: source_alphabet,
                 target: target_alphabet,
                 pairs
-            })
+            });
+            self.report_progress(Section::Conversions, conversions.len())?;
         }
 
+        info!("Conversions read - {} conversions found", conversions.len());
         Ok(conversions)
     }
 
-    fn read_correlations(&mut self, alphabet_count: usize, symbol_array_count: usize) -> Result<Vec<HashMap<Alphabet, SymbolArrayIndex>>, ReadError> {
+    #[instrument(level = "debug", skip_all)]
+    fn read_correlations(&mut self, alphabet_count: usize, symbol_array_count: usize) -> Result<Vec<BTreeMap<Alphabet, SymbolArrayIndex>>, ReadError> {
+        self.begin_section(Section::Correlations);
         let number_of_correlations = self.stream.read_symbol(&self.natural8_usize_table)?;
-        let mut correlations: Vec<HashMap<Alphabet, SymbolArrayIndex>> = Vec::with_capacity(number_of_correlations);
+        self.set_record_count(number_of_correlations);
+        debug!(number_of_correlations);
+        let mut correlations: Vec<BTreeMap<Alphabet, SymbolArrayIndex>> = Vec::with_capacity(preallocation(number_of_correlations));
         if number_of_correlations > 0 {
             // The serialization of correlations can be improved in several ways:
             // - There can be only one correlation with length 0. It could be serialised with a single bit: 0 (not present), 1 (present at the beginning)
             // - If correlations cannot mix alphabets from different languages, then we could reduce the number of possible keys once we know the first key, or even the language. For languages where only one alphabet is available, then the length and the key gets irrelevant
             // TODO: Improve codification for this table, it include lot of edge cases that should not be possible
             let length_table = self.stream.read_table(&self.integer8_table, &self.natural8_table, InputBitStream::read_symbol,InputBitStream::read_diff_i32)?;
-            for _ in 0..number_of_correlations {
-                let map_length = usize::try_from(self.stream.read_symbol(&length_table)?).unwrap();
-                if map_length >= alphabet_count {
-                    panic!("Map for correlation cannot be longer than the actual number of valid alphabets");
+            self.tables.correlation_lengths = Some(length_table.clone());
+            for correlation_index in 0..number_of_correlations {
+                let raw_map_length = self.stream.read_symbol(&length_table)?;
+                let mut map_length = self.length_from_i32(raw_map_length, "correlation")?;
+                if map_length > alphabet_count {
+                    self.violation(format!("Map for correlation {} has {} entries, but only {} alphabets are valid", correlation_index, map_length, alphabet_count))?;
+                    map_length = alphabet_count;
                 }
 
-                let mut map: HashMap<Alphabet, SymbolArrayIndex> = HashMap::with_capacity(map_length);
+                let mut map: BTreeMap<Alphabet, SymbolArrayIndex> = BTreeMap::new();
                 if map_length > 0 {
+                    if symbol_array_count == 0 {
+                        return Err(ReadError::range_violation("Correlations found, but there is no symbol array to refer to"));
+                    }
+
                     let key_table = RangedNaturalUsizeHuffmanTable::new(0, alphabet_count - map_length);
                     let value_table = RangedNaturalUsizeHuffmanTable::new(0, symbol_array_count - 1);
                     let mut raw_key = self.stream.read_symbol(&key_table)?;
@@ -250,89 +877,164 @@ impl<'a> SdbReader<'a> {
                     }
                 }
                 correlations.push(map);
+                self.report_progress(Section::Correlations, correlations.len())?;
             }
         }
 
+        info!("Correlations read - {} correlations found", correlations.len());
         Ok(correlations)
     }
 
+    #[instrument(level = "debug", skip_all)]
     fn read_correlation_arrays(&mut self, number_of_correlations: usize) -> Result<Vec<Vec<CorrelationIndex>>, ReadError> {
+        self.begin_section(Section::CorrelationArrays);
         let number_of_arrays = self.stream.read_symbol(&self.natural8_usize_table)?;
-        let mut arrays: Vec<Vec<CorrelationIndex>> = Vec::with_capacity(number_of_arrays);
+        self.set_record_count(number_of_arrays);
+        debug!(number_of_arrays);
+        let mut arrays: Vec<Vec<CorrelationIndex>> = Vec::with_capacity(preallocation(number_of_arrays));
         if number_of_arrays > 0 {
+            if number_of_correlations == 0 {
+                return Err(ReadError::range_violation("Correlation arrays found, but there is no correlation to refer to"));
+            }
+
             let correlation_table = RangedNaturalUsizeHuffmanTable::new(0, number_of_correlations - 1);
             // TODO: Improve codification for this table, it include lot of edge cases that should not be possible
             let length_table = self.stream.read_table(&self.integer8_table, &self.natural8_table, InputBitStream::read_symbol,InputBitStream::read_diff_i32)?;
+            self.tables.correlation_array_lengths = Some(length_table.clone());
 
             for _ in 0..number_of_arrays {
-                let array_length = usize::try_from(self.stream.read_symbol(&length_table)?).unwrap();
-                let mut array: Vec<CorrelationIndex> = Vec::with_capacity(array_length);
+                let raw_array_length = self.stream.read_symbol(&length_table)?;
+                let array_length = self.length_from_i32(raw_array_length, "correlation array")?;
+                let mut array: Vec<CorrelationIndex> = Vec::with_capacity(preallocation(array_length));
                 for _ in 0..array_length {
                     array.push(CorrelationIndex {
                         index: self.stream.read_symbol(&correlation_table)?
                     });
                 }
                 arrays.push(array);
+                self.report_progress(Section::CorrelationArrays, arrays.len())?;
             }
         }
 
+        info!("Correlation arrays read - {} correlation arrays found", arrays.len());
         Ok(arrays)
     }
 
-    fn read_acceptations(&mut self, min_valid_concept: usize, max_valid_concept: usize, correlation_array_count: usize) -> Result<Vec<Acceptation>, ReadError> {
+    /// Gives the acceptation to the handler, if any, or stores it otherwise.
+    fn store_acceptation(&mut self, decoded: &SdbReadResult, acceptations: &mut Vec<Acceptation>, acceptation: Acceptation) -> Result<(), ReadError> {
+        match &mut self.acceptation_handler {
+            Some(handler) => handler(decoded, &acceptation),
+            None => {
+                acceptations.push(acceptation);
+                Ok(())
+            }
+        }
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    fn read_acceptations(&mut self, min_valid_concept: usize, max_valid_concept: usize, decoded: &SdbReadResult) -> Result<Vec<Acceptation>, ReadError> {
+        let correlation_array_count = decoded.correlation_arrays.len();
+        self.begin_section(Section::Acceptations);
         let number_of_entries = self.stream.read_symbol(&self.natural8_usize_table)?;
+        self.set_record_count(number_of_entries);
+        debug!(number_of_entries);
         let mut result: Vec<Acceptation> = Vec::new();
+        let mut acceptation_count = 0;
         if number_of_entries > 0 {
+            if correlation_array_count == 0 {
+                return Err(ReadError::range_violation("Acceptations found, but there is no correlation array to refer to"));
+            }
+
+            if max_valid_concept < min_valid_concept {
+                return Err(ReadError::range_violation("Acceptations found, but there is no valid concept"));
+            }
+
             // TODO: Improve codification for this table, it include some edge cases that should not be possible, like negative values for lengths
             let correlation_array_set_length_table = self.stream.read_table(&self.integer8_table, &self.natural8_table, InputBitStream::read_symbol, InputBitStream::read_diff_i32)?;
+            self.tables.acceptation_set_lengths = Some(correlation_array_set_length_table.clone());
             let concept_table = RangedNaturalUsizeHuffmanTable::new(min_valid_concept, max_valid_concept);
-            for _ in 0..number_of_entries {
+            for entry_index in 0..number_of_entries {
                 let concept = self.stream.read_symbol(&concept_table)?;
-                let length = usize::try_from(self.stream.read_symbol(&correlation_array_set_length_table)?).unwrap();
+                let raw_length = self.stream.read_symbol(&correlation_array_set_length_table)?;
+                let mut length = self.length_from_i32(raw_length, "acceptation set")?;
+                if length == 0 {
+                    // There is nothing to decode for an empty set, so it is just skipped
+                    self.violation(format!("Concept {} has an empty set of acceptations", concept))?;
+                    self.report_progress(Section::Acceptations, entry_index + 1)?;
+                    continue;
+                }
+
+                if length > correlation_array_count {
+                    self.violation(format!("Concept {} has {} acceptations, but there are only {} correlation arrays", concept, length, correlation_array_count))?;
+                    length = correlation_array_count;
+                }
+
                 let symbol_table = RangedNaturalUsizeHuffmanTable::new(0, correlation_array_count - length);
                 let mut value = self.stream.read_symbol(&symbol_table)?;
-                result.push(Acceptation {
+                self.store_acceptation(decoded, &mut result, Acceptation {
                     concept,
                     correlation_array_index: CorrelationArrayIndex {
-                        index: usize::try_from(value).unwrap()
+                        index: value
                     }
-                });
+                })?;
+                acceptation_count += 1;
 
+                // Each table starts right after the previous value, so the symbols read are already
+                // the indexes, and not differences to be added to the previous one
                 for set_entry_index in 1..length {
                     let symbol_diff_table = RangedNaturalUsizeHuffmanTable::new(value + 1, correlation_array_count - length + set_entry_index);
-                    value += self.stream.read_symbol(&symbol_diff_table)? + 1;
-                    result.push(Acceptation {
+                    value = self.stream.read_symbol(&symbol_diff_table)?;
+                    self.store_acceptation(decoded, &mut result, Acceptation {
                         concept,
                         correlation_array_index: CorrelationArrayIndex {
                             index: value
                         }
-                    });
+                    })?;
+                    acceptation_count += 1;
                 }
+
+                self.report_progress(Section::Acceptations, entry_index + 1)?;
             }
         }
 
+        info!("Acceptations read - {} acceptations found", acceptation_count);
         Ok(result)
     }
 
-    fn read_definitions(&mut self, min_valid_concept: usize, max_valid_concept: usize) -> Result<HashMap<usize, Definition>, ReadError> {
+    #[instrument(level = "debug", skip_all)]
+    fn read_definitions(&mut self, min_valid_concept: usize, max_valid_concept: usize) -> Result<BTreeMap<usize, Definition>, ReadError> {
+        self.begin_section(Section::Definitions);
         let number_of_base_concepts = self.stream.read_symbol(&self.natural8_usize_table)?;
-        let mut definitions: HashMap<usize, Definition> = HashMap::new();
+        self.set_record_count(number_of_base_concepts);
+        debug!(number_of_base_concepts);
+        let mut definitions: BTreeMap<usize, Definition> = BTreeMap::new();
         if number_of_base_concepts > 0 {
+            if number_of_base_concepts > max_valid_concept + 1 - min_valid_concept {
+                return Err(ReadError::range_violation(format!("Found {} base concepts, but there are only {} valid concepts", number_of_base_concepts, max_valid_concept + 1 - min_valid_concept)));
+            }
+
             let concept_map_length_table = self.stream.read_table(&self.natural8_table, &self.natural8_table, InputBitStream::read_symbol, InputBitStream::read_diff_u32)?;
+            self.tables.definition_lengths = Some(concept_map_length_table.clone());
             let mut min_base_concept = min_valid_concept;
-            for max_base_concept in (max_valid_concept - number_of_base_concepts + 1)..=max_valid_concept {
+            let first_max_base_concept = max_valid_concept - number_of_base_concepts + 1;
+            for max_base_concept in first_max_base_concept..=max_valid_concept {
                 let table = RangedNaturalUsizeHuffmanTable::new(min_base_concept, max_base_concept);
                 let base = self.stream.read_symbol(&table)?;
                 min_base_concept = base + 1;
 
-                let map_length = usize::try_from(self.stream.read_symbol(&concept_map_length_table)?).unwrap();
+                let mut map_length = usize::try_from(self.stream.read_symbol(&concept_map_length_table)?).unwrap();
+                if map_length > max_valid_concept + 1 - min_valid_concept {
+                    self.violation(format!("Base concept {} is used in {} definitions, but there are only {} valid concepts", base, map_length, max_valid_concept + 1 - min_valid_concept))?;
+                    map_length = max_valid_concept + 1 - min_valid_concept;
+                }
+
                 if map_length > 0 {
                     let concept_table = RangedNaturalUsizeHuffmanTable::new(min_valid_concept, max_valid_concept - map_length + 1);
                     let mut concept = self.stream.read_symbol(&concept_table)?;
 
-                    fn read_complements(stream: &mut InputBitStream, min_valid_concept: usize, max_valid_concept: usize) -> Result<HashSet<usize>, ReadError> {
+                    fn read_complements<R: Read>(stream: &mut InputBitStream<R>, min_valid_concept: usize, max_valid_concept: usize) -> Result<BTreeSet<usize>, ReadError> {
                         let mut min_valid_complement = min_valid_concept;
-                        let mut complements: HashSet<usize> = HashSet::new();
+                        let mut complements: BTreeSet<usize> = BTreeSet::new();
                         while min_valid_complement < max_valid_concept && stream.read_boolean()? {
                             let complement_table = RangedNaturalUsizeHuffmanTable::new(min_valid_complement, max_valid_concept);
                             let complement = stream.read_symbol(&complement_table)?;
@@ -358,71 +1060,414 @@ impl<'a> SdbReader<'a> {
                         });
                     }
                 }
+
+                self.report_progress(Section::Definitions, max_base_concept - first_max_base_concept + 1)?;
             }
         }
 
+        info!("Definitions read - {} definitions found", definitions.len());
         Ok(definitions)
     }
 
-    pub fn read(mut self) -> Result<SdbReadResult, ReadError> {
+    /// Reads all sections in order, storing each of them in the given result as soon as it is
+    /// decoded.
+    fn read_sections(&mut self, result: &mut SdbReadResult) -> Result<(), ReadError> {
+        self.begin_section(Section::SymbolArrays);
         let symbol_array_count = self.stream.read_symbol(&self.natural8_usize_table)?;
+        self.set_record_count(symbol_array_count);
+        debug!(symbol_array_count);
         let chars_table = self.stream.read_table(&self.natural8_table, &self.natural4_table, InputBitStream::read_character, InputBitStream::read_diff_character)?;
         let symbol_arrays_length_table = self.stream.read_table(&self.natural8_table, &self.natural3_table, InputBitStream::read_symbol, InputBitStream::read_diff_u32)?;
-        let symbol_arrays = self.read_symbol_arrays(symbol_array_count, symbol_arrays_length_table, chars_table)?;
-        let languages = self.read_languages()?;
-
-        if symbol_array_count == 0 {
-            todo!("Implementation missing when symbol array count is 0");
-        }
-
-        let mut alphabet_count: usize = 0;
-        for language in &languages {
-            alphabet_count += language.number_of_alphabets;
-        }
-
-        let conversions = self.read_conversions(alphabet_count, symbol_array_count)?;
-        let max_concept = self.stream.read_symbol(&self.natural8_usize_table)?;
-        let correlations = self.read_correlations(alphabet_count, symbol_array_count)?;
-        let correlation_arrays = self.read_correlation_arrays(correlations.len())?;
-        let acceptations = self.read_acceptations(1, max_concept, correlation_arrays.len())?;
-        let definitions = self.read_definitions(1, max_concept)?;
-
-        Ok(SdbReadResult {
-            symbol_arrays,
-            languages,
-            conversions,
-            max_concept,
-            correlations,
-            correlation_arrays,
-            acceptations,
-            definitions
-        })
+        self.tables.chars = Some(chars_table.clone());
+        self.tables.symbol_array_lengths = Some(symbol_arrays_length_table.clone());
+        result.symbol_arrays = self.read_symbol_arrays(symbol_array_count, symbol_arrays_length_table, chars_table)?;
+        if self.is_last_section(Section::SymbolArrays) {
+            return Ok(());
+        }
+
+        result.languages = self.read_languages()?;
+        if self.is_last_section(Section::Languages) {
+            return Ok(());
+        }
+
+        let alphabet_count = result.alphabet_count();
+        debug!(alphabet_count);
+        result.conversions = self.read_conversions(alphabet_count, symbol_array_count)?;
+        result.max_concept = self.stream.read_symbol(&self.natural8_usize_table)?;
+        info!("Found {} concepts", result.max_concept);
+        if self.is_last_section(Section::Conversions) {
+            return Ok(());
+        }
+
+        result.correlations = self.read_correlations(alphabet_count, symbol_array_count)?;
+        if self.is_last_section(Section::Correlations) {
+            return Ok(());
+        }
+
+        result.correlation_arrays = self.read_correlation_arrays(result.correlations.len())?;
+        if self.is_last_section(Section::CorrelationArrays) {
+            return Ok(());
+        }
+
+        result.acceptations = self.read_acceptations(1, result.max_concept, result)?;
+        if self.is_last_section(Section::Acceptations) {
+            return Ok(());
+        }
+
+        result.definitions = self.read_definitions(1, result.max_concept)?;
+        if self.last_section.is_none() {
+            self.read_extra_sections(result)?;
+        }
+
+        Ok(())
+    }
+
+    /// Calls the handlers of the extra sections in order, with the standard sections decoded.
+    fn read_extra_sections(&mut self, result: &SdbReadResult) -> Result<(), ReadError> {
+        // Extra sections are not accounted within the bits of the definitions
+        self.end_section();
+        self.position = None;
+        let mut handlers = core::mem::take(&mut self.extra_section_handlers);
+        for (position, handler) in handlers.iter_mut().enumerate() {
+            self.extra_section = Some(position);
+            handler(&mut self.stream, result)?;
+            info!("Extra section {} read", position);
+        }
+
+        self.extra_section = None;
+        Ok(())
+    }
+
+    /// Decodes as many sections as possible. If an error is found, it is returned together with
+    /// all sections decoded before it, so that most of a slightly corrupted file can still be
+    /// used. In lenient mode, the error is also added to the warnings.
+    #[instrument(level = "debug", skip_all)]
+    pub fn read_partial(mut self) -> PartialReadResult {
+        let mut result = SdbReadResult::default();
+        let mut error = None;
+        let mut failed_section = None;
+        if let Err(err) = self.read_sections(&mut result) {
+            let err = self.locate_error(err);
+            if self.mode == DecodingMode::Lenient && err.kind != ReadErrorKind::Cancelled {
+                let message = format!("Decoding stopped: {}", err.message);
+                warn!("{}", message);
+                self.warnings.push(message);
+            }
+
+            failed_section = self.position.as_ref().map(|position| position.section);
+            error = Some(err);
+        }
+
+        self.end_section();
+        result.section_bits = self.section_bits;
+        result.tables = self.tables;
+        result.warnings = self.warnings;
+        PartialReadResult {
+            result,
+            error,
+            failed_section
+        }
+    }
+
+    /// Decodes all sections. In strict mode, any error found is returned. In lenient mode, errors
+    /// only stop the decoding, and sections decoded before them are returned, unless the decoding
+    /// was cancelled.
+    pub fn read(self) -> Result<SdbReadResult, ReadError> {
+        let mode = self.mode;
+        let partial = self.read_partial();
+        match partial.error {
+            Some(err) if mode == DecodingMode::Strict || err.kind == ReadErrorKind::Cancelled => Err(err),
+            _ => Ok(partial.result)
+        }
     }
 }
 
 impl SdbReadResult {
-    pub fn get_complete_correlation(&self, correlation_array_index: CorrelationArrayIndex) -> HashMap<Alphabet, String> {
-        let mut result: HashMap<Alphabet, String> = HashMap::new();
-        let array: &Vec<CorrelationIndex> = &self.correlation_arrays[correlation_array_index.index];
-        let array_length = array.len();
-        if array_length == 0 {
-            return result;
+    /// Total number of alphabets, summing up the alphabets of all languages.
+    pub fn alphabet_count(&self) -> usize {
+        self.languages.iter().map(|language| language.number_of_alphabets).sum()
+    }
+
+    /// Iterates over all languages in the same order they are found, together with the absolute
+    /// indexes of their alphabets.
+    pub fn languages(&self) -> impl Iterator<Item = LanguageInfo> + '_ {
+        let mut first_alphabet = 0;
+        self.languages.iter().map(move |language| {
+            let alphabets = first_alphabet..first_alphabet + language.number_of_alphabets;
+            first_alphabet = alphabets.end;
+            LanguageInfo {
+                code: language.code,
+                alphabet_count: language.number_of_alphabets,
+                alphabets
+            }
+        })
+    }
+
+    /// Returns the alphabet with the given index, if there is such alphabet.
+    pub fn alphabet(&self, index: usize) -> Option<Alphabet> {
+        if index < self.alphabet_count() {
+            Some(Alphabet {
+                index
+            })
+        }
+        else {
+            None
+        }
+    }
+
+    /// Returns the symbol array at the given position, if it exists within this database.
+    pub fn symbol_array_index(&self, index: usize) -> Option<SymbolArrayIndex> {
+        if index < self.symbol_arrays.len() {
+            Some(SymbolArrayIndex {
+                index
+            })
+        }
+        else {
+            None
+        }
+    }
+
+    /// Returns the correlation at the given position, if it exists within this database.
+    pub fn correlation_index(&self, index: usize) -> Option<CorrelationIndex> {
+        if index < self.correlations.len() {
+            Some(CorrelationIndex {
+                index
+            })
+        }
+        else {
+            None
+        }
+    }
+
+    /// Returns the correlation array at the given position, if it exists within this database.
+    pub fn correlation_array_index(&self, index: usize) -> Option<CorrelationArrayIndex> {
+        if index < self.correlation_arrays.len() {
+            Some(CorrelationArrayIndex {
+                index
+            })
+        }
+        else {
+            None
+        }
+    }
+
+    /// Returns the index within the languages list of the language the given alphabet belongs to.
+    pub fn language_index_of(&self, alphabet: Alphabet) -> Option<usize> {
+        let mut first_alphabet = 0;
+        for (language_index, language) in self.languages.iter().enumerate() {
+            first_alphabet += language.number_of_alphabets;
+            if alphabet.index < first_alphabet {
+                return Some(language_index);
+            }
+        }
+
+        None
+    }
+
+    /// Returns the code of the language the given alphabet belongs to, if any.
+    pub fn language_code_of(&self, alphabet: Alphabet) -> Option<LanguageCode> {
+        self.language_index_of(alphabet).map(|language_index| self.languages[language_index].code)
+    }
+
+    /// Returns a short label for the given alphabet, made of the code of its language and its
+    /// position within that language, like "ja:1". Alphabets not belonging to any language are
+    /// labeled with a placeholder like "alphabet#7".
+    pub fn alphabet_label(&self, alphabet: Alphabet) -> String {
+        match self.languages().find(|language| language.alphabets.contains(&alphabet.index)) {
+            Some(language) => format!("{}:{}", language.code, alphabet.index - language.alphabets.start),
+            None => format!("alphabet#{}", alphabet.index)
+        }
+    }
+
+    /// Returns the text of the given symbol array, borrowed from this result.
+    pub fn symbol_array(&self, index: SymbolArrayIndex) -> &str {
+        &self.symbol_arrays[index.index]
+    }
+
+    /// Returns the alphabets with text within the given correlation array, sorted. These are the
+    /// alphabets of its first correlation, as all correlations within an array share them.
+    pub fn correlation_array_alphabets(&self, correlation_array_index: CorrelationArrayIndex) -> impl Iterator<Item = Alphabet> + '_ {
+        let alphabets: BTreeSet<Alphabet> = match self.correlation_arrays[correlation_array_index.index].first() {
+            Some(first) => self.correlations[first.index].keys().copied().collect(),
+            None => BTreeSet::new()
+        };
+
+        alphabets.into_iter()
+    }
+
+    /// Iterates over the texts that, once concatenated, make the text of the given correlation
+    /// array in the given alphabet. Texts are borrowed from the symbol arrays of this result, so
+    /// they can be written one after the other without building the complete text.
+    pub fn correlation_segments(&self, correlation_array_index: CorrelationArrayIndex, alphabet: Alphabet) -> impl Iterator<Item = &str> + '_ {
+        self.correlation_arrays[correlation_array_index.index].iter()
+            .filter_map(move |correlation_index| self.correlations[correlation_index.index].get(&alphabet))
+            .map(|symbol_array| self.symbol_array(*symbol_array))
+    }
+
+    /// Concatenates, for each alphabet, the texts of all correlations within the given correlation
+    /// array. Entries are sorted by alphabet.
+    pub fn get_complete_correlation(&self, correlation_array_index: CorrelationArrayIndex) -> BTreeMap<Alphabet, String> {
+        self.correlation_array_alphabets(correlation_array_index)
+            .map(|alphabet| (alphabet, self.correlation_segments(correlation_array_index, alphabet).collect()))
+            .collect()
+    }
+
+    /// Returns the complete correlation of each acceptation, in the same order as the acceptations.
+    /// With the parallel feature, acceptations are split among all available cores.
+    pub fn complete_correlations(&self) -> Vec<BTreeMap<Alphabet, String>> {
+        #[cfg(feature = "parallel")]
+        let acceptations = self.acceptations.par_iter();
+
+        #[cfg(not(feature = "parallel"))]
+        let acceptations = self.acceptations.iter();
+
+        acceptations.map(|acc| self.get_complete_correlation(acc.correlation_array_index)).collect()
+    }
+
+    /// Iterates over all concepts with any acceptation, sorted by concept, together with their
+    /// acceptations and texts. Texts are only resolved when each concept is reached.
+    pub fn iter_concepts(&self) -> impl Iterator<Item = ConceptEntry<'_>> {
+        let mut groups: BTreeMap<usize, Vec<&Acceptation>> = BTreeMap::new();
+        for acc in self.acceptations.iter() {
+            groups.entry(acc.concept).or_default().push(acc);
         }
 
-        let correlation: &HashMap<Alphabet, SymbolArrayIndex> = &self.correlations[array[0].index];
-        for (key, value) in correlation {
-            result.insert(*key, self.symbol_arrays[value.index].clone());
+        groups.into_iter().map(|(concept, acceptations)| ConceptEntry {
+            concept,
+            acceptations: acceptations.into_iter().map(|acc| (acc, self.get_complete_correlation(acc.correlation_array_index))).collect()
+        })
+    }
+
+    /// Returns the texts of all symbol arrays used for the given alphabet by any correlation,
+    /// without duplicates and sorted by symbol array index.
+    pub fn texts_in_alphabet(&self, alphabet: Alphabet) -> impl Iterator<Item = &str> {
+        let indexes: BTreeSet<usize> = self.correlations.iter()
+            .filter_map(|correlation| correlation.get(&alphabet))
+            .map(|symbol_array| symbol_array.index)
+            .collect();
+
+        indexes.into_iter().map(|index| &self.symbol_arrays[index])
+    }
+
+    /// Returns all concepts having at least one acceptation whose text in any alphabet matches the
+    /// given one, sorted and without duplicates.
+    pub fn find_concepts(&self, text: &str) -> Vec<usize> {
+        let mut concepts: Vec<usize> = Vec::new();
+        for acc in self.acceptations.iter() {
+            if self.get_complete_correlation(acc.correlation_array_index).values().any(|value| value == text) {
+                concepts.push(acc.concept);
+            }
         }
 
-        if array_length > 1 {
-            for array_index in 1..array_length {
-                for (key, value) in self.correlations[array[array_index].index].iter() {
-                    let text = &self.symbol_arrays[value.index];
-                    result.get_mut(key).unwrap().push_str(text);
+        concepts.sort();
+        concepts.dedup();
+        concepts
+    }
+
+    /// Returns all concepts defined with the given one as their base concept, sorted.
+    pub fn concepts_with_base(&self, base_concept: usize) -> Vec<usize> {
+        self.definitions.iter()
+            .filter(|(_, definition)| definition.base_concept == base_concept)
+            .map(|(concept, _)| *concept)
+            .collect()
+    }
+
+    /// Returns all concepts defined with the given one among their complements, sorted.
+    pub fn concepts_with_complement(&self, complement: usize) -> Vec<usize> {
+        self.definitions.iter()
+            .filter(|(_, definition)| definition.complements.contains(&complement))
+            .map(|(concept, _)| *concept)
+            .collect()
+    }
+
+    /// Returns the texts in all alphabets of all acceptations of the given concept, without
+    /// duplicates and in the order acceptations are found.
+    pub fn concept_texts(&self, concept: usize) -> Vec<String> {
+        let mut texts: Vec<String> = Vec::new();
+        for acc in self.acceptations.iter().filter(|acc| acc.concept == concept) {
+            for text in self.get_complete_correlation(acc.correlation_array_index).into_values() {
+                if !texts.contains(&text) {
+                    texts.push(text);
                 }
             }
         }
 
-        result
+        texts
+    }
+
+    /// Returns a text for the given concept, joining with slashes the texts in all alphabets of
+    /// the first acceptation found for it. None is returned if no acceptation has any text.
+    pub fn concept_text(&self, concept: usize) -> Option<String> {
+        for acc in self.acceptations.iter() {
+            if acc.concept == concept {
+                let text = self.get_complete_correlation(acc.correlation_array_index).into_values().reduce(|a, b| {
+                    let mut c = String::new();
+                    c.push_str(&a);
+                    c.push('/');
+                    c.push_str(&b);
+                    c
+                });
+
+                if text.is_some() {
+                    return text;
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Returns the text of the given concept following the given preferences in order. For each
+    /// preference, acceptations are checked in the order they are found, and the first one with
+    /// text for it is taken. If no preference matches, the result is the same as concept_text.
+    pub fn concept_text_with(&self, concept: usize, preferences: &[DisplayPreference]) -> Option<String> {
+        let acceptations: Vec<&Acceptation> = self.acceptations.iter().filter(|acc| acc.concept == concept).collect();
+        for preference in preferences {
+            for acc in acceptations.iter() {
+                let texts = self.get_complete_correlation(acc.correlation_array_index);
+                let text = match *preference {
+                    DisplayPreference::Language(code) => {
+                        let in_language: Vec<String> = texts.into_iter()
+                            .filter(|(alphabet, _)| self.language_code_of(*alphabet) == Some(code))
+                            .map(|(_, text)| text)
+                            .collect();
+
+                        if in_language.is_empty() {
+                            None
+                        }
+                        else {
+                            Some(in_language.join("/"))
+                        }
+                    },
+                    DisplayPreference::Alphabet(code, position) => self.languages()
+                        .find(|language| language.code == code && position < language.alphabet_count)
+                        .and_then(|language| texts.get(&Alphabet { index: language.alphabets.start + position }).cloned())
+                };
+
+                if text.is_some() {
+                    return text;
+                }
+            }
+        }
+
+        self.concept_text(concept)
+    }
+
+    /// Same as concept_text, but returns a placeholder like "concept#12" for concepts without any
+    /// text, logging a warning about it.
+    pub fn concept_to_string(&self, concept: usize) -> String {
+        self.concept_to_string_with(concept, &[])
+    }
+
+    /// Same as concept_text_with, but returns a placeholder like "concept#12" for concepts
+    /// without any text, logging a warning about it.
+    pub fn concept_to_string_with(&self, concept: usize, preferences: &[DisplayPreference]) -> String {
+        match self.concept_text_with(concept, preferences) {
+            Some(text) => text,
+            None => {
+                warn!("No suitable string found for concept {}", concept);
+                format!("concept#{}", concept)
+            }
+        }
     }
 }
@@ -0,0 +1,351 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::Range;
+use crate::sdb::{Alphabet, CorrelationArrayIndex, SdbReadResult};
+
+/// Acceptation containing the searched pattern in one of its texts.
+pub struct SearchMatch {
+    /// Index of the acceptation within the acceptations section.
+    pub acceptation: usize,
+    pub alphabet: Alphabet,
+    pub text: String,
+
+    /// Number of edits needed to turn the pattern into the text, ignoring case. This is always 0
+    /// when searching for substrings.
+    pub distance: usize
+}
+
+/// Levenshtein distance between the given texts, counting characters, or None if it is greater
+/// than the given maximum.
+fn edit_distance(a: &[char], b: &[char], max_distance: usize) -> Option<usize> {
+    if a.len().abs_diff(b.len()) > max_distance {
+        return None;
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, a_char) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+
+        if current.iter().all(|distance| *distance > max_distance) {
+            return None;
+        }
+
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    Some(previous[b.len()]).filter(|distance| *distance <= max_distance)
+}
+
+/// Part of an analyzed text, matching the whole text of some acceptations, or none of them.
+pub struct TextSegment {
+    /// Text of the segment, as found in the analyzed text.
+    pub text: String,
+
+    /// Indexes of the acceptations with this text, sorted. This is empty for parts of the text
+    /// not matching any acceptation.
+    pub acceptations: Vec<usize>
+}
+
+/// How a pattern is compared with the texts.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SearchMode {
+    /// Texts containing the pattern.
+    Substring,
+
+    /// Texts starting with the pattern.
+    Prefix,
+
+    /// Texts within the given number of edits of the pattern.
+    Fuzzy(usize)
+}
+
+#[derive(Default)]
+struct TrieNode {
+    children: BTreeMap<char, usize>,
+
+    /// Acceptation and text position within it for all texts ending at this node.
+    entries: Vec<(usize, usize)>
+}
+
+/// Tree where each node represents a prefix, and its children the prefixes one character longer.
+struct PrefixTrie {
+    nodes: Vec<TrieNode>
+}
+
+impl PrefixTrie {
+    fn new(lowercase_texts: &[Vec<String>]) -> Self {
+        let mut nodes = vec![TrieNode::default()];
+        for (acceptation, texts) in lowercase_texts.iter().enumerate() {
+            for (text_index, text) in texts.iter().enumerate() {
+                let mut node = 0;
+                for ch in text.chars() {
+                    node = match nodes[node].children.get(&ch) {
+                        Some(child) => *child,
+                        None => {
+                            let child = nodes.len();
+                            nodes.push(TrieNode::default());
+                            nodes[node].children.insert(ch, child);
+                            child
+                        }
+                    };
+                }
+
+                nodes[node].entries.push((acceptation, text_index));
+            }
+        }
+
+        Self {
+            nodes
+        }
+    }
+
+    /// Returns all entries for texts starting with the given prefix, unsorted.
+    fn entries_with_prefix(&self, prefix: &str) -> Vec<(usize, usize)> {
+        let mut node = 0;
+        for ch in prefix.chars() {
+            match self.nodes[node].children.get(&ch) {
+                Some(child) => node = *child,
+                None => return Vec::new()
+            }
+        }
+
+        let mut entries = Vec::new();
+        let mut pending = vec![node];
+        while let Some(node) = pending.pop() {
+            entries.extend_from_slice(&self.nodes[node].entries);
+            pending.extend(self.nodes[node].children.values());
+        }
+
+        entries
+    }
+}
+
+/// Inverted index from each character to the acceptations containing it, allowing to search
+/// for substrings in any script without splitting texts into words.
+///
+/// The index is built once, so that it can be reused for any number of searches. Prefix searches
+/// can optionally be sped up by building a trie too, at the cost of some memory.
+pub struct SearchIndex {
+    texts: Vec<Vec<(Alphabet, String)>>,
+    lowercase_texts: Vec<Vec<String>>,
+    postings: HashMap<char, Vec<usize>>,
+    trie: Option<PrefixTrie>
+}
+
+impl SearchIndex {
+    pub fn new(result: &SdbReadResult) -> Self {
+        let mut correlations: HashMap<CorrelationArrayIndex, Vec<(Alphabet, String)>> = HashMap::new();
+        let mut texts = Vec::with_capacity(result.acceptations.len());
+        let mut lowercase_texts = Vec::with_capacity(result.acceptations.len());
+        let mut postings: HashMap<char, Vec<usize>> = HashMap::new();
+        for (index, acc) in result.acceptations.iter().enumerate() {
+            let acceptation_texts = correlations.entry(acc.correlation_array_index)
+                .or_insert_with(|| result.get_complete_correlation(acc.correlation_array_index).into_iter().collect())
+                .clone();
+
+            let acceptation_lowercase_texts: Vec<String> = acceptation_texts.iter().map(|(_, text)| text.to_lowercase()).collect();
+            for ch in acceptation_lowercase_texts.iter().flat_map(|text| text.chars()) {
+                let posting = postings.entry(ch).or_default();
+                if posting.last() != Some(&index) {
+                    posting.push(index);
+                }
+            }
+
+            texts.push(acceptation_texts);
+            lowercase_texts.push(acceptation_lowercase_texts);
+        }
+
+        Self {
+            texts,
+            lowercase_texts,
+            postings,
+            trie: None
+        }
+    }
+
+    /// Builds a trie over all texts, to be used by prefix searches.
+    pub fn with_prefix_trie(mut self) -> Self {
+        self.trie = Some(PrefixTrie::new(&self.lowercase_texts));
+        self
+    }
+
+    fn search_match(&self, acceptation: usize, text_index: usize, distance: usize) -> SearchMatch {
+        let (alphabet, text) = &self.texts[acceptation][text_index];
+        SearchMatch {
+            acceptation,
+            alphabet: *alphabet,
+            text: text.clone(),
+            distance
+        }
+    }
+
+    /// Returns all acceptations with a text containing the given pattern, ignoring case.
+    /// Matches are sorted by acceptation, and an empty pattern matches nothing.
+    pub fn search(&self, pattern: &str) -> Vec<SearchMatch> {
+        let pattern = pattern.to_lowercase();
+        let mut chars: Vec<char> = pattern.chars().collect();
+        chars.sort_unstable();
+        chars.dedup();
+
+        let mut posting_lists = Vec::with_capacity(chars.len());
+        for ch in chars {
+            match self.postings.get(&ch) {
+                Some(posting) => posting_lists.push(posting),
+                None => return Vec::new()
+            }
+        }
+
+        // Starting with the shortest list reduces the number of candidates to check
+        posting_lists.sort_by_key(|posting| posting.len());
+        let (first, others) = match posting_lists.split_first() {
+            Some(lists) => lists,
+            None => return Vec::new()
+        };
+
+        let mut matches = Vec::new();
+        for &acceptation in first.iter() {
+            if others.iter().all(|posting| posting.binary_search(&acceptation).is_ok()) {
+                for (text_index, text) in self.lowercase_texts[acceptation].iter().enumerate() {
+                    if text.contains(&pattern) {
+                        matches.push(self.search_match(acceptation, text_index, 0));
+                    }
+                }
+            }
+        }
+
+        matches
+    }
+
+    /// Returns all acceptations with a text within the given number of edits of the query,
+    /// ignoring case. Matches are sorted by distance, and then by acceptation.
+    pub fn fuzzy_search(&self, query: &str, max_distance: usize) -> Vec<SearchMatch> {
+        let query: Vec<char> = query.to_lowercase().chars().collect();
+        let mut matches = Vec::new();
+        for (acceptation, texts) in self.lowercase_texts.iter().enumerate() {
+            for (text_index, text) in texts.iter().enumerate() {
+                let text_chars: Vec<char> = text.chars().collect();
+                if let Some(distance) = edit_distance(&query, &text_chars, max_distance) {
+                    matches.push(self.search_match(acceptation, text_index, distance));
+                }
+            }
+        }
+
+        matches.sort_by_key(|search_match| (search_match.distance, search_match.acceptation));
+        matches
+    }
+
+    /// Returns all acceptations with a text starting with the given prefix, ignoring case.
+    /// Matches are sorted by acceptation. The trie is used if it was built, otherwise all texts
+    /// are checked.
+    pub fn prefix_search(&self, prefix: &str) -> Vec<SearchMatch> {
+        let prefix = prefix.to_lowercase();
+        let mut entries = match &self.trie {
+            Some(trie) => trie.entries_with_prefix(&prefix),
+            None => self.lowercase_texts.iter().enumerate()
+                .flat_map(|(acceptation, texts)| texts.iter().enumerate().map(move |(text_index, text)| (acceptation, text_index, text)))
+                .filter(|(_, _, text)| text.starts_with(&prefix))
+                .map(|(acceptation, text_index, _)| (acceptation, text_index))
+                .collect()
+        };
+
+        entries.sort_unstable();
+        entries.into_iter().map(|(acceptation, text_index)| self.search_match(acceptation, text_index, 0)).collect()
+    }
+
+    /// Searches all the given patterns, merging their matches. When the same text matches
+    /// several patterns, only the closest match is kept. Matches are sorted by distance, and then
+    /// by acceptation. If an alphabet is given, texts in other alphabets are ignored.
+    pub fn find(&self, patterns: &[String], mode: SearchMode, alphabet: Option<Alphabet>) -> Vec<SearchMatch> {
+        let mut matches: Vec<SearchMatch> = patterns.iter().flat_map(|pattern| match mode {
+            SearchMode::Substring => self.search(pattern),
+            SearchMode::Prefix => self.prefix_search(pattern),
+            SearchMode::Fuzzy(max_distance) => self.fuzzy_search(pattern, max_distance)
+        }).collect();
+
+        matches.sort_by_key(|search_match| (search_match.distance, search_match.acceptation, search_match.alphabet));
+        let mut found = HashSet::new();
+        matches.retain(|search_match| alphabet.is_none_or(|alphabet| alphabet == search_match.alphabet) && found.insert((search_match.acceptation, search_match.alphabet)));
+        matches
+    }
+
+    /// Splits the given text into segments matching whole texts of acceptations, ignoring case.
+    /// At each position, the longest text found is taken, and consecutive characters not starting
+    /// any text are joined into segments without acceptations. Whitespace only separates
+    /// segments. If a range of alphabets is given, texts in other alphabets are ignored.
+    pub fn segment(&self, text: &str, alphabets: Option<Range<usize>>) -> Vec<TextSegment> {
+        let mut known: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (acceptation, texts) in self.lowercase_texts.iter().enumerate() {
+            for (text_index, lowercase_text) in texts.iter().enumerate() {
+                let alphabet = self.texts[acceptation][text_index].0;
+                if !lowercase_text.is_empty() && alphabets.as_ref().is_none_or(|alphabets| alphabets.contains(&alphabet.index())) {
+                    let acceptations = known.entry(lowercase_text).or_default();
+                    if acceptations.last() != Some(&acceptation) {
+                        acceptations.push(acceptation);
+                    }
+                }
+            }
+        }
+
+        let max_length = known.keys().map(|text| text.chars().count()).max().unwrap_or(0);
+        let chars: Vec<char> = text.chars().collect();
+        let lowercase_chars: Vec<char> = chars.iter().map(|ch| {
+            let mut lowercase = ch.to_lowercase();
+            match (lowercase.next(), lowercase.next()) {
+                (Some(lowercase_ch), None) => lowercase_ch,
+                _ => *ch
+            }
+        }).collect();
+
+        let mut segments = Vec::new();
+        let mut unknown = String::new();
+        let mut position = 0;
+        while position < chars.len() {
+            let longest = if chars[position].is_whitespace() {
+                None
+            }
+            else {
+                (1..=max_length.min(chars.len() - position)).rev().find_map(|length| {
+                    let candidate: String = lowercase_chars[position..position + length].iter().collect();
+                    known.get(candidate.as_str()).map(|acceptations| (length, acceptations))
+                })
+            };
+
+            if (longest.is_some() || chars[position].is_whitespace()) && !unknown.is_empty() {
+                segments.push(TextSegment {
+                    text: std::mem::take(&mut unknown),
+                    acceptations: Vec::new()
+                });
+            }
+
+            match longest {
+                Some((length, acceptations)) => {
+                    segments.push(TextSegment {
+                        text: chars[position..position + length].iter().collect(),
+                        acceptations: acceptations.clone()
+                    });
+                    position += length;
+                },
+                None => {
+                    if !chars[position].is_whitespace() {
+                        unknown.push(chars[position]);
+                    }
+
+                    position += 1;
+                }
+            }
+        }
+
+        if !unknown.is_empty() {
+            segments.push(TextSegment {
+                text: unknown,
+                acceptations: Vec::new()
+            });
+        }
+
+        segments
+    }
+}
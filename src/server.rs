@@ -0,0 +1,232 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use langbook_sdb_dump::analysis;
+use langbook_sdb_dump::json::{object, JsonValue};
+use langbook_sdb_dump::sdb::SdbReadResult;
+use langbook_sdb_dump::search::SearchIndex;
+use tracing::{debug, info, warn};
+use crate::i18n::{tr, Message};
+
+/// Maximum number of texts returned when completing a prefix.
+const MAX_COMPLETIONS: usize = 20;
+
+/// Number of connections answered at once. Further connections wait to be accepted.
+const WORKER_COUNT: usize = 8;
+
+/// Maximum number of bytes read for the request line and the headers together.
+const MAX_REQUEST_LENGTH: u64 = 8192;
+
+/// Time given to clients to send each part of their request and to receive the response.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+struct Database {
+    result: SdbReadResult,
+    index: SearchIndex
+}
+
+struct Response {
+    status: &'static str,
+    body: JsonValue
+}
+
+impl Response {
+    fn ok(body: JsonValue) -> Self {
+        Self {
+            status: "200 OK",
+            body
+        }
+    }
+
+    fn error(status: &'static str, message: &str) -> Self {
+        Self {
+            status,
+            body: object([("error", message.into())])
+        }
+    }
+}
+
+/// Decodes a percent-encoded query string component, where '+' also stands for a space.
+fn decode_component(text: &str) -> Option<String> {
+    let mut bytes: Vec<u8> = Vec::with_capacity(text.len());
+    let mut iter = text.bytes();
+    while let Some(byte) = iter.next() {
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let high = char::from(iter.next()?).to_digit(16)?;
+                let low = char::from(iter.next()?).to_digit(16)?;
+                bytes.push(u8::try_from(high * 16 + low).unwrap());
+            },
+            byte => bytes.push(byte)
+        }
+    }
+
+    String::from_utf8(bytes).ok()
+}
+
+fn query_parameter(query: &str, name: &str) -> Option<String> {
+    query.split('&').find_map(|pair| match pair.split_once('=') {
+        Some((key, value)) if key == name => decode_component(value),
+        _ => None
+    })
+}
+
+fn concept_json(result: &SdbReadResult, concept: usize) -> JsonValue {
+    let definition = result.definitions.get(&concept).map(|definition| {
+        let complements: Vec<usize> = definition.complements.iter().copied().collect();
+        object([
+            ("base_concept", definition.base_concept.into()),
+            ("complements", complements.into())
+        ])
+    });
+
+    object([
+        ("id", concept.into()),
+        ("texts", result.concept_texts(concept).into()),
+        ("definition", definition.unwrap_or(JsonValue::Null))
+    ])
+}
+
+fn lookup(result: &SdbReadResult, query: &str) -> Response {
+    match query_parameter(query, "text") {
+        None => Response::error("400 Bad Request", "Missing text parameter"),
+        Some(text) => {
+            let concepts: Vec<JsonValue> = result.find_concepts(&text).into_iter().map(|concept| concept_json(result, concept)).collect();
+            Response::ok(object([
+                ("text", text.into()),
+                ("concepts", JsonValue::Array(concepts))
+            ]))
+        }
+    }
+}
+
+fn complete(database: &Database, query: &str) -> Response {
+    match query_parameter(query, "prefix") {
+        None => Response::error("400 Bad Request", "Missing prefix parameter"),
+        Some(prefix) => {
+            let matches: Vec<JsonValue> = database.index.prefix_search(&prefix).into_iter().take(MAX_COMPLETIONS).map(|search_match| object([
+                ("text", search_match.text.into()),
+                ("acceptation", search_match.acceptation.into()),
+                ("concept", database.result.acceptations[search_match.acceptation].concept.into())
+            ])).collect();
+
+            Response::ok(object([
+                ("prefix", prefix.into()),
+                ("matches", JsonValue::Array(matches))
+            ]))
+        }
+    }
+}
+
+fn stats(result: &SdbReadResult) -> Response {
+    Response::ok(object(analysis::entry_counts(result).map(|(key, count)| (key, count.into()))))
+}
+
+fn route(database: &Database, method: &str, target: &str) -> Response {
+    let result = &database.result;
+    if method != "GET" {
+        return Response::error("405 Method Not Allowed", "Only GET requests are supported");
+    }
+
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    if path == "/lookup" {
+        lookup(result, query)
+    }
+    else if path == "/complete" {
+        complete(database, query)
+    }
+    else if path == "/stats" {
+        stats(result)
+    }
+    else if let Some(id) = path.strip_prefix("/concept/") {
+        match id.parse::<usize>() {
+            Ok(concept) if concept >= 1 && concept <= result.max_concept => Response::ok(concept_json(result, concept)),
+            _ => Response::error("404 Not Found", "Unknown concept")
+        }
+    }
+    else {
+        Response::error("404 Not Found", "Unknown path")
+    }
+}
+
+fn handle_connection(database: &Database, stream: TcpStream) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let mut reader = BufReader::new(&stream).take(MAX_REQUEST_LENGTH);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    // Headers are not needed, but they must be consumed before answering
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && header.trim_end() != "" {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let response = match (parts.next(), parts.next()) {
+        _ if reader.limit() == 0 => Response::error("431 Request Header Fields Too Large", "Request too large"),
+        (Some(method), Some(target)) => {
+            debug!("{} {}", method, target);
+            route(database, method, target)
+        },
+        _ => Response::error("400 Bad Request", "Invalid request line")
+    };
+
+    let body = response.body.to_string();
+    let mut stream = &stream;
+    write!(stream, "HTTP/1.1 {}\r\nContent-Type: application/json; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", response.status, body.len(), body)?;
+    stream.flush()
+}
+
+/// Answers the connections received through the given channel until it is closed.
+fn run_worker(database: &Database, connections: &Mutex<Receiver<TcpStream>>) {
+    loop {
+        // The lock is released as soon as a connection is received
+        let stream = match connections.lock().expect("Workers should not panic while waiting").recv() {
+            Ok(stream) => stream,
+            Err(_) => break
+        };
+
+        if let Err(err) = handle_connection(database, stream) {
+            warn!("{}", tr!(Message::UnableToAnswer, err));
+        }
+    }
+}
+
+/// Answers HTTP queries about the given database until the process is killed. Connections are
+/// answered by a fixed number of workers, so that slow clients cannot exhaust the threads or the
+/// memory of the process.
+pub fn serve(result: SdbReadResult, host: &str, port: u16) -> std::io::Result<()> {
+    let listener = TcpListener::bind((host, port))?;
+    let index = SearchIndex::new(&result).with_prefix_trie();
+    let database = Arc::new(Database {
+        result,
+        index
+    });
+
+    let (sender, receiver) = mpsc::sync_channel(WORKER_COUNT);
+    let receiver = Arc::new(Mutex::new(receiver));
+    for _ in 0..WORKER_COUNT {
+        let database = Arc::clone(&database);
+        let receiver = Arc::clone(&receiver);
+        thread::spawn(move || run_worker(&database, &receiver));
+    }
+
+    info!("{}", tr!(Message::ListeningOn, host, port));
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if sender.send(stream).is_err() {
+                    break;
+                }
+            },
+            Err(err) => warn!("{}", tr!(Message::UnableToAccept, err))
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,193 @@
+//! Import of the SQLite database used by the Langbook app.
+//!
+//! Only the tables that have a counterpart in the streamed format are read:
+//!
+//! * `SymbolArrays(id, str)`
+//! * `Languages(id, code, mainAlphabet)`
+//! * `Alphabets(id, language)`
+//! * `Conversions(sourceAlphabet, targetAlphabet, source, target)`
+//! * `Correlations(correlationId, alphabet, symbolArray)`
+//! * `CorrelationArrays(arrayId, arrayPos, correlation)`
+//! * `Acceptations(id, concept, correlationArray)`
+//! * `ComplementedConcepts(id, base, complement)`
+//! * `ConceptCompositions(composition, item)`
+//!
+//! Identifiers in the app are not required to be contiguous, so they are all remapped. Languages
+//! are sorted by their code, and their alphabets by their identifier. Concepts keep their
+//! identifiers, as they are already the ones used in the streamed format.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+use rusqlite::{Connection, OpenFlags};
+use crate::builder::SdbBuilder;
+use crate::sdb::{Alphabet, CorrelationIndex, LanguageCode, SdbReadResult};
+
+fn query_error(table: &str, err: rusqlite::Error) -> String {
+    format!("Unable to read table {}: {}", table, err)
+}
+
+fn query<T>(connection: &Connection, table: &str, sql: &str, mapper: impl FnMut(&rusqlite::Row) -> rusqlite::Result<T>) -> Result<Vec<T>, String> {
+    let mut statement = connection.prepare(sql).map_err(|err| query_error(table, err))?;
+    let rows = statement.query_map([], mapper).map_err(|err| query_error(table, err))?;
+    rows.collect::<rusqlite::Result<Vec<T>>>().map_err(|err| query_error(table, err))
+}
+
+fn concept_from(value: i64) -> Result<usize, String> {
+    usize::try_from(value).ok().filter(|concept| *concept > 0).ok_or_else(|| format!("Invalid concept {}", value))
+}
+
+struct Importer<'a> {
+    connection: &'a Connection,
+    builder: SdbBuilder,
+    symbol_arrays: HashMap<i64, String>,
+    alphabets: HashMap<i64, Alphabet>
+}
+
+impl<'a> Importer<'a> {
+    fn symbol_array_text(&self, id: i64) -> Result<&str, String> {
+        self.symbol_arrays.get(&id).map(String::as_str).ok_or_else(|| format!("Symbol array {} not found", id))
+    }
+
+    fn alphabet(&self, id: i64) -> Result<Alphabet, String> {
+        self.alphabets.get(&id).copied().ok_or_else(|| format!("Alphabet {} not found", id))
+    }
+
+    fn import_symbol_arrays(&mut self) -> Result<(), String> {
+        let rows = query(self.connection, "SymbolArrays", "SELECT id, str FROM SymbolArrays", |row| Ok((row.get(0)?, row.get(1)?)))?;
+        self.symbol_arrays = rows.into_iter().collect();
+        Ok(())
+    }
+
+    fn import_languages(&mut self) -> Result<(), String> {
+        let mut languages: Vec<(String, i64)> = query(self.connection, "Languages", "SELECT code, id FROM Languages", |row| Ok((row.get(0)?, row.get(1)?)))?;
+        languages.sort();
+
+        let mut alphabets: BTreeMap<i64, Vec<i64>> = BTreeMap::new();
+        for (alphabet, language) in query(self.connection, "Alphabets", "SELECT id, language FROM Alphabets ORDER BY id", |row| Ok((row.get(0)?, row.get(1)?)))? {
+            alphabets.entry(language).or_default().push(alphabet);
+        }
+
+        for (code, id) in languages {
+            let alphabet_ids = alphabets.remove(&id).ok_or_else(|| format!("Language {} has no alphabets", code))?;
+            let new_alphabets = self.builder.add_language(LanguageCode::try_from(code.as_str())?, alphabet_ids.len())?;
+            self.alphabets.extend(alphabet_ids.into_iter().zip(new_alphabets));
+        }
+
+        match alphabets.keys().next() {
+            Some(language) => Err(format!("Alphabets found for unknown language {}", language)),
+            None => Ok(())
+        }
+    }
+
+    fn import_conversions(&mut self) -> Result<(), String> {
+        let rows: Vec<(i64, i64, i64, i64)> = query(self.connection, "Conversions", "SELECT sourceAlphabet, targetAlphabet, source, target FROM Conversions ORDER BY id",
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?;
+
+        let mut conversions: BTreeMap<(Alphabet, Alphabet), Vec<(i64, i64)>> = BTreeMap::new();
+        for (source_alphabet, target_alphabet, source, target) in rows {
+            let key = (self.alphabet(source_alphabet)?, self.alphabet(target_alphabet)?);
+            conversions.entry(key).or_default().push((source, target));
+        }
+
+        for ((source_alphabet, target_alphabet), pairs) in conversions {
+            let pairs = pairs.into_iter()
+                .map(|(source, target)| Ok((String::from(self.symbol_array_text(source)?), String::from(self.symbol_array_text(target)?))))
+                .collect::<Result<Vec<(String, String)>, String>>()?;
+            let pairs: Vec<(&str, &str)> = pairs.iter().map(|(source, target)| (source.as_str(), target.as_str())).collect();
+            self.builder.add_conversion(source_alphabet, target_alphabet, &pairs);
+        }
+
+        Ok(())
+    }
+
+    fn read_correlations(&mut self) -> Result<HashMap<i64, CorrelationIndex>, String> {
+        let rows: Vec<(i64, i64, i64)> = query(self.connection, "Correlations", "SELECT correlationId, alphabet, symbolArray FROM Correlations",
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+
+        let mut correlations: BTreeMap<i64, BTreeMap<Alphabet, i64>> = BTreeMap::new();
+        for (correlation, alphabet, symbol_array) in rows {
+            let alphabet = self.alphabet(alphabet)?;
+            if correlations.entry(correlation).or_default().insert(alphabet, symbol_array).is_some() {
+                return Err(format!("Correlation {} has more than one text for the same alphabet", correlation));
+            }
+        }
+
+        let mut indexes = HashMap::new();
+        for (correlation, entries) in correlations {
+            let mut texts = BTreeMap::new();
+            for (alphabet, symbol_array) in entries {
+                texts.insert(alphabet, String::from(self.symbol_array_text(symbol_array)?));
+            }
+
+            let texts: BTreeMap<Alphabet, &str> = texts.iter().map(|(alphabet, text)| (*alphabet, text.as_str())).collect();
+            indexes.insert(correlation, self.builder.correlation(&texts));
+        }
+
+        Ok(indexes)
+    }
+
+    fn import_acceptations(&mut self) -> Result<(), String> {
+        let correlations = self.read_correlations()?;
+        let rows: Vec<(i64, i64)> = query(self.connection, "CorrelationArrays", "SELECT arrayId, correlation FROM CorrelationArrays ORDER BY arrayId, arrayPos",
+            |row| Ok((row.get(0)?, row.get(1)?)))?;
+
+        let mut arrays: HashMap<i64, Vec<CorrelationIndex>> = HashMap::new();
+        for (array, correlation) in rows {
+            let correlation = *correlations.get(&correlation).ok_or_else(|| format!("Correlation {} not found", correlation))?;
+            arrays.entry(array).or_default().push(correlation);
+        }
+
+        let rows: Vec<(i64, i64)> = query(self.connection, "Acceptations", "SELECT concept, correlationArray FROM Acceptations ORDER BY id",
+            |row| Ok((row.get(0)?, row.get(1)?)))?;
+        for (concept, array) in rows {
+            let correlations = arrays.get(&array).ok_or_else(|| format!("Correlation array {} not found", array))?;
+            let array_index = self.builder.correlation_array(correlations);
+            self.builder.add_acceptation(concept_from(concept)?, array_index)?;
+        }
+
+        Ok(())
+    }
+
+    /// In the app, the complement of a concept is either a single concept or a composition, whose
+    /// items are the actual complements. A complement of 0 means that there is none.
+    fn import_definitions(&mut self) -> Result<(), String> {
+        let mut compositions: HashMap<i64, BTreeSet<usize>> = HashMap::new();
+        for (composition, item) in query(self.connection, "ConceptCompositions", "SELECT composition, item FROM ConceptCompositions", |row| Ok((row.get(0)?, row.get(1)?)))? {
+            compositions.entry(composition).or_default().insert(concept_from(item)?);
+        }
+
+        let rows: Vec<(i64, i64, i64)> = query(self.connection, "ComplementedConcepts", "SELECT id, base, complement FROM ComplementedConcepts",
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+        for (concept, base, complement) in rows {
+            let complements = match compositions.get(&complement) {
+                Some(items) => items.clone(),
+                None if complement == 0 => BTreeSet::new(),
+                None => BTreeSet::from([concept_from(complement)?])
+            };
+
+            self.builder.add_definition(concept_from(concept)?, concept_from(base)?, complements)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads the Langbook database at the given path, which is never modified.
+pub fn import(path: &Path) -> Result<SdbReadResult, String> {
+    let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|err| format!("Unable to open {}: {}", path.display(), err))?;
+
+    let mut importer = Importer {
+        connection: &connection,
+        builder: SdbBuilder::new(),
+        symbol_arrays: HashMap::new(),
+        alphabets: HashMap::new()
+    };
+
+    importer.import_symbol_arrays()?;
+    importer.import_languages()?;
+    importer.import_conversions()?;
+    importer.import_acceptations()?;
+    importer.import_definitions()?;
+    Ok(importer.builder.build())
+}
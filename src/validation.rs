@@ -0,0 +1,282 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use crate::sdb::{CorrelationArrayIndex, SdbReadResult};
+
+/// Inconsistency found between the decoded sections of a database.
+pub enum Violation {
+    ConversionAlphabetOutOfRange {
+        conversion: usize,
+        alphabet: usize
+    },
+    ConversionSymbolArrayOutOfRange {
+        conversion: usize,
+        pair: usize,
+        symbol_array: usize
+    },
+    CorrelationAlphabetOutOfRange {
+        correlation: usize,
+        alphabet: usize
+    },
+    CorrelationSymbolArrayOutOfRange {
+        correlation: usize,
+        symbol_array: usize
+    },
+    CorrelationMixingLanguages {
+        correlation: usize,
+
+        /// Codes of the languages involved, in the order their alphabets are found.
+        languages: Vec<String>,
+
+        /// Alphabet index and text of each entry within the correlation, to help locating it.
+        texts: Vec<(usize, String)>
+    },
+    CorrelationOutOfRange {
+        correlation_array: usize,
+        position: usize,
+        correlation: usize
+    },
+    CorrelationArrayOutOfRange {
+        acceptation: usize,
+        correlation_array: usize
+    },
+    AcceptationConceptOutOfRange {
+        acceptation: usize,
+        concept: usize
+    },
+    DefinitionConceptOutOfRange {
+        concept: usize
+    },
+    DefinitionBaseConceptOutOfRange {
+        concept: usize,
+        base_concept: usize
+    },
+    DefinitionComplementOutOfRange {
+        concept: usize,
+        complement: usize
+    },
+    UnconvertibleText {
+        conversion: usize,
+        acceptation: usize,
+        text: String
+    },
+    ConceptWithoutAcceptation {
+        concept: usize
+    }
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Violation::ConversionAlphabetOutOfRange { conversion, alphabet } => write!(f, "Conversion {} refers to alphabet {}, which does not exist", conversion, alphabet),
+            Violation::ConversionSymbolArrayOutOfRange { conversion, pair, symbol_array } => write!(f, "Pair {} of conversion {} refers to symbol array {}, which does not exist", pair, conversion, symbol_array),
+            Violation::CorrelationAlphabetOutOfRange { correlation, alphabet } => write!(f, "Correlation {} refers to alphabet {}, which does not exist", correlation, alphabet),
+            Violation::CorrelationSymbolArrayOutOfRange { correlation, symbol_array } => write!(f, "Correlation {} refers to symbol array {}, which does not exist", correlation, symbol_array),
+            Violation::CorrelationMixingLanguages { correlation, languages, texts } => {
+                let texts: Vec<String> = texts.iter().map(|(alphabet, text)| format!("{} ({})", text, alphabet)).collect();
+                write!(f, "Correlation {} mixes alphabets from languages {}: {}", correlation, languages.join(", "), texts.join(", "))
+            },
+            Violation::CorrelationOutOfRange { correlation_array, position, correlation } => write!(f, "Position {} of correlation array {} refers to correlation {}, which does not exist", position, correlation_array, correlation),
+            Violation::CorrelationArrayOutOfRange { acceptation, correlation_array } => write!(f, "Acceptation {} refers to correlation array {}, which does not exist", acceptation, correlation_array),
+            Violation::AcceptationConceptOutOfRange { acceptation, concept } => write!(f, "Acceptation {} refers to concept {}, which is out of range", acceptation, concept),
+            Violation::DefinitionConceptOutOfRange { concept } => write!(f, "Definition found for concept {}, which is out of range", concept),
+            Violation::DefinitionBaseConceptOutOfRange { concept, base_concept } => write!(f, "Definition of concept {} refers to base concept {}, which is out of range", concept, base_concept),
+            Violation::DefinitionComplementOutOfRange { concept, complement } => write!(f, "Definition of concept {} refers to complement {}, which is out of range", concept, complement),
+            Violation::UnconvertibleText { conversion, acceptation, text } => write!(f, "Text {} of acceptation {} cannot be fully converted by conversion {}", text, acceptation, conversion),
+            Violation::ConceptWithoutAcceptation { concept } => write!(f, "Concept {} is used in definitions, but it has no acceptation to be reached from", concept)
+        }
+    }
+}
+
+fn validate_conversions(result: &SdbReadResult, alphabet_count: usize, violations: &mut Vec<Violation>) {
+    let symbol_array_count = result.symbol_arrays.len();
+    for (conversion_index, conversion) in result.conversions.iter().enumerate() {
+        for alphabet in [conversion.source, conversion.target] {
+            if alphabet.index >= alphabet_count {
+                violations.push(Violation::ConversionAlphabetOutOfRange {
+                    conversion: conversion_index,
+                    alphabet: alphabet.index
+                });
+            }
+        }
+
+        for (pair_index, (source, target)) in conversion.pairs.iter().enumerate() {
+            for symbol_array in [source, target] {
+                if symbol_array.index >= symbol_array_count {
+                    violations.push(Violation::ConversionSymbolArrayOutOfRange {
+                        conversion: conversion_index,
+                        pair: pair_index,
+                        symbol_array: symbol_array.index
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn validate_correlations(result: &SdbReadResult, alphabet_count: usize, violations: &mut Vec<Violation>) {
+    let symbol_array_count = result.symbol_arrays.len();
+    for (correlation_index, correlation) in result.correlations.iter().enumerate() {
+        let mut languages: Vec<usize> = Vec::new();
+        let mut texts: Vec<(usize, String)> = Vec::new();
+        for alphabet in correlation.keys() {
+            if alphabet.index >= alphabet_count {
+                violations.push(Violation::CorrelationAlphabetOutOfRange {
+                    correlation: correlation_index,
+                    alphabet: alphabet.index
+                });
+            }
+            else if let Some(language) = result.language_index_of(*alphabet) {
+                if !languages.contains(&language) {
+                    languages.push(language);
+                }
+            }
+
+            let symbol_array = &correlation[alphabet];
+            if let Some(text) = result.symbol_arrays.get(symbol_array.index) {
+                texts.push((alphabet.index, String::from(text)));
+            }
+
+            if symbol_array.index >= symbol_array_count {
+                violations.push(Violation::CorrelationSymbolArrayOutOfRange {
+                    correlation: correlation_index,
+                    symbol_array: symbol_array.index
+                });
+            }
+        }
+
+        if languages.len() > 1 {
+            violations.push(Violation::CorrelationMixingLanguages {
+                correlation: correlation_index,
+                languages: languages.into_iter().map(|language| result.languages[language].code.to_string()).collect(),
+                texts
+            });
+        }
+    }
+}
+
+fn validate_correlation_arrays(result: &SdbReadResult, violations: &mut Vec<Violation>) {
+    let correlation_count = result.correlations.len();
+    for (array_index, array) in result.correlation_arrays.iter().enumerate() {
+        for (position, correlation) in array.iter().enumerate() {
+            if correlation.index >= correlation_count {
+                violations.push(Violation::CorrelationOutOfRange {
+                    correlation_array: array_index,
+                    position,
+                    correlation: correlation.index
+                });
+            }
+        }
+    }
+}
+
+fn is_valid_concept(result: &SdbReadResult, concept: usize) -> bool {
+    concept >= 1 && concept <= result.max_concept
+}
+
+fn validate_acceptations(result: &SdbReadResult, violations: &mut Vec<Violation>) {
+    let correlation_array_count = result.correlation_arrays.len();
+    for (acceptation_index, acceptation) in result.acceptations.iter().enumerate() {
+        if !is_valid_concept(result, acceptation.concept) {
+            violations.push(Violation::AcceptationConceptOutOfRange {
+                acceptation: acceptation_index,
+                concept: acceptation.concept
+            });
+        }
+
+        if acceptation.correlation_array_index.index >= correlation_array_count {
+            violations.push(Violation::CorrelationArrayOutOfRange {
+                acceptation: acceptation_index,
+                correlation_array: acceptation.correlation_array_index.index
+            });
+        }
+    }
+}
+
+fn validate_definitions(result: &SdbReadResult, violations: &mut Vec<Violation>) {
+    for (concept, definition) in result.definitions.iter() {
+        if !is_valid_concept(result, *concept) {
+            violations.push(Violation::DefinitionConceptOutOfRange {
+                concept: *concept
+            });
+        }
+
+        if !is_valid_concept(result, definition.base_concept) {
+            violations.push(Violation::DefinitionBaseConceptOutOfRange {
+                concept: *concept,
+                base_concept: definition.base_concept
+            });
+        }
+
+        for complement in definition.complements.iter() {
+            if !is_valid_concept(result, *complement) {
+                violations.push(Violation::DefinitionComplementOutOfRange {
+                    concept: *concept,
+                    complement: *complement
+                });
+            }
+        }
+    }
+}
+
+/// Checks that all concepts involved in definitions have at least one acceptation, as they could
+/// not be reached from any text otherwise. Concepts out of range are already reported on their own.
+fn validate_concept_reachability(result: &SdbReadResult, violations: &mut Vec<Violation>) {
+    let reachable: HashSet<usize> = result.acceptations.iter().map(|acc| acc.concept).collect();
+    let mut defined = BTreeSet::new();
+    for (concept, definition) in result.definitions.iter() {
+        defined.insert(*concept);
+        defined.insert(definition.base_concept);
+        defined.extend(definition.complements.iter().copied());
+    }
+
+    for concept in defined {
+        if is_valid_concept(result, concept) && !reachable.contains(&concept) {
+            violations.push(Violation::ConceptWithoutAcceptation {
+                concept
+            });
+        }
+    }
+}
+
+/// Applies each conversion to the text of every acceptation in its source alphabet. This expects
+/// all references to be valid, as texts could not be resolved otherwise.
+fn validate_conversion_coverage(result: &SdbReadResult, violations: &mut Vec<Violation>) {
+    let mut correlations: HashMap<CorrelationArrayIndex, _> = HashMap::new();
+    for (conversion_index, conversion) in result.conversions.iter().enumerate() {
+        for (acceptation_index, acceptation) in result.acceptations.iter().enumerate() {
+            let texts = correlations.entry(acceptation.correlation_array_index)
+                .or_insert_with(|| result.get_complete_correlation(acceptation.correlation_array_index));
+
+            if let Some(text) = texts.get(&conversion.source) {
+                if conversion.convert(&result.symbol_arrays, text).is_none() {
+                    violations.push(Violation::UnconvertibleText {
+                        conversion: conversion_index,
+                        acceptation: acceptation_index,
+                        text: text.clone()
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Checks that all references between sections point to existing entries, that no correlation
+/// mixes alphabets from different languages, that all texts in the source alphabet of a
+/// conversion can be converted, and that all concepts in definitions have an acceptation. The
+/// check on conversions is skipped if any reference is invalid.
+pub fn validate(result: &SdbReadResult) -> Vec<Violation> {
+    let alphabet_count = result.alphabet_count();
+    let mut violations: Vec<Violation> = Vec::new();
+    validate_conversions(result, alphabet_count, &mut violations);
+    validate_correlations(result, alphabet_count, &mut violations);
+    validate_correlation_arrays(result, &mut violations);
+    validate_acceptations(result, &mut violations);
+    validate_definitions(result, &mut violations);
+    if violations.is_empty() {
+        validate_conversion_coverage(result, &mut violations);
+    }
+
+    validate_concept_reachability(result, &mut violations);
+
+    violations
+}
@@ -0,0 +1,82 @@
+use std::io::{Cursor, Read};
+use wasm_bindgen::prelude::*;
+use crate::file_utils;
+use crate::huffman::InputBitStream;
+use crate::sdb::{SdbReader, SdbReadResult, SDB_HEADER};
+
+/// Decoded database, as exposed to JavaScript.
+#[wasm_bindgen]
+pub struct Database {
+    result: SdbReadResult
+}
+
+/// Texts and definition of a concept, as exposed to JavaScript.
+#[wasm_bindgen]
+pub struct Concept {
+    id: usize,
+    texts: Vec<String>,
+    base_concept: Option<usize>,
+    complements: Vec<usize>
+}
+
+#[wasm_bindgen]
+impl Concept {
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Texts in all alphabets of all acceptations of this concept.
+    #[wasm_bindgen(getter)]
+    pub fn texts(&self) -> Vec<String> {
+        self.texts.clone()
+    }
+
+    #[wasm_bindgen(getter, js_name = baseConcept)]
+    pub fn base_concept(&self) -> Option<usize> {
+        self.base_concept
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn complements(&self) -> Vec<usize> {
+        self.complements.clone()
+    }
+}
+
+#[wasm_bindgen]
+impl Database {
+    /// Decodes the given SDB file content.
+    pub fn load(bytes: &[u8]) -> Result<Database, JsError> {
+        let mut input = Cursor::new(bytes);
+        file_utils::read_header(&mut (&mut input).bytes(), SDB_HEADER).map_err(|err| JsError::new(&err.message))?;
+        match SdbReader::new(InputBitStream::from(&mut input)).read() {
+            Ok(result) => Ok(Database {
+                result
+            }),
+            Err(err) => Err(JsError::new(&err.message))
+        }
+    }
+
+    /// Returns the identifiers of all concepts with the given text in any of their alphabets.
+    pub fn lookup(&self, text: &str) -> Vec<usize> {
+        self.result.find_concepts(text)
+    }
+
+    #[wasm_bindgen(js_name = getConcept)]
+    pub fn get_concept(&self, id: usize) -> Option<Concept> {
+        if id == 0 || id > self.result.max_concept {
+            return None;
+        }
+
+        let texts = self.result.concept_texts(id);
+        let definition = self.result.definitions.get(&id);
+        let complements: Vec<usize> = definition.map_or_else(Vec::new, |definition| definition.complements.iter().copied().collect());
+
+        Some(Concept {
+            id,
+            texts,
+            base_concept: definition.map(|definition| definition.base_concept),
+            complements
+        })
+    }
+}
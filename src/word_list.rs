@@ -0,0 +1,194 @@
+//! Import of simple word lists, where each line is an acceptation written as:
+//!
+//! ```text
+//! <language>,<text>[,<text>...],<gloss>
+//! ```
+//!
+//! The language is given by its 2-letter code, followed by one text for each of its alphabets,
+//! and the gloss identifies the concept. All lines sharing the same gloss are considered
+//! synonyms or translations of each other. All lines for a language must include the same
+//! number of texts, that becomes the number of alphabets of that language. Texts can be left
+//! empty when they are not known for an alphabet.
+//!
+//! Conversion tables can be imported in the same way, with one pair per line written as:
+//!
+//! ```text
+//! <source>,<target>
+//! ```
+//!
+//! Fields can be separated by commas or tabs. Fields separated by commas can be quoted with
+//! double quotes, doubling them when they are part of the text. Empty lines and lines starting
+//! with `#` are ignored.
+
+use std::collections::{BTreeMap, HashMap};
+use crate::builder::SdbBuilder;
+use crate::sdb::{Alphabet, LanguageCode, SdbReadResult};
+
+fn split_quoted(line: &str, delimiter: char) -> Result<Vec<String>, String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = line.chars().peekable();
+    let mut quoted = false;
+    while let Some(ch) = chars.next() {
+        if quoted {
+            if ch == '"' {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                }
+                else {
+                    quoted = false;
+                }
+            }
+            else {
+                field.push(ch);
+            }
+        }
+        else if ch == '"' && field.is_empty() {
+            quoted = true;
+        }
+        else if ch == delimiter {
+            fields.push(field);
+            field = String::new();
+        }
+        else {
+            field.push(ch);
+        }
+    }
+
+    if quoted {
+        return Err(String::from("Unterminated quoted field"));
+    }
+
+    fields.push(field);
+    Ok(fields)
+}
+
+/// Splits a line into its fields. Quotes are only understood when separating with commas.
+fn split(line: &str, delimiter: char) -> Result<Vec<String>, String> {
+    if delimiter == ',' {
+        split_quoted(line, delimiter)
+    }
+    else {
+        Ok(line.split(delimiter).map(String::from).collect())
+    }
+}
+
+struct Entry {
+    language: LanguageCode,
+    texts: Vec<String>,
+    gloss: String
+}
+
+fn parse_entry(line: &str, delimiter: char) -> Result<Entry, String> {
+    let mut fields = split(line, delimiter)?;
+    if fields.len() < 3 {
+        return Err(format!("Expected a language, at least one text and a gloss, but found {} fields", fields.len()));
+    }
+
+    let gloss = fields.pop().unwrap();
+    if gloss.is_empty() {
+        return Err(String::from("Missing gloss"));
+    }
+
+    let language = LanguageCode::try_from(fields.remove(0).as_str())?;
+    if fields.iter().all(String::is_empty) {
+        return Err(String::from("All texts are empty"));
+    }
+
+    Ok(Entry {
+        language,
+        texts: fields,
+        gloss
+    })
+}
+
+/// Builds a database with one acceptation per line of the given content, using the given field
+/// delimiter. Concepts are numbered following the order in which their glosses first appear.
+pub fn import(content: &str, delimiter: char) -> Result<SdbReadResult, String> {
+    let mut entries = Vec::new();
+    let mut alphabet_counts: BTreeMap<u16, usize> = BTreeMap::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let line_number = index + 1;
+        let entry = parse_entry(line, delimiter).map_err(|err| format!("{} at line {}", err, line_number))?;
+        let alphabet_count = *alphabet_counts.entry(entry.language.code).or_insert(entry.texts.len());
+        if alphabet_count != entry.texts.len() {
+            return Err(format!("Expected {} texts for language {}, but found {} at line {}", alphabet_count, entry.language, entry.texts.len(), line_number));
+        }
+
+        entries.push(entry);
+    }
+
+    let mut builder = SdbBuilder::new();
+    let mut alphabets: HashMap<u16, Vec<Alphabet>> = HashMap::new();
+    for (code, alphabet_count) in alphabet_counts {
+        alphabets.insert(code, builder.add_language(LanguageCode { code }, alphabet_count)?);
+    }
+
+    let mut concepts: HashMap<String, usize> = HashMap::new();
+    for entry in entries {
+        let next_concept = concepts.len() + 1;
+        let concept = *concepts.entry(entry.gloss).or_insert(next_concept);
+        let texts: BTreeMap<Alphabet, &str> = alphabets[&entry.language.code].iter().copied()
+            .zip(entry.texts.iter().map(String::as_str))
+            .filter(|(_, text)| !text.is_empty())
+            .collect();
+
+        let correlation = builder.correlation(&texts);
+        let correlation_array = builder.correlation_array(&[correlation]);
+        builder.add_acceptation(concept, correlation_array)?;
+    }
+
+    Ok(builder.build())
+}
+
+/// Sets the conversion between the given alphabets of the database from the given content, with
+/// one pair of source and target texts per line, using the given field delimiter. Any previous
+/// conversion between the same alphabets is replaced.
+pub fn import_conversion(result: &mut SdbReadResult, source: usize, target: usize, content: &str, delimiter: char) -> Result<(), String> {
+    let alphabet_count = result.alphabet_count();
+    let (source_alphabet, target_alphabet) = match (result.alphabet(source), result.alphabet(target)) {
+        (Some(source_alphabet), Some(target_alphabet)) => (source_alphabet, target_alphabet),
+        _ => return Err(format!("Conversion from alphabet {} to {} refers to missing alphabets, there are only {} alphabets", source, target, alphabet_count))
+    };
+
+    if source == target {
+        return Err(format!("Conversion source and target alphabets must be different, but both are {}", source));
+    }
+
+    let mut pairs: Vec<(String, String)> = Vec::new();
+    let mut sources: HashMap<String, usize> = HashMap::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let line_number = index + 1;
+        let mut fields = split(line, delimiter).map_err(|err| format!("{} at line {}", err, line_number))?;
+        if fields.len() != 2 {
+            return Err(format!("Expected a source and a target text, but found {} fields at line {}", fields.len(), line_number));
+        }
+
+        let target_text = fields.pop().unwrap();
+        let source_text = fields.pop().unwrap();
+        if source_text.is_empty() {
+            return Err(format!("Missing source text at line {}", line_number));
+        }
+
+        if let Some(previous_line) = sources.insert(source_text.clone(), line_number) {
+            return Err(format!("Source text {} at line {} was already found at line {}", source_text, line_number, previous_line));
+        }
+
+        pairs.push((source_text, target_text));
+    }
+
+    let pairs: Vec<(&str, &str)> = pairs.iter().map(|(source_text, target_text)| (source_text.as_str(), target_text.as_str())).collect();
+    result.set_conversion(source_alphabet, target_alphabet, &pairs);
+    Ok(())
+}
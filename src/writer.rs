@@ -0,0 +1,483 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+use std::io::{self, Write};
+use crate::huffman::{DefinedHuffmanTable, HuffmanEncoder, IntegerNumberHuffmanTable, NaturalNumberHuffmanTable, NaturalUsizeHuffmanTable, OutputBitStream, RangedIntegerHuffmanTable, RangedNaturalUsizeHuffmanTable};
+use crate::sdb::{Conversion, DefinedTables, SdbReadResult, SDB_HEADER};
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Counts how many times each value appears, to build a table for them.
+fn frequencies<S: Copy + Ord>(values: impl Iterator<Item = S>) -> BTreeMap<S, usize> {
+    let mut frequencies: BTreeMap<S, usize> = BTreeMap::new();
+    for value in values {
+        *frequencies.entry(value).or_insert(0) += 1;
+    }
+
+    frequencies
+}
+
+/// Builds the optimal table for the given values. As the format always expects at least one
+/// symbol in the table, the given default is used when there are no values.
+fn build_table<S: Copy + Ord>(values: impl Iterator<Item = S>, default: S) -> DefinedHuffmanTable<S> {
+    let mut frequencies = frequencies(values);
+    if frequencies.is_empty() {
+        frequencies.insert(default, 1);
+    }
+
+    DefinedHuffmanTable::from_frequencies(&frequencies)
+}
+
+/// Symbols that can be stored in a table defined within the file.
+trait TableSymbol: Copy + Ord {
+    /// Writes the definition of the given table. Differences between consecutive symbols are
+    /// encoded with a natural number table with the given alignment.
+    fn write_definition<W: Write>(stream: &mut OutputBitStream<W>, table: &DefinedHuffmanTable<Self>, diff_alignment: u32) -> io::Result<()>;
+}
+
+impl TableSymbol for char {
+    fn write_definition<W: Write>(stream: &mut OutputBitStream<W>, table: &DefinedHuffmanTable<Self>, diff_alignment: u32) -> io::Result<()> {
+        let diff_table = NaturalNumberHuffmanTable::create_with_alignment(diff_alignment);
+        stream.write_table(table, &NaturalNumberHuffmanTable::create_with_alignment(8), &diff_table, OutputBitStream::write_character, OutputBitStream::write_diff_character)
+    }
+}
+
+impl TableSymbol for u32 {
+    fn write_definition<W: Write>(stream: &mut OutputBitStream<W>, table: &DefinedHuffmanTable<Self>, diff_alignment: u32) -> io::Result<()> {
+        let diff_table = NaturalNumberHuffmanTable::create_with_alignment(diff_alignment);
+        stream.write_table(table, &NaturalNumberHuffmanTable::create_with_alignment(8), &diff_table, OutputBitStream::write_symbol, OutputBitStream::write_diff_u32)
+    }
+}
+
+impl TableSymbol for i32 {
+    fn write_definition<W: Write>(stream: &mut OutputBitStream<W>, table: &DefinedHuffmanTable<Self>, diff_alignment: u32) -> io::Result<()> {
+        let diff_table = NaturalNumberHuffmanTable::create_with_alignment(diff_alignment);
+        stream.write_table(table, &IntegerNumberHuffmanTable::create_with_alignment(8), &diff_table, OutputBitStream::write_symbol, OutputBitStream::write_diff_i32)
+    }
+}
+
+/// Number of bits needed to define the given table and encode all the given values with it, or
+/// None if any of the values is not in the table.
+fn encoded_bits<S: TableSymbol>(table: &DefinedHuffmanTable<S>, values: &[S], diff_alignment: u32) -> Option<usize> {
+    let mut stream = OutputBitStream::new(io::sink());
+    S::write_definition(&mut stream, table, diff_alignment).ok()?;
+    let mut bits = stream.bits_written();
+    for value in values {
+        let (value_bits, _) = table.find_symbol(*value)?;
+        bits += value_bits as usize;
+    }
+
+    Some(bits)
+}
+
+/// Tables defined within the file.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Table {
+    Chars,
+    SymbolArrayLengths,
+    CorrelationLengths,
+    CorrelationArrayLengths,
+    AcceptationSetLengths,
+    DefinitionLengths
+}
+
+impl Display for Table {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Table::Chars => "characters",
+            Table::SymbolArrayLengths => "symbol array lengths",
+            Table::CorrelationLengths => "correlation lengths",
+            Table::CorrelationArrayLengths => "correlation array lengths",
+            Table::AcceptationSetLengths => "acceptation set lengths",
+            Table::DefinitionLengths => "definition lengths"
+        })
+    }
+}
+
+/// Bits taken by a table definition and all values encoded with it.
+pub struct TableSize {
+    pub table: Table,
+
+    /// Bits when using the table found in the original file. This is None if there was no
+    /// original table, or it cannot encode all values.
+    pub original_bits: Option<usize>,
+
+    /// Bits when using a table built from the frequencies of the values.
+    pub rebuilt_bits: usize,
+
+    /// Whether the original table was the one written.
+    pub reused: bool
+}
+
+fn to_i32(value: usize) -> io::Result<i32> {
+    i32::try_from(value).map_err(|_| invalid_data(format!("Length {} is too big", value)))
+}
+
+/// Counterpart of SdbReader, encoding all sections of a database with tables built from the
+/// actual frequencies of the values written, unless the original tables are reused.
+pub struct SdbWriter<W: Write> {
+    stream: OutputBitStream<W>,
+    natural2_usize_table: NaturalUsizeHuffmanTable,
+    natural8_usize_table: NaturalUsizeHuffmanTable,
+    original_tables: DefinedTables,
+    reuse_tables: bool,
+    table_sizes: Vec<TableSize>
+}
+
+impl<W: Write> SdbWriter<W> {
+    pub fn new(stream: OutputBitStream<W>) -> Self {
+        Self {
+            stream,
+            natural2_usize_table: NaturalUsizeHuffmanTable::create_with_alignment(2),
+            natural8_usize_table: NaturalUsizeHuffmanTable::create_with_alignment(8),
+            original_tables: DefinedTables::default(),
+            reuse_tables: false,
+            table_sizes: Vec::new()
+        }
+    }
+
+    /// Sets the tables found in the original file, usually the ones in the database being written,
+    /// so that their size can be compared with the tables built from the actual frequencies. If
+    /// reuse is true, the original tables are written instead, as long as they can encode all
+    /// values.
+    pub fn with_original_tables(mut self, tables: &DefinedTables, reuse: bool) -> Self {
+        self.original_tables = tables.clone();
+        self.reuse_tables = reuse;
+        self
+    }
+
+    /// Writes the definition of the table to be used for the given values, and returns it.
+    fn write_table<S: TableSymbol>(&mut self, table: Table, original: Option<DefinedHuffmanTable<S>>, values: &[S], default: S, diff_alignment: u32) -> io::Result<DefinedHuffmanTable<S>> {
+        let rebuilt = build_table(values.iter().copied(), default);
+        let rebuilt_bits = encoded_bits(&rebuilt, values, diff_alignment).expect("Tables built from the values should encode all of them");
+        let original = original.and_then(|original| encoded_bits(&original, values, diff_alignment).map(|bits| (original, bits)));
+        let original_bits = original.as_ref().map(|(_, bits)| *bits);
+        let chosen = match original {
+            Some((original, _)) if self.reuse_tables => original,
+            _ => rebuilt
+        };
+
+        self.table_sizes.push(TableSize {
+            table,
+            original_bits,
+            rebuilt_bits,
+            reused: self.reuse_tables && original_bits.is_some()
+        });
+
+        S::write_definition(&mut self.stream, &chosen, diff_alignment)?;
+        Ok(chosen)
+    }
+
+    fn write_length_table(&mut self, table: Table, original: Option<DefinedHuffmanTable<i32>>, lengths: impl Iterator<Item = usize>) -> io::Result<DefinedHuffmanTable<i32>> {
+        let lengths: Vec<i32> = lengths.map(to_i32).collect::<io::Result<Vec<i32>>>()?;
+        self.write_table(table, original, &lengths, 0, 8)
+    }
+
+    fn write_symbol_arrays(&mut self, result: &SdbReadResult) -> io::Result<()> {
+        let symbol_arrays = &result.symbol_arrays;
+        self.stream.write_symbol(&self.natural8_usize_table, symbol_arrays.len())?;
+
+        let chars: Vec<char> = symbol_arrays.iter().flat_map(str::chars).collect();
+        let original = self.original_tables.chars.take();
+        let chars_table = self.write_table(Table::Chars, original, &chars, 'a', 4)?;
+
+        let lengths: Vec<u32> = symbol_arrays.iter().map(|text| u32::try_from(text.chars().count()).unwrap()).collect();
+        let original = self.original_tables.symbol_array_lengths.take();
+        let length_table = self.write_table(Table::SymbolArrayLengths, original, &lengths, 0, 3)?;
+
+        for text in symbol_arrays.iter() {
+            self.stream.write_symbol(&length_table, u32::try_from(text.chars().count()).unwrap())?;
+            for ch in text.chars() {
+                self.stream.write_symbol(&chars_table, ch)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_languages(&mut self, result: &SdbReadResult) -> io::Result<()> {
+        self.stream.write_symbol(&self.natural8_usize_table, result.languages.len())?;
+        let mut first_valid_lang_code = 0;
+        for language in result.languages.iter() {
+            let code = u32::from(language.code.code);
+            if code < first_valid_lang_code {
+                return Err(invalid_data(format!("Languages must be sorted by code, but {} is out of order", language.code)));
+            }
+
+            let table = RangedIntegerHuffmanTable::new(first_valid_lang_code, 26 * 26 - 1);
+            self.stream.write_symbol(&table, code)?;
+            first_valid_lang_code = code + 1;
+            self.stream.write_symbol(&self.natural2_usize_table, language.number_of_alphabets)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_conversions(&mut self, result: &SdbReadResult) -> io::Result<()> {
+        let mut conversions: Vec<&Conversion> = result.conversions.iter().collect();
+        conversions.sort_by_key(|conversion| (conversion.source, conversion.target));
+        self.stream.write_symbol(&self.natural8_usize_table, conversions.len())?;
+        if conversions.is_empty() {
+            return Ok(());
+        }
+
+        let alphabet_count = result.alphabet_count();
+        let symbol_array_count = result.symbol_arrays.len();
+        if alphabet_count == 0 || symbol_array_count == 0 {
+            return Err(invalid_data(String::from("Conversions require at least one alphabet and one symbol array")));
+        }
+
+        let symbol_array_table = RangedNaturalUsizeHuffmanTable::new(0, symbol_array_count - 1);
+        let max_valid_alphabet = alphabet_count - 1;
+        let mut min_source_alphabet = 0usize;
+        let mut min_target_alphabet = 0usize;
+        for conversion in conversions {
+            let source = conversion.source.index;
+            let target = conversion.target.index;
+            if source > max_valid_alphabet || target > max_valid_alphabet {
+                return Err(invalid_data(format!("Conversion from alphabet {} to {} found, but there are only {} alphabets", source, target, alphabet_count)));
+            }
+
+            self.stream.write_symbol(&RangedNaturalUsizeHuffmanTable::new(min_source_alphabet, max_valid_alphabet), source)?;
+            if min_source_alphabet != source {
+                min_target_alphabet = 0;
+                min_source_alphabet = source;
+            }
+
+            // Conversions are sorted, so a target below the minimum can only be a repeated one
+            if target < min_target_alphabet {
+                return Err(invalid_data(format!("Duplicated conversion from alphabet {} to {}", source, target)));
+            }
+
+            self.stream.write_symbol(&RangedNaturalUsizeHuffmanTable::new(min_target_alphabet, max_valid_alphabet), target)?;
+            min_target_alphabet = target + 1;
+
+            self.stream.write_symbol(&self.natural8_usize_table, conversion.pairs.len())?;
+            for (source, target) in conversion.pairs.iter() {
+                self.stream.write_symbol(&symbol_array_table, source.index)?;
+                self.stream.write_symbol(&symbol_array_table, target.index)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_correlations(&mut self, result: &SdbReadResult) -> io::Result<()> {
+        let correlations = &result.correlations;
+        self.stream.write_symbol(&self.natural8_usize_table, correlations.len())?;
+        if correlations.is_empty() {
+            return Ok(());
+        }
+
+        let alphabet_count = result.alphabet_count();
+        let original = self.original_tables.correlation_lengths.take();
+        let length_table = self.write_length_table(Table::CorrelationLengths, original, correlations.iter().map(|correlation| correlation.len()))?;
+        for correlation in correlations.iter() {
+            let map_length = correlation.len();
+            self.stream.write_symbol(&length_table, to_i32(map_length)?)?;
+            if map_length > alphabet_count {
+                return Err(invalid_data(format!("Correlation with {} entries found, but there are only {} alphabets", map_length, alphabet_count)));
+            }
+
+            if map_length > 0 {
+                if result.symbol_arrays.is_empty() {
+                    return Err(invalid_data(String::from("Correlations with entries require at least one symbol array")));
+                }
+
+                let value_table = RangedNaturalUsizeHuffmanTable::new(0, result.symbol_arrays.len() - 1);
+                let entries: BTreeMap<usize, usize> = correlation.iter().map(|(alphabet, symbol_array)| (alphabet.index, symbol_array.index)).collect();
+                let mut min_key = 0;
+                for (map_index, (key, value)) in entries.into_iter().enumerate() {
+                    let key_table = RangedNaturalUsizeHuffmanTable::new(min_key, alphabet_count - map_length + map_index);
+                    self.stream.write_symbol(&key_table, key)?;
+                    self.stream.write_symbol(&value_table, value)?;
+                    min_key = key + 1;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_correlation_arrays(&mut self, result: &SdbReadResult) -> io::Result<()> {
+        let arrays = &result.correlation_arrays;
+        self.stream.write_symbol(&self.natural8_usize_table, arrays.len())?;
+        if arrays.is_empty() {
+            return Ok(());
+        }
+
+        if result.correlations.is_empty() {
+            return Err(invalid_data(String::from("Correlation arrays require at least one correlation")));
+        }
+
+        let correlation_table = RangedNaturalUsizeHuffmanTable::new(0, result.correlations.len() - 1);
+        let original = self.original_tables.correlation_array_lengths.take();
+        let length_table = self.write_length_table(Table::CorrelationArrayLengths, original, arrays.iter().map(Vec::len))?;
+        for array in arrays.iter() {
+            self.stream.write_symbol(&length_table, to_i32(array.len())?)?;
+            for correlation in array.iter() {
+                self.stream.write_symbol(&correlation_table, correlation.index)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Acceptations are encoded as sets of correlation arrays per concept. Consecutive acceptations
+    /// sharing the same concept are grouped into the same set as long as their correlation arrays
+    /// are sorted, so that the original order is kept.
+    fn write_acceptations(&mut self, result: &SdbReadResult) -> io::Result<()> {
+        let mut groups: Vec<(usize, Vec<usize>)> = Vec::new();
+        for acc in result.acceptations.iter() {
+            let index = acc.correlation_array_index.index;
+            match groups.last_mut() {
+                Some((concept, indexes)) if *concept == acc.concept && *indexes.last().unwrap() < index => indexes.push(index),
+                _ => groups.push((acc.concept, vec![index]))
+            }
+        }
+
+        self.stream.write_symbol(&self.natural8_usize_table, groups.len())?;
+        if groups.is_empty() {
+            return Ok(());
+        }
+
+        let correlation_array_count = result.correlation_arrays.len();
+        if correlation_array_count == 0 || result.max_concept == 0 {
+            return Err(invalid_data(String::from("Acceptations require at least one correlation array and one concept")));
+        }
+
+        // Indexes within a set are distinct, so they cannot outnumber the correlation arrays either
+        for acc in result.acceptations.iter() {
+            if acc.concept == 0 || acc.concept > result.max_concept {
+                return Err(invalid_data(format!("Acceptation for concept {} found, but the maximum concept is {}", acc.concept, result.max_concept)));
+            }
+
+            if acc.correlation_array_index.index >= correlation_array_count {
+                return Err(invalid_data(format!("Acceptation for correlation array {} found, but there are only {} correlation arrays", acc.correlation_array_index.index, correlation_array_count)));
+            }
+        }
+
+        let original = self.original_tables.acceptation_set_lengths.take();
+        let length_table = self.write_length_table(Table::AcceptationSetLengths, original, groups.iter().map(|(_, indexes)| indexes.len()))?;
+        let concept_table = RangedNaturalUsizeHuffmanTable::new(1, result.max_concept);
+        for (concept, indexes) in groups {
+            self.stream.write_symbol(&concept_table, concept)?;
+            let length = indexes.len();
+            self.stream.write_symbol(&length_table, to_i32(length)?)?;
+            let mut min_value = 0;
+            for (set_entry_index, value) in indexes.into_iter().enumerate() {
+                let table = RangedNaturalUsizeHuffmanTable::new(min_value, correlation_array_count - length + set_entry_index);
+                self.stream.write_symbol(&table, value)?;
+                min_value = value + 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_complements(&mut self, complements: &BTreeSet<usize>, min_valid_concept: usize, max_valid_concept: usize) -> io::Result<()> {
+        let mut min_valid_complement = min_valid_concept;
+        for complement in complements.iter() {
+            if min_valid_complement >= max_valid_concept {
+                return Err(invalid_data(String::from("Complements cannot be encoded after the second to last concept")));
+            }
+
+            self.stream.write_boolean(true)?;
+            self.stream.write_symbol(&RangedNaturalUsizeHuffmanTable::new(min_valid_complement, max_valid_concept), *complement)?;
+            min_valid_complement = complement + 1;
+        }
+
+        if min_valid_complement < max_valid_concept {
+            self.stream.write_boolean(false)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_definitions(&mut self, result: &SdbReadResult) -> io::Result<()> {
+        let mut bases: BTreeMap<usize, BTreeMap<usize, BTreeSet<usize>>> = BTreeMap::new();
+        for (concept, definition) in result.definitions.iter() {
+            bases.entry(definition.base_concept).or_default().insert(*concept, definition.complements.iter().copied().collect());
+        }
+
+        self.stream.write_symbol(&self.natural8_usize_table, bases.len())?;
+        if bases.is_empty() {
+            return Ok(());
+        }
+
+        let min_valid_concept = 1;
+        let max_valid_concept = result.max_concept;
+        // There can be neither more base concepts nor more concepts sharing a base than definitions
+        if result.definitions.len() > max_valid_concept {
+            return Err(invalid_data(format!("There are {} defined concepts, but the maximum concept is {}", result.definitions.len(), max_valid_concept)));
+        }
+
+        for (concept, definition) in result.definitions.iter() {
+            for value in [*concept, definition.base_concept] {
+                if value < min_valid_concept || value > max_valid_concept {
+                    return Err(invalid_data(format!("Definition for concept {} refers to concept {}, but the maximum concept is {}", concept, value, max_valid_concept)));
+                }
+            }
+        }
+
+        let map_lengths: Vec<u32> = bases.values().map(|definitions| u32::try_from(definitions.len()).unwrap()).collect();
+        let original = self.original_tables.definition_lengths.take();
+        let map_length_table = self.write_table(Table::DefinitionLengths, original, &map_lengths, 1, 8)?;
+
+        let mut min_base_concept = min_valid_concept;
+        let first_max_base_concept = max_valid_concept + 1 - bases.len();
+        for (max_base_concept, (base, definitions)) in (first_max_base_concept..).zip(bases) {
+            self.stream.write_symbol(&RangedNaturalUsizeHuffmanTable::new(min_base_concept, max_base_concept), base)?;
+            min_base_concept = base + 1;
+
+            let map_length = definitions.len();
+            self.stream.write_symbol(&map_length_table, u32::try_from(map_length).unwrap())?;
+            let mut min_concept = min_valid_concept;
+            for (map_index, (concept, complements)) in definitions.into_iter().enumerate() {
+                let concept_table = RangedNaturalUsizeHuffmanTable::new(min_concept, max_valid_concept - map_length + 1 + map_index);
+                self.stream.write_symbol(&concept_table, concept)?;
+                min_concept = concept + 1;
+                self.write_complements(&complements, min_valid_concept, max_valid_concept)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes all sections, returning the underlying writer once everything has been flushed.
+    pub fn write(self, result: &SdbReadResult) -> io::Result<W> {
+        self.write_with_table_sizes(result).map(|(writer, _)| writer)
+    }
+
+    /// Same as write, but also returns the size of each table written, in the order they are
+    /// found in the file.
+    pub fn write_with_table_sizes(mut self, result: &SdbReadResult) -> io::Result<(W, Vec<TableSize>)> {
+        self.write_symbol_arrays(result)?;
+        self.write_languages(result)?;
+        self.write_conversions(result)?;
+        self.stream.write_symbol(&self.natural8_usize_table, result.max_concept)?;
+        self.write_correlations(result)?;
+        self.write_correlation_arrays(result)?;
+        self.write_acceptations(result)?;
+        self.write_definitions(result)?;
+        Ok((self.stream.finish()?, self.table_sizes))
+    }
+}
+
+/// Writes the header and all sections of the given database.
+pub fn write_database<W: Write>(mut writer: W, result: &SdbReadResult) -> io::Result<W> {
+    writer.write_all(SDB_HEADER.as_bytes())?;
+    SdbWriter::new(OutputBitStream::new(writer)).write(result)
+}
+
+/// Writes the header and all sections of a database decoded from another SDB file. If
+/// reuse_tables is true, the tables found in that file are written again whenever they can encode
+/// all values, otherwise all tables are rebuilt from the values written. The size of each table
+/// with both choices is returned.
+pub fn reencode_database<W: Write>(mut writer: W, result: &SdbReadResult, reuse_tables: bool) -> io::Result<(W, Vec<TableSize>)> {
+    writer.write_all(SDB_HEADER.as_bytes())?;
+    SdbWriter::new(OutputBitStream::new(writer))
+        .with_original_tables(&result.tables, reuse_tables)
+        .write_with_table_sizes(result)
+}
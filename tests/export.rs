@@ -0,0 +1,23 @@
+#![cfg(feature = "export")]
+
+use langbook_sdb_dump::builder::SdbBuilder;
+use langbook_sdb_dump::export;
+use langbook_sdb_dump::sdb::{LanguageCode, SdbReadResult};
+
+fn japanese_with_conversions(empty_conversion: bool) -> SdbReadResult {
+    let mut builder = SdbBuilder::new();
+    let ja = builder.add_language(LanguageCode::try_from("ja").unwrap(), 2).unwrap();
+    builder.add_conversion(ja[0], ja[1], &[("犬", "いぬ")]);
+    if empty_conversion {
+        builder.add_conversion(ja[1], ja[0], &[]);
+    }
+
+    builder.build()
+}
+
+#[test]
+fn records_include_conversions_without_pairs() {
+    let records = export::export_records(&japanese_with_conversions(true));
+    assert!(records.lines().any(|line| line.starts_with("conversion ") && line.ends_with(" 0")));
+    assert_ne!(records, export::export_records(&japanese_with_conversions(false)));
+}
@@ -0,0 +1,58 @@
+#![cfg(feature = "std")]
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Read;
+use std::panic;
+use langbook_sdb_dump::builder::SdbBuilder;
+use langbook_sdb_dump::file_utils::{self, ReadError};
+use langbook_sdb_dump::huffman::InputBitStream;
+use langbook_sdb_dump::sdb::{DecodingMode, LanguageCode, SdbReader, SdbReadResult, SDB_HEADER};
+use langbook_sdb_dump::writer;
+
+fn read(bytes: &[u8], mode: DecodingMode) -> Result<SdbReadResult, ReadError> {
+    let mut input = bytes;
+    file_utils::read_header(&mut (&mut input).bytes(), SDB_HEADER)?;
+    SdbReader::new(InputBitStream::from(&mut input)).with_mode(mode).read()
+}
+
+/// Small database with content in every section: two languages, one of them with two alphabets
+/// converted into the other one.
+fn sample_database() -> Vec<u8> {
+    let mut builder = SdbBuilder::new();
+    let es = builder.add_language(LanguageCode::try_from("es").unwrap(), 1).unwrap();
+    let ja = builder.add_language(LanguageCode::try_from("ja").unwrap(), 2).unwrap();
+
+    let perro = builder.correlation(&BTreeMap::from([(es[0], "perro")]));
+    let inu = builder.correlation(&BTreeMap::from([(ja[0], "犬"), (ja[1], "いぬ")]));
+    let perro_array = builder.correlation_array(&[perro]);
+    let inu_array = builder.correlation_array(&[inu]);
+    builder.add_acceptation(1, perro_array).unwrap();
+    builder.add_acceptation(1, inu_array).unwrap();
+    builder.add_definition(2, 1, BTreeSet::from([3])).unwrap();
+    builder.add_conversion(ja[1], es[0], &[("い", "i"), ("ぬ", "nu")]);
+    writer::write_database(Vec::new(), &builder.build()).expect("Database should be writable")
+}
+
+#[test]
+fn sample_database_is_readable() {
+    assert!(read(&sample_database(), DecodingMode::Strict).is_ok());
+}
+
+#[test]
+fn flipped_bits_are_reported_without_panicking() {
+    let database = sample_database();
+    for mode in [DecodingMode::Strict, DecodingMode::Lenient] {
+        for bit in (SDB_HEADER.len() * 8)..(database.len() * 8) {
+            let mut corrupted = database.clone();
+            corrupted[bit / 8] ^= 1 << (bit % 8);
+            let result = panic::catch_unwind(|| read(&corrupted, mode).map(|result| {
+                // Every index decoded must be resolvable
+                for acceptation in &result.acceptations {
+                    result.get_complete_correlation(acceptation.correlation_array_index);
+                }
+            }));
+
+            assert!(result.is_ok(), "Decoding panicked in {:?} mode after flipping bit {}", mode, bit);
+        }
+    }
+}
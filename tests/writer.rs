@@ -0,0 +1,205 @@
+#![cfg(feature = "std")]
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Read;
+use langbook_sdb_dump::builder::SdbBuilder;
+use langbook_sdb_dump::file_utils;
+use langbook_sdb_dump::huffman::InputBitStream;
+use langbook_sdb_dump::sdb::{Acceptation, Alphabet, CorrelationArrayIndex, CorrelationIndex, Definition, Language, LanguageCode, SdbReader, SdbReadResult, SymbolArrayIndex, SymbolArrays, SDB_HEADER};
+use langbook_sdb_dump::writer;
+
+fn read(bytes: &[u8]) -> SdbReadResult {
+    let mut input = bytes;
+    file_utils::read_header(&mut (&mut input).bytes(), SDB_HEADER).expect("Header should be valid");
+    SdbReader::new(InputBitStream::from(&mut input)).read().expect("Written database should be readable")
+}
+
+fn round_trip(result: &SdbReadResult) -> SdbReadResult {
+    read(&writer::write_database(Vec::new(), result).expect("Database should be writable"))
+}
+
+fn texts(result: &SdbReadResult, indexes: impl Iterator<Item = SymbolArrayIndex>) -> Vec<&str> {
+    indexes.map(|index| result.symbol_array(index)).collect()
+}
+
+fn all_symbol_arrays(result: &SdbReadResult) -> Vec<&str> {
+    texts(result, (0..result.symbol_arrays.len()).map(SymbolArrayIndex::from))
+}
+
+/// Returns the alphabets and the texts of the pairs of each conversion, sorted.
+fn all_conversions(result: &SdbReadResult) -> Vec<(usize, usize, Vec<&str>)> {
+    let mut conversions: Vec<(usize, usize, Vec<&str>)> = result.conversions.iter().map(|conversion| {
+        let pairs = conversion.pairs.iter().flat_map(|(source, target)| [*source, *target]);
+        (conversion.source.index(), conversion.target.index(), texts(result, pairs))
+    }).collect();
+    conversions.sort();
+    conversions
+}
+
+/// Checks that both databases have the same content in every section.
+fn assert_same(expected: &SdbReadResult, actual: &SdbReadResult) {
+    assert_eq!(all_symbol_arrays(expected), all_symbol_arrays(actual));
+
+    let languages = |result: &SdbReadResult| result.languages.iter().map(|language| (language.code.to_string(), language.number_of_alphabets)).collect::<Vec<_>>();
+    assert_eq!(languages(expected), languages(actual));
+
+    assert_eq!(all_conversions(expected), all_conversions(actual));
+
+    assert_eq!(expected.max_concept, actual.max_concept);
+    assert_eq!(expected.correlations, actual.correlations);
+    assert_eq!(expected.correlation_arrays, actual.correlation_arrays);
+
+    let acceptations = |result: &SdbReadResult| result.acceptations.iter().map(|acceptation| (acceptation.concept, acceptation.correlation_array_index)).collect::<Vec<_>>();
+    assert_eq!(acceptations(expected), acceptations(actual));
+
+    let definitions = |result: &SdbReadResult| result.definitions.iter().map(|(concept, definition)| (*concept, definition.base_concept, definition.complements.clone())).collect::<Vec<_>>();
+    assert_eq!(definitions(expected), definitions(actual));
+}
+
+fn correlation(builder: &mut SdbBuilder, texts: &[(Alphabet, &str)]) -> CorrelationIndex {
+    builder.correlation(&texts.iter().copied().collect::<BTreeMap<Alphabet, &str>>())
+}
+
+/// Database with content in every section: Spanish with one alphabet, and Japanese with kanji
+/// and kana, converted into Latin script.
+fn sample_database() -> SdbReadResult {
+    let mut builder = SdbBuilder::new();
+    let es = builder.add_language(LanguageCode::try_from("es").unwrap(), 1).unwrap();
+    let ja = builder.add_language(LanguageCode::try_from("ja").unwrap(), 2).unwrap();
+
+    let animal = correlation(&mut builder, &[(es[0], "animal")]);
+    let perro = correlation(&mut builder, &[(es[0], "perro")]);
+    let gato = correlation(&mut builder, &[(es[0], "gato")]);
+    let inu = correlation(&mut builder, &[(ja[0], "犬"), (ja[1], "いぬ")]);
+    let ko = correlation(&mut builder, &[(ja[0], "子"), (ja[1], "こ")]);
+
+    let animal_array = builder.correlation_array(&[animal]);
+    let perro_array = builder.correlation_array(&[perro]);
+    let gato_array = builder.correlation_array(&[gato]);
+    let inu_array = builder.correlation_array(&[inu]);
+    let koinu_array = builder.correlation_array(&[ko, inu]);
+
+    builder.add_acceptation(1, animal_array).unwrap();
+    builder.add_acceptation(2, perro_array).unwrap();
+    builder.add_acceptation(2, inu_array).unwrap();
+    builder.add_acceptation(3, gato_array).unwrap();
+    builder.add_acceptation(4, koinu_array).unwrap();
+
+    builder.add_definition(2, 1, BTreeSet::new()).unwrap();
+    builder.add_definition(3, 1, BTreeSet::from([5])).unwrap();
+    builder.add_definition(4, 2, BTreeSet::from([1, 3])).unwrap();
+
+    builder.add_conversion(ja[1], es[0], &[("い", "i"), ("ぬ", "nu"), ("こ", "ko")]);
+    builder.add_conversion(ja[0], ja[1], &[("犬", "いぬ")]);
+    builder.build()
+}
+
+#[test]
+fn round_trip_keeps_every_section() {
+    let database = sample_database();
+    assert_same(&database, &round_trip(&database));
+}
+
+#[test]
+fn round_trip_keeps_an_empty_database() {
+    let database = SdbBuilder::new().build();
+    assert_same(&database, &round_trip(&database));
+}
+
+#[test]
+fn round_trip_keeps_conversions_without_pairs() {
+    let mut builder = SdbBuilder::new();
+    let ja = builder.add_language(LanguageCode::try_from("ja").unwrap(), 2).unwrap();
+    builder.symbol_array("いぬ");
+    builder.add_conversion(ja[1], ja[0], &[]);
+    builder.add_conversion(ja[0], ja[1], &[]);
+    let database = builder.build();
+    assert_same(&database, &round_trip(&database));
+}
+
+#[test]
+fn round_trip_keeps_definitions_at_the_concept_bounds() {
+    let mut builder = SdbBuilder::new();
+    builder.add_definition(1, 3, BTreeSet::from([3])).unwrap();
+    builder.add_definition(3, 1, BTreeSet::new()).unwrap();
+    builder.add_definition(2, 1, BTreeSet::from([1, 2])).unwrap();
+    let database = builder.build();
+    assert_same(&database, &round_trip(&database));
+}
+
+#[test]
+fn conversions_without_symbol_arrays_are_rejected() {
+    let mut builder = SdbBuilder::new();
+    let ja = builder.add_language(LanguageCode::try_from("ja").unwrap(), 2).unwrap();
+    builder.add_conversion(ja[0], ja[1], &[]);
+    assert!(writer::write_database(Vec::new(), &builder.build()).is_err());
+}
+
+#[test]
+fn duplicated_conversions_are_rejected() {
+    let mut builder = SdbBuilder::new();
+    let ja = builder.add_language(LanguageCode::try_from("ja").unwrap(), 2).unwrap();
+    builder.add_conversion(ja[0], ja[1], &[("犬", "いぬ")]);
+    builder.add_conversion(ja[0], ja[1], &[]);
+    assert!(writer::write_database(Vec::new(), &builder.build()).is_err());
+}
+
+#[test]
+fn correlations_without_symbol_arrays_are_rejected() {
+    let mut database = sample_database();
+    database.symbol_arrays = SymbolArrays::default();
+    assert!(writer::write_database(Vec::new(), &database).is_err());
+}
+
+#[test]
+fn more_definitions_than_concepts_are_rejected() {
+    let mut database = sample_database();
+    database.max_concept = 2;
+    assert!(writer::write_database(Vec::new(), &database).is_err());
+}
+
+#[test]
+fn complements_after_the_second_to_last_concept_are_rejected() {
+    let mut builder = SdbBuilder::new();
+    builder.add_definition(1, 2, BTreeSet::from([2, 3])).unwrap();
+    assert!(writer::write_database(Vec::new(), &builder.build()).is_err());
+}
+
+#[test]
+fn unsorted_languages_are_rejected() {
+    let mut database = SdbBuilder::new().build();
+    for code in ["zz", "aa"] {
+        database.languages.push(Language {
+            code: LanguageCode::try_from(code).unwrap(),
+            number_of_alphabets: 1
+        });
+    }
+
+    assert!(writer::write_database(Vec::new(), &database).is_err());
+}
+
+#[test]
+fn acceptations_after_the_last_correlation_array_are_rejected() {
+    // Appended to the set of the last concept, which then has more entries than correlation arrays
+    let mut database = sample_database();
+    let count = database.correlation_arrays.len();
+    for index in count..(2 * count) {
+        database.acceptations.push(Acceptation {
+            concept: 4,
+            correlation_array_index: CorrelationArrayIndex::from(index)
+        });
+    }
+
+    assert!(writer::write_database(Vec::new(), &database).is_err());
+}
+
+#[test]
+fn base_concepts_after_the_maximum_concept_are_rejected() {
+    let mut database = sample_database();
+    database.definitions.insert(1, Definition {
+        base_concept: database.max_concept + 1,
+        complements: BTreeSet::new()
+    });
+
+    assert!(writer::write_database(Vec::new(), &database).is_err());
+}